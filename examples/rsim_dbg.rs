@@ -0,0 +1,62 @@
+use crossbeam_channel::unbounded;
+use rsim_core::components::adder::Adder;
+use rsim_core::components::register::Register;
+use rsim_core::debugger::Debugger;
+use rsim_core::driver::RandomDriver;
+use rsim_core::error::SimError;
+use rsim_core::random::Distribution;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use std::sync::Arc;
+use std::thread;
+
+/// An accumulator summing random values, simulated under the command line debugger:
+/// ```text
+/// cargo run --example rsim_dbg
+/// ```
+/// Try `list`, `watch acc.output`, `break acc.output > 100`, `continue`, `back 3` or `help`.
+/// The driver sends 100 values before letting the simulation end, set `RSIM_SEED` to replay a run.
+fn main() -> Result<(), SimError> {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    // acc <= acc + driver
+    let mut driver_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut acc_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut sum = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let adder_a = acc_output.add_rx();
+    let adder_b = driver_output.add_rx();
+    let acc_data = sum.add_rx();
+
+    let driver = RandomDriver::from_distribution(
+        0,
+        sim_manager.clone(),
+        "driver",
+        driver_output,
+        ack_channel.0.clone(),
+        Distribution::range(0..=15)?,
+    )
+    .with_num_values(100)
+    .build()?;
+    let adder = Adder::new(1, sim_manager.clone(), "adder", adder_a, adder_b, sum)
+        .with_width(16)
+        .build()?;
+    let acc = Register::new(
+        2,
+        sim_manager.clone(),
+        "acc",
+        acc_data,
+        acc_output,
+        ack_channel.0.clone(),
+    )
+    .build()?;
+
+    let sim_dispatcher = SimDispatcher::new(Arc::downgrade(&sim_manager), vec![driver, adder, acc]);
+    sim_dispatcher.init();
+    sim_manager.enable_snapshots(4, 16)?;
+    thread::spawn(move || sim_dispatcher.run());
+
+    // the dispatcher thread is left running if the debugger quits before the end of the simulation
+    Debugger::new(sim_manager).run_stdio()
+}
@@ -0,0 +1,215 @@
+use crate::error::SimError;
+use crate::types::Cycle;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Width of the integers nested in a port value, the width of an integer port is the width of its type
+pub(crate) const NESTED_INTEGER_WIDTH: usize = 64;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitToggles {
+    pub name: String,
+    /// 0 to 1 transitions
+    pub rises: u64,
+    /// 1 to 0 transitions
+    pub falls: u64,
+}
+
+/// Activity of a single port since `SimManager::enable_activity_stats`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortActivity {
+    /// Values sent on an output port, or events received on an input port
+    pub events: u64,
+    /// Events that changed the value of the port
+    pub changes: u64,
+    /// Toggles of every bit of the value, see `get_bits`
+    pub bits: Vec<BitToggles>,
+}
+
+impl PortActivity {
+    pub fn get_toggles(&self) -> u64 {
+        self.bits.iter().map(|bit| bit.rises + bit.falls).sum()
+    }
+
+    /// Number of bits that rose and fell at least once
+    pub fn get_toggled_bits(&self) -> usize {
+        self.bits
+            .iter()
+            .filter(|bit| bit.rises > 0 && bit.falls > 0)
+            .count()
+    }
+}
+
+/// Counts the activity of a port, kept in the port state
+#[derive(Clone)]
+pub(crate) struct ActivityCounter<T> {
+    activity: PortActivity,
+    bit_indices: HashMap<String, usize>,
+    get_bits: fn(&T) -> Vec<(String, bool)>,
+}
+
+impl<T> ActivityCounter<T> {
+    /// Starts counting from the current value of the port, which lists the bits of the port
+    pub(crate) fn new(get_bits: fn(&T) -> Vec<(String, bool)>, value: &T) -> Self {
+        let mut counter = ActivityCounter {
+            activity: PortActivity::default(),
+            bit_indices: HashMap::new(),
+            get_bits,
+        };
+        for (name, _) in get_bits(value) {
+            counter.get_bit_index(&name);
+        }
+        counter
+    }
+
+    fn get_bit_index(&mut self, name: &str) -> usize {
+        *self.bit_indices.entry(name.to_string()).or_insert_with(|| {
+            self.activity.bits.push(BitToggles {
+                name: name.to_string(),
+                ..Default::default()
+            });
+            self.activity.bits.len() - 1
+        })
+    }
+
+    /// Records an event taking the port from `previous` to `value`
+    pub(crate) fn record(&mut self, previous: &T, value: &T, changed: bool) {
+        self.activity.events += 1;
+        if !changed {
+            return;
+        }
+        self.activity.changes += 1;
+
+        let previous: HashMap<String, bool> = (self.get_bits)(previous).into_iter().collect();
+        for (name, bit) in (self.get_bits)(value) {
+            let index = self.get_bit_index(&name);
+            let toggles = &mut self.activity.bits[index];
+            match (previous.get(&name).copied().unwrap_or(false), bit) {
+                (false, true) => toggles.rises += 1,
+                (true, false) => toggles.falls += 1,
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn get_activity(&self) -> PortActivity {
+        self.activity.clone()
+    }
+}
+
+/// Flattens a value into named bits through its serialized form.
+///
+/// Booleans are a single bit named after their field, integers are split into bits named `field[i]`,
+/// as wide as their type for an integer port and `NESTED_INTEGER_WIDTH` bits inside structures.
+/// Floats are split as `f64`, other values such as strings have no bits.
+pub(crate) fn get_bits<T: Serialize>(value: &T) -> Vec<(String, bool)> {
+    let mut bits = Vec::new();
+    if let Ok(value) = serde_json::to_value(value) {
+        let width = (std::mem::size_of::<T>() * 8).clamp(1, 128);
+        push_bits("", &value, width, &mut bits);
+    }
+    bits
+}
+
+fn push_bits(path: &str, value: &Value, width: usize, bits: &mut Vec<(String, bool)>) {
+    let join = |field: &str| {
+        if path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", path, field)
+        }
+    };
+    match value {
+        Value::Bool(bit) => {
+            let name = if path.is_empty() { "value" } else { path };
+            bits.push((name.to_string(), *bit))
+        }
+        Value::Number(number) => {
+            let raw = number
+                .as_u64()
+                .map(|value| value as u128)
+                .or(number.as_i64().map(|value| value as i128 as u128))
+                .or(number.as_f64().map(|value| value.to_bits() as u128))
+                .unwrap_or(0);
+            for i in 0..width {
+                bits.push((format!("{}[{}]", path, i), (raw >> i) & 1 == 1));
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                push_bits(&join(&i.to_string()), item, NESTED_INTEGER_WIDTH, bits);
+            }
+        }
+        Value::Object(fields) => {
+            for (name, field) in fields.iter() {
+                push_bits(&join(name), field, NESTED_INTEGER_WIDTH, bits);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Activity of every registered port, e.g. for toggle coverage or dynamic power estimates
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityReport {
+    /// Cycles simulated since `SimManager::enable_activity_stats`
+    pub cycles: Cycle,
+    pub ports: BTreeMap<String, PortActivity>,
+}
+
+impl ActivityReport {
+    pub fn get_toggles(&self) -> u64 {
+        self.ports.values().map(|port| port.get_toggles()).sum()
+    }
+
+    /// Fraction of the bits of every port that rose and fell at least once
+    pub fn get_toggle_coverage(&self) -> f64 {
+        let bits: usize = self.ports.values().map(|port| port.bits.len()).sum();
+        if bits == 0 {
+            return 0.0;
+        }
+        let toggled: usize = self
+            .ports
+            .values()
+            .map(|port| port.get_toggled_bits())
+            .sum();
+        toggled as f64 / bits as f64
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Display for ActivityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "activity over {} cycles, {} toggles, {:.2}% toggle coverage",
+            self.cycles,
+            self.get_toggles(),
+            self.get_toggle_coverage() * 100.0
+        )?;
+        for (name, port) in self.ports.iter() {
+            writeln!(
+                f,
+                "  {}: {} events, {} changes, {} toggles, {}/{} bits toggled",
+                name,
+                port.events,
+                port.changes,
+                port.get_toggles(),
+                port.get_toggled_bits(),
+                port.bits.len()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+use crate::types::Cycle;
+use crate::watchpoint::CompareOp;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+pub type AssertionId = usize;
+
+/// A condition on the values of named ports in a single cycle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Predicate {
+    /// The port value compares to the given value, see `CompareOp::holds`
+    Compare {
+        port: String,
+        op: CompareOp,
+        value: String,
+    },
+    Not(Box<Predicate>),
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+}
+
+impl Predicate {
+    pub fn compare(port: &str, op: CompareOp, value: &str) -> Self {
+        Predicate::Compare {
+            port: port.to_string(),
+            op,
+            value: value.to_string(),
+        }
+    }
+
+    /// The port is high, i.e. `true` or non-zero
+    pub fn high(port: &str) -> Self {
+        Predicate::compare(port, CompareOp::Ne, "0")
+    }
+
+    /// The port is low, i.e. `false` or zero
+    pub fn low(port: &str) -> Self {
+        Predicate::compare(port, CompareOp::Eq, "0")
+    }
+
+    /// Evaluates the predicate, `values` must hold every port in `Predicate::get_ports`
+    pub fn holds(&self, values: &BTreeMap<String, String>) -> bool {
+        match self {
+            Predicate::Compare { port, op, value } => values
+                .get(port)
+                .is_some_and(|port_value| op.holds(port_value, value)),
+            Predicate::Not(predicate) => !predicate.holds(values),
+            Predicate::And(predicates) => predicates.iter().all(|p| p.holds(values)),
+            Predicate::Or(predicates) => predicates.iter().any(|p| p.holds(values)),
+        }
+    }
+
+    pub fn get_ports(&self) -> Vec<String> {
+        match self {
+            Predicate::Compare { port, .. } => vec![port.clone()],
+            Predicate::Not(predicate) => predicate.get_ports(),
+            Predicate::And(predicates) | Predicate::Or(predicates) => {
+                predicates.iter().flat_map(|p| p.get_ports()).collect()
+            }
+        }
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let join = |predicates: &[Predicate], separator: &str| {
+            predicates
+                .iter()
+                .map(|p| format!("({})", p))
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self {
+            Predicate::Compare { port, op, value } => write!(f, "{} {} {}", port, op, value),
+            Predicate::Not(predicate) => write!(f, "!({})", predicate),
+            Predicate::And(predicates) => write!(f, "{}", join(predicates, " && ")),
+            Predicate::Or(predicates) => write!(f, "{}", join(predicates, " || ")),
+        }
+    }
+}
+
+/// A property checked on the port values at the end of every cycle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Property {
+    /// The predicate holds in every cycle
+    Always(Predicate),
+    /// The predicate holds in no cycle
+    Never(Predicate),
+    /// Whenever `antecedent` holds, `consequent` holds within `min_delay..=max_delay` cycles,
+    /// as `antecedent |-> ##[min_delay:max_delay] consequent` in SystemVerilog
+    Implies {
+        antecedent: Predicate,
+        consequent: Predicate,
+        min_delay: Cycle,
+        max_delay: Cycle,
+    },
+    /// Once `hold` holds, it keeps holding until the cycle `release` holds,
+    /// as `hold && !release |=> hold` in SystemVerilog
+    Until { hold: Predicate, release: Predicate },
+}
+
+impl Property {
+    pub fn get_ports(&self) -> Vec<String> {
+        let mut ports = match self {
+            Property::Always(predicate) | Property::Never(predicate) => predicate.get_ports(),
+            Property::Implies {
+                antecedent,
+                consequent,
+                ..
+            } => [antecedent.get_ports(), consequent.get_ports()].concat(),
+            Property::Until { hold, release } => [hold.get_ports(), release.get_ports()].concat(),
+        };
+        ports.sort();
+        ports.dedup();
+        ports
+    }
+}
+
+impl Display for Property {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Property::Always(predicate) => write!(f, "always {}", predicate),
+            Property::Never(predicate) => write!(f, "never {}", predicate),
+            Property::Implies {
+                antecedent,
+                consequent,
+                min_delay,
+                max_delay,
+            } => write!(
+                f,
+                "{} implies {} within {}..{} cycles",
+                antecedent, consequent, min_delay, max_delay
+            ),
+            Property::Until { hold, release } => write!(f, "{} until {}", hold, release),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssertionFailure {
+    pub id: AssertionId,
+    pub name: String,
+    /// The property, as displayed
+    pub property: String,
+    /// The cycle the property was found violated
+    pub cycle: Cycle,
+    /// The cycle the violated obligation started, e.g. when the antecedent of `Property::Implies` held
+    pub start_cycle: Cycle,
+    /// The values of the ports of the property in `cycle`
+    pub values: BTreeMap<String, String>,
+}
+
+impl Display for AssertionFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "assertion \"{}\" failed at cycle {} (started at cycle {}): {}",
+            self.name, self.cycle, self.start_cycle, self.property
+        )?;
+        for (port, value) in self.values.iter() {
+            write!(f, "\n  {} = {}", port, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Assertion {
+    pub id: AssertionId,
+    pub name: String,
+    pub property: Property,
+    /// Whether `SimManager::run` stops when the assertion fails
+    pub stop_on_failure: bool,
+    /// Start cycles of the obligations of a `Property::Implies` not yet met
+    pending: Vec<Cycle>,
+    /// Whether `hold && !release` in the previous cycle of a `Property::Until`, and since when
+    holding_since: Option<Cycle>,
+}
+
+impl Assertion {
+    /// Checks the property against the values at the end of `cycle`
+    fn check(&mut self, cycle: Cycle, values: &BTreeMap<String, String>) -> Vec<Cycle> {
+        match &self.property {
+            Property::Always(predicate) => {
+                if predicate.holds(values) {
+                    Vec::new()
+                } else {
+                    vec![cycle]
+                }
+            }
+            Property::Never(predicate) => {
+                if predicate.holds(values) {
+                    vec![cycle]
+                } else {
+                    Vec::new()
+                }
+            }
+            Property::Implies {
+                antecedent,
+                consequent,
+                min_delay,
+                max_delay,
+            } => {
+                if antecedent.holds(values) {
+                    self.pending.push(cycle);
+                }
+                let consequent_holds = consequent.holds(values);
+                let mut failed = Vec::new();
+                self.pending.retain(|start| {
+                    let delay = cycle - start;
+                    if delay >= *min_delay && consequent_holds {
+                        false
+                    } else if delay >= *max_delay {
+                        failed.push(*start);
+                        false
+                    } else {
+                        true
+                    }
+                });
+                failed
+            }
+            Property::Until { hold, release } => {
+                let hold_holds = hold.holds(values);
+                let failed = match self.holding_since {
+                    Some(start) if !hold_holds => vec![start],
+                    _ => Vec::new(),
+                };
+                self.holding_since = if hold_holds && !release.holds(values) {
+                    self.holding_since.or(Some(cycle))
+                } else {
+                    None
+                };
+                failed
+            }
+        }
+    }
+}
+
+/// The obligations not yet met and the number of failures at some cycle, see `AssertionChecker::save`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AssertionState {
+    windows: Vec<(AssertionId, Vec<Cycle>, Option<Cycle>)>,
+    failures: usize,
+}
+
+/// The assertions of a simulation and their failures so far
+#[derive(Debug, Default)]
+pub struct AssertionChecker {
+    assertions: Vec<Assertion>,
+    failures: Vec<AssertionFailure>,
+    /// Failures of assertions with `stop_on_failure` not yet returned by `AssertionChecker::take_stop`
+    stop: Vec<AssertionFailure>,
+}
+
+impl AssertionChecker {
+    pub fn add(&mut self, name: &str, property: Property, stop_on_failure: bool) -> AssertionId {
+        let id = self.assertions.len() + 1;
+        self.assertions.push(Assertion {
+            id,
+            name: name.to_string(),
+            property,
+            stop_on_failure,
+            pending: Vec::new(),
+            holding_since: None,
+        });
+        id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assertions.is_empty()
+    }
+
+    /// Every port used by an assertion
+    pub fn get_ports(&self) -> Vec<String> {
+        let mut ports: Vec<String> = self
+            .assertions
+            .iter()
+            .flat_map(|assertion| assertion.property.get_ports())
+            .collect();
+        ports.sort();
+        ports.dedup();
+        ports
+    }
+
+    pub fn get_assertions(&self) -> &[Assertion] {
+        &self.assertions
+    }
+
+    /// Checks every assertion against the port values at the end of `cycle`
+    pub fn check(&mut self, cycle: Cycle, values: &BTreeMap<String, String>) {
+        for assertion in self.assertions.iter_mut() {
+            for start_cycle in assertion.check(cycle, values) {
+                let failure = AssertionFailure {
+                    id: assertion.id,
+                    name: assertion.name.clone(),
+                    property: assertion.property.to_string(),
+                    cycle,
+                    start_cycle,
+                    values: assertion
+                        .property
+                        .get_ports()
+                        .into_iter()
+                        .filter_map(|port| values.get(&port).map(|value| (port, value.clone())))
+                        .collect(),
+                };
+                if assertion.stop_on_failure {
+                    self.stop.push(failure.clone());
+                }
+                self.failures.push(failure);
+            }
+        }
+    }
+
+    pub fn get_failures(&self) -> &[AssertionFailure] {
+        &self.failures
+    }
+
+    /// Returns the failures that should stop the simulation since the last call
+    pub fn take_stop(&mut self) -> Vec<AssertionFailure> {
+        std::mem::take(&mut self.stop)
+    }
+
+    pub(crate) fn save(&self) -> AssertionState {
+        AssertionState {
+            windows: self
+                .assertions
+                .iter()
+                .map(|assertion| {
+                    (
+                        assertion.id,
+                        assertion.pending.clone(),
+                        assertion.holding_since,
+                    )
+                })
+                .collect(),
+            failures: self.failures.len(),
+        }
+    }
+
+    /// Brings the assertions back to `state`, the ones added since have no obligation pending
+    pub(crate) fn restore(&mut self, state: &AssertionState) {
+        for assertion in self.assertions.iter_mut() {
+            let window = state.windows.iter().find(|(id, _, _)| *id == assertion.id);
+            assertion.pending = window
+                .map(|(_, pending, _)| pending.clone())
+                .unwrap_or_default();
+            assertion.holding_since = window.and_then(|(_, _, holding_since)| *holding_since);
+        }
+        self.failures.truncate(state.failures);
+        self.stop.clear();
+    }
+}
@@ -0,0 +1,299 @@
+use crate::error::SimError;
+use crate::event::{Event, EventValue, ValueEvent};
+use crate::rx::Rx;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::{ComponentId, Cycle, EventId};
+use crossbeam_channel::Sender;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// The forward signals of a ready/valid channel, `data` is only meaningful when `valid` is set
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Beat<T> {
+    pub valid: bool,
+    pub data: T,
+}
+
+impl<T: Default> Beat<T> {
+    pub fn new(data: T) -> Self {
+        Beat { valid: true, data }
+    }
+
+    pub fn idle() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: Copy + Send + Sync + Debug + 'static> EventValue for Beat<T> {
+    fn build_event(&self, event_id: EventId, scheduled_time: Cycle) -> Box<dyn Event> {
+        Box::new(ValueEvent::new(scheduled_time, *self, event_id))
+    }
+}
+
+/// Builds the two ends of a ready/valid channel called `name`, the name its protocol violations are reported under.
+///
+/// A value is transferred at a clock edge when `valid` and `ready` were both set at the end of the previous cycle.
+/// Both signals are sent with no delay, so each end must call `poll` from `Component::poll_recv`
+/// and `tick` at the start of every clock tick, before `can_send`, `send`, `peek` or `take`.
+pub fn ready_valid<
+    T: Default + Copy + Send + Sync + PartialEq + Debug + Serialize + DeserializeOwned + 'static,
+>(
+    sim_manager: Arc<SimManager>,
+    ack_sender: Sender<EventId>,
+    name: &str,
+) -> (ReadyValidTx<T>, ReadyValidRx<T>) {
+    let mut beat = Tx::new(sim_manager.clone(), ack_sender.clone());
+    let mut ready = Tx::new(sim_manager.clone(), ack_sender);
+    let beat_rx = beat.add_rx();
+    let ready_rx = ready.add_rx();
+    (
+        ReadyValidTx::new(beat, ready_rx),
+        ReadyValidRx::new(sim_manager, name, beat_rx, ready),
+    )
+}
+
+/// The sending end of a ready/valid channel.
+///
+/// Once sent, a value is held with `valid` set until it is transferred, `send` refusing any other value meanwhile.
+pub struct ReadyValidTx<
+    T: Default + Copy + Send + Sync + PartialEq + Debug + Serialize + DeserializeOwned + 'static,
+> {
+    beat: Tx<Beat<T>>,
+    ready: Rx<bool>,
+    /// The value offered, not transferred yet
+    pending: Option<T>,
+    transfer_count: u64,
+}
+
+impl<
+        T: Default + Copy + Send + Sync + PartialEq + Debug + Serialize + DeserializeOwned + 'static,
+    > ReadyValidTx<T>
+{
+    pub fn new(beat: Tx<Beat<T>>, ready: Rx<bool>) -> Self {
+        ReadyValidTx {
+            beat,
+            ready,
+            pending: None,
+            transfer_count: 0,
+        }
+    }
+
+    /// Registers the forward signals as `name` and `ready` as `<name>_ready`
+    pub fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        sim_manager.register_port(component_id, name, &self.beat)?;
+        sim_manager.register_port(component_id, &format!("{}_ready", name), &self.ready)
+    }
+
+    /// Receives `ready`, to be called from `Component::poll_recv`
+    pub fn poll(&mut self) {
+        self.ready.drain();
+    }
+
+    /// Handles the clock edge, returning whether the value offered was transferred
+    pub fn tick(&mut self) -> bool {
+        if self.pending.is_none() || !self.ready.get_value() {
+            return false;
+        }
+        self.pending = None;
+        self.transfer_count += 1;
+        self.beat.send(Beat::idle(), 0);
+        true
+    }
+
+    /// Whether no value is waiting to be transferred
+    pub fn can_send(&self) -> bool {
+        self.pending.is_none()
+    }
+
+    /// Offers `value` from this cycle on, it fails if the previous value has not been transferred yet
+    pub fn send(&mut self, value: T) -> Result<(), SimError> {
+        if let Some(pending) = self.pending {
+            return Err(SimError::ProtocolError(format!(
+                "cannot send {:?}, {:?} has not been transferred yet",
+                value, pending
+            )));
+        }
+        self.pending = Some(value);
+        self.beat.send(Beat::new(value), 0);
+        Ok(())
+    }
+
+    pub fn get_transfer_count(&self) -> u64 {
+        self.transfer_count
+    }
+
+    pub fn reset(&mut self) {
+        self.pending = None;
+        self.transfer_count = 0;
+        self.ready.reset();
+    }
+
+    /// The state of the end, for `Component::save_state`
+    pub fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "pending": self.pending,
+            "transfer_count": self.transfer_count,
+        }))
+    }
+
+    pub fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.pending = serde_json::from_value(state["pending"].clone())?;
+        self.transfer_count = serde_json::from_value(state["transfer_count"].clone())?;
+        Ok(())
+    }
+}
+
+/// The receiving end of a ready/valid channel, with a buffer of one value.
+///
+/// `ready` is set while the buffer is empty. The end also checks the sending end, as it may be a plain `Tx<Beat<T>>`:
+/// a value offered but not transferred must stay offered, unchanged, until it is.
+/// Violations are reported with `SimManager::report_violation`.
+pub struct ReadyValidRx<
+    T: Default + Copy + Send + Sync + PartialEq + Debug + Serialize + DeserializeOwned + 'static,
+> {
+    sim_manager: Arc<SimManager>,
+    name: String,
+    beat: Rx<Beat<T>>,
+    ready: Tx<bool>,
+    ready_driven: bool,
+    /// The value transferred, not taken yet
+    buffer: Option<T>,
+    /// The forward signals at the previous clock edge, and whether they were transferred
+    last_beat: Beat<T>,
+    last_transfer: bool,
+    transfer_count: u64,
+}
+
+impl<
+        T: Default + Copy + Send + Sync + PartialEq + Debug + Serialize + DeserializeOwned + 'static,
+    > ReadyValidRx<T>
+{
+    /// The end of the channel called `name` made of existing ports, e.g. to check a sender not using `ReadyValidTx`
+    pub fn new(
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        beat: Rx<Beat<T>>,
+        ready: Tx<bool>,
+    ) -> Self {
+        ReadyValidRx {
+            sim_manager,
+            name: name.to_string(),
+            beat,
+            ready,
+            ready_driven: false,
+            buffer: None,
+            last_beat: Beat::idle(),
+            last_transfer: false,
+            transfer_count: 0,
+        }
+    }
+
+    /// Registers the forward signals as `name` and `ready` as `<name>_ready`
+    pub fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        sim_manager.register_port(component_id, name, &self.beat)?;
+        sim_manager.register_port(component_id, &format!("{}_ready", name), &self.ready)
+    }
+
+    /// Receives the forward signals, to be called from `Component::poll_recv`
+    pub fn poll(&mut self) {
+        self.beat.drain();
+    }
+
+    /// Handles the clock edge, returning whether a value was transferred into the buffer
+    pub fn tick(&mut self) -> bool {
+        let beat = self.beat.get_value();
+        if self.last_beat.valid && !self.last_transfer && beat != self.last_beat {
+            let message = if beat.valid {
+                format!(
+                    "data changed from {:?} to {:?} without a transfer",
+                    self.last_beat.data, beat.data
+                )
+            } else {
+                format!(
+                    "valid dropped without a transfer of {:?}",
+                    self.last_beat.data
+                )
+            };
+            self.sim_manager
+                .report_violation(&self.name, message)
+                .unwrap();
+        }
+
+        let transfer = beat.valid && self.ready_driven;
+        if transfer {
+            self.buffer = Some(beat.data);
+            self.transfer_count += 1;
+        }
+        self.last_beat = beat;
+        self.last_transfer = transfer;
+        self.drive_ready();
+        transfer
+    }
+
+    fn drive_ready(&mut self) {
+        let ready = self.buffer.is_none();
+        if ready != self.ready_driven {
+            self.ready_driven = ready;
+            self.ready.send(ready, 0);
+        }
+    }
+
+    /// The value transferred, if not taken yet
+    pub fn peek(&self) -> Option<T> {
+        self.buffer
+    }
+
+    /// Takes the value transferred, if any, which sets `ready` for the next one
+    pub fn take(&mut self) -> Option<T> {
+        let value = self.buffer.take();
+        self.drive_ready();
+        value
+    }
+
+    pub fn get_transfer_count(&self) -> u64 {
+        self.transfer_count
+    }
+
+    pub fn reset(&mut self) {
+        self.ready_driven = false;
+        self.buffer = None;
+        self.last_beat = Beat::idle();
+        self.last_transfer = false;
+        self.transfer_count = 0;
+        self.beat.reset();
+    }
+
+    /// The state of the end, for `Component::save_state`
+    pub fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "ready_driven": self.ready_driven,
+            "buffer": self.buffer,
+            "last_beat": self.last_beat,
+            "last_transfer": self.last_transfer,
+            "transfer_count": self.transfer_count,
+        }))
+    }
+
+    pub fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.ready_driven = serde_json::from_value(state["ready_driven"].clone())?;
+        self.buffer = serde_json::from_value(state["buffer"].clone())?;
+        self.last_beat = serde_json::from_value(state["last_beat"].clone())?;
+        self.last_transfer = serde_json::from_value(state["last_transfer"].clone())?;
+        self.transfer_count = serde_json::from_value(state["transfer_count"].clone())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use crate::assertion::AssertionState;
+use crate::coverage::GroupCoverage;
+use crate::error::SimError;
+use crate::fault::FaultState;
+use crate::stats::SimStats;
+use crate::trace::SerializedEvent;
+use crate::types::{ComponentId, Cycle, EventId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// A `Checkpoint` is the full state of a simulation at a cycle boundary, see `SimManager::checkpoint`.
+///
+/// Ports and components are identified by their registered name,
+/// so a checkpoint can be restored into a freshly built netlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub cycle: Cycle,
+    pub next_event_id: EventId,
+    pub event_processed: u128,
+    pub do_not_end: Vec<ComponentId>,
+    /// The last cycle of the events replayed so far, see `SimManager::replay`
+    #[serde(default)]
+    pub replay_end: Cycle,
+    /// The statistics and counters so far, see `SimManager::get_stats`
+    #[serde(default)]
+    pub stats: SimStats,
+    pub pending_events: Vec<SerializedEvent>,
+    pub ports: BTreeMap<String, Value>,
+    pub components: BTreeMap<String, Value>,
+}
+
+impl Checkpoint {
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Checkpoint, SimError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// What the assertions, the covergroups and the faults had collected when a snapshot was taken,
+/// restored along with it by `SimManager::goto_cycle` so that the re-executed cycles are not counted twice
+#[derive(Debug, Clone, Default)]
+pub(crate) struct VerificationState {
+    pub(crate) assertions: AssertionState,
+    pub(crate) coverage: BTreeMap<String, GroupCoverage>,
+    pub(crate) faults: FaultState,
+}
+
+/// A `SnapshotStore` keeps the in-memory checkpoints taken every `interval` cycles,
+/// which `SimManager::goto_cycle` restores from before re-executing up to the requested cycle
+#[derive(Debug, Default)]
+pub struct SnapshotStore {
+    interval: Option<Cycle>,
+    capacity: usize,
+    snapshots: BTreeMap<Cycle, (Checkpoint, VerificationState)>,
+}
+
+impl SnapshotStore {
+    /// Takes a snapshot every `interval` cycles, keeping the latest `capacity` of them
+    pub fn enable(&mut self, interval: Cycle, capacity: usize) {
+        self.interval = Some(interval.max(1));
+        self.capacity = capacity.max(1);
+    }
+
+    pub fn disable(&mut self) {
+        self.interval = None;
+        self.snapshots.clear();
+    }
+
+    pub fn is_due(&self, cycle: Cycle) -> bool {
+        self.interval.is_some_and(|interval| {
+            cycle.is_multiple_of(interval) && !self.snapshots.contains_key(&cycle)
+        })
+    }
+
+    pub(crate) fn insert(&mut self, checkpoint: Checkpoint, verification: VerificationState) {
+        self.snapshots
+            .insert(checkpoint.cycle, (checkpoint, verification));
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_first();
+        }
+    }
+
+    /// Returns the latest snapshot taken at or before `cycle`
+    pub(crate) fn get_latest_before(
+        &self,
+        cycle: Cycle,
+    ) -> Option<&(Checkpoint, VerificationState)> {
+        self.snapshots
+            .range(..=cycle)
+            .next_back()
+            .map(|(_, snapshot)| snapshot)
+    }
+
+    /// Drops the snapshots after `cycle`, they may not hold anymore once the past is changed
+    pub fn truncate_after(&mut self, cycle: Cycle) {
+        let _ = self.snapshots.split_off(&(cycle + 1));
+    }
+
+    pub fn get_cycles(&self) -> Vec<Cycle> {
+        self.snapshots.keys().copied().collect()
+    }
+}
@@ -1,4 +1,6 @@
+use crate::error::SimError;
 use crate::types::ComponentId;
+use serde_json::Value;
 
 pub trait Component: Send + Sync {
     /// `init` is called prior to the start of the simulation.
@@ -13,4 +15,22 @@ pub trait Component: Send + Sync {
     fn poll_recv(&mut self);
 
     fn get_component_id(&self) -> ComponentId;
+
+    /// `save_state` is called when the simulation manager takes a checkpoint, see `SimManager::checkpoint`.
+    /// Registered ports are saved by the simulation manager,
+    /// so only the internal state of the component needs to be returned.
+    ///
+    /// Checkpoints need every component to be registered with `SimManager::register_component`
+    /// and every input port with `SimManager::register_port`: a checkpoint cannot be taken
+    /// while an event is pending on an unregistered port, and unregistered components are not saved.
+    /// A component keeping state besides its ports must implement both `save_state` and `restore_state`,
+    /// the defaults save nothing and a restored simulation would silently differ.
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(Value::Null)
+    }
+
+    /// `restore_state` is given back what `save_state` returned, see `SimManager::restore`
+    fn restore_state(&mut self, _state: Value) -> Result<(), SimError> {
+        Ok(())
+    }
 }
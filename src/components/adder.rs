@@ -0,0 +1,163 @@
+use crate::component::Component;
+use crate::components::{check_width, get_mask};
+use crate::error::SimError;
+use crate::rx::Rx;
+use crate::rx::RxType::NewValue;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::ComponentId;
+use std::sync::{Arc, Mutex};
+
+/// An adder/subtractor of `width` bit values, sending `a + b`, or `a - b` when `subtract` is given and set.
+///
+/// `carry` is the carry out of the adder, computing `a - b` as `a + !b + 1`, so it is set when a subtraction does not borrow.
+/// `overflow` is set when the result does not fit as a signed value.
+pub struct Adder {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    width: u32,
+    a: Rx<u64>,
+    b: Rx<u64>,
+    subtract: Option<Rx<bool>>,
+    output: Tx<u64>,
+    carry: Option<Tx<bool>>,
+    overflow: Option<Tx<bool>>,
+}
+
+impl Adder {
+    /// A 64 bit adder by default
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        a: Rx<u64>,
+        b: Rx<u64>,
+        output: Tx<u64>,
+    ) -> Self {
+        Adder {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            width: u64::BITS,
+            a,
+            b,
+            subtract: None,
+            output,
+            carry: None,
+            overflow: None,
+        }
+    }
+
+    pub fn with_width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_subtract(mut self, subtract: Rx<bool>) -> Self {
+        self.subtract = Some(subtract);
+        self
+    }
+
+    pub fn with_carry(mut self, carry: Tx<bool>) -> Self {
+        self.carry = Some(carry);
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: Tx<bool>) -> Self {
+        self.overflow = Some(overflow);
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_width(&self.name, self.width)?;
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn on_comb(&mut self) {
+        let mask = get_mask(self.width);
+        let subtract = self
+            .subtract
+            .as_ref()
+            .is_some_and(|subtract| subtract.get_value());
+        let a = self.a.get_value() & mask;
+        let b = if subtract {
+            !self.b.get_value() & mask
+        } else {
+            self.b.get_value() & mask
+        };
+        let full = a as u128 + b as u128 + subtract as u128;
+        let sum = full as u64 & mask;
+        self.output.send(sum, 0);
+
+        if let Some(carry) = self.carry.as_mut() {
+            carry.send(full >> self.width != 0, 0);
+        }
+        if let Some(overflow) = self.overflow.as_mut() {
+            // the operands have the same sign and the result does not
+            let sign = 1 << (self.width - 1);
+            overflow.send((a ^ sum) & (b ^ sum) & sign != 0, 0);
+        }
+    }
+}
+
+impl Component for Adder {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "a", &self.a)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "b", &self.b)
+            .unwrap();
+        if let Some(subtract) = self.subtract.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "subtract", subtract)
+                .unwrap();
+        }
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        if let Some(carry) = self.carry.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "carry", carry)
+                .unwrap();
+        }
+        if let Some(overflow) = self.overflow.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "overflow", overflow)
+                .unwrap();
+        }
+        self.on_comb();
+    }
+
+    fn reset(&mut self) {
+        self.a.reset();
+        self.b.reset();
+        if let Some(subtract) = self.subtract.as_mut() {
+            subtract.reset();
+        }
+    }
+
+    fn poll_recv(&mut self) {
+        let mut changed = self.a.try_recv() == NewValue;
+        changed |= self.b.try_recv() == NewValue;
+        if let Some(subtract) = self.subtract.as_mut() {
+            changed |= subtract.try_recv() == NewValue;
+        }
+        if changed {
+            self.on_comb();
+        }
+        self.a.ack();
+        self.b.ack();
+        if let Some(subtract) = self.subtract.as_mut() {
+            subtract.ack();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
@@ -0,0 +1,507 @@
+use crate::component::Component;
+use crate::components::bus::{check_ranges, decode, drive, AddressRange, Arbiter, Arbitration};
+use crate::components::poll_clocked;
+use crate::error::SimError;
+use crate::event::{Event, EventValue, ValueEvent};
+use crate::rx::Rx;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::{ComponentId, Cycle, EventId, Input, Output};
+use crossbeam_channel::{unbounded, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// The signals an APB manager drives: `PSEL`, `PENABLE`, `PWRITE`, `PADDR`, `PWDATA` and `PSTRB`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApbRequest {
+    pub sel: bool,
+    pub enable: bool,
+    pub write: bool,
+    pub addr: u64,
+    pub wdata: u64,
+    pub strb: u8,
+}
+
+impl ApbRequest {
+    /// The setup phase of a read
+    pub fn read(addr: u64) -> Self {
+        ApbRequest {
+            sel: true,
+            addr,
+            ..Default::default()
+        }
+    }
+
+    /// The setup phase of a write
+    pub fn write(addr: u64, wdata: u64, strb: u8) -> Self {
+        ApbRequest {
+            sel: true,
+            write: true,
+            addr,
+            wdata,
+            strb,
+            ..Default::default()
+        }
+    }
+
+    /// The access phase following the setup phase `self`
+    pub fn access(self) -> Self {
+        ApbRequest {
+            enable: true,
+            ..self
+        }
+    }
+
+    /// Whether the signals that must hold during a transfer are the same
+    fn is_same_transfer(&self, other: &ApbRequest) -> bool {
+        (self.write, self.addr, self.wdata, self.strb)
+            == (other.write, other.addr, other.wdata, other.strb)
+    }
+}
+
+impl EventValue for ApbRequest {
+    fn build_event(&self, event_id: EventId, scheduled_time: Cycle) -> Box<dyn Event> {
+        Box::new(ValueEvent::new(scheduled_time, *self, event_id))
+    }
+}
+
+/// The signals an APB subordinate drives: `PREADY`, `PRDATA` and `PSLVERR`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApbResponse {
+    pub ready: bool,
+    pub rdata: u64,
+    pub slverr: bool,
+}
+
+impl EventValue for ApbResponse {
+    fn build_event(&self, event_id: EventId, scheduled_time: Cycle) -> Box<dyn Event> {
+        Box::new(ValueEvent::new(scheduled_time, *self, event_id))
+    }
+}
+
+/// Checks an APB bus called `name`, the name its violations are reported under,
+/// see `SimManager::report_violation`.
+///
+/// `enable` needs `sel`, a setup phase must be followed by an access phase,
+/// and the request must not change until `ready` completes the transfer.
+pub struct ApbChecker {
+    sim_manager: Arc<SimManager>,
+    name: String,
+    last_request: ApbRequest,
+    last_response: ApbResponse,
+    transfer_count: u64,
+}
+
+impl ApbChecker {
+    pub fn new(sim_manager: Arc<SimManager>, name: &str) -> Self {
+        ApbChecker {
+            sim_manager,
+            name: name.to_string(),
+            last_request: ApbRequest::default(),
+            last_response: ApbResponse::default(),
+            transfer_count: 0,
+        }
+    }
+
+    /// Checks the signals of a cycle, to be called at every clock tick with the signals of the cycle before.
+    /// Returns whether a transfer completed in the cycle.
+    pub fn check(&mut self, request: ApbRequest, response: ApbResponse) -> bool {
+        let last = self.last_request;
+        // a setup phase or an access phase not completed
+        let in_transfer = last.sel && (!last.enable || !self.last_response.ready);
+        let message = if request.enable && !request.sel {
+            Some("enable set without sel".to_string())
+        } else if in_transfer && !request.enable {
+            Some(if last.enable {
+                format!("access phase of {:?} dropped before ready", last)
+            } else {
+                format!("setup phase of {:?} not followed by an access phase", last)
+            })
+        } else if in_transfer && !request.is_same_transfer(&last) {
+            Some(format!(
+                "request changed from {:?} to {:?} during a transfer",
+                last, request
+            ))
+        } else if !in_transfer && request.enable {
+            Some(format!(
+                "access phase of {:?} without a setup phase",
+                request
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            self.sim_manager
+                .report_violation(&self.name, message)
+                .unwrap();
+        }
+
+        self.last_request = request;
+        self.last_response = response;
+        let transfer = request.sel && request.enable && response.ready;
+        if transfer {
+            self.transfer_count += 1;
+        }
+        transfer
+    }
+
+    pub fn get_transfer_count(&self) -> u64 {
+        self.transfer_count
+    }
+
+    pub fn reset(&mut self) {
+        self.last_request = ApbRequest::default();
+        self.last_response = ApbResponse::default();
+        self.transfer_count = 0;
+    }
+
+    /// The state of the checker, for `Component::save_state`
+    pub fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "last_request": self.last_request,
+            "last_response": self.last_response,
+            "transfer_count": self.transfer_count,
+        }))
+    }
+
+    pub fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.last_request = serde_json::from_value(state["last_request"].clone())?;
+        self.last_response = serde_json::from_value(state["last_response"].clone())?;
+        self.transfer_count = serde_json::from_value(state["transfer_count"].clone())?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum ManagerState {
+    #[default]
+    Idle,
+    /// The transfer waits for the subordinate to be granted
+    Waiting(usize),
+    /// The transfer is issued to the subordinate
+    Granted,
+    /// The response is driven
+    Responding,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum SubordinateState {
+    #[default]
+    Idle,
+    /// The setup phase of a transfer of a manager is driven
+    Setup(usize),
+    /// The access phase of a transfer of a manager is driven
+    Access(usize),
+}
+
+struct ManagerPort {
+    request: Rx<ApbRequest>,
+    response: Tx<ApbResponse>,
+    checker: ApbChecker,
+    state: ManagerState,
+}
+
+struct SubordinatePort {
+    request: Tx<ApbRequest>,
+    response: Rx<ApbResponse>,
+    checker: ApbChecker,
+    arbiter: Arbiter,
+    state: SubordinateState,
+}
+
+/// An APB interconnect between any number of managers and subordinates.
+///
+/// The interconnect is a registered bridge, a subordinate to each manager and a manager to each subordinate.
+/// The setup phase of a manager is decoded to the subordinate whose range holds `addr`,
+/// granted to one manager at a time, see `Arbitration`.
+/// The transfer is then issued to the subordinate and its response returned to the manager,
+/// which sees 2 wait states more than the subordinate adds.
+/// Transfers to addresses no subordinate answers to complete after no wait state with `slverr` set.
+///
+/// Every bus is checked by an `ApbChecker` named `<name>.manager<i>` or `<name>.subordinate<i>`.
+/// The ports are registered as `manager<i>_request`, `manager<i>_response`,
+/// `subordinate<i>_request` and `subordinate<i>_response`.
+pub struct ApbInterconnect {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    arbitration: Arbitration,
+    managers: Vec<ManagerPort>,
+    subordinates: Vec<SubordinatePort>,
+    ranges: Vec<AddressRange>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl ApbInterconnect {
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        ack_sender: Sender<EventId>,
+    ) -> Self {
+        let clock_tick_channel = unbounded();
+        ApbInterconnect {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            arbitration: Arbitration::default(),
+            managers: Vec::new(),
+            subordinates: Vec::new(),
+            ranges: Vec::new(),
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }
+    }
+
+    pub fn with_arbitration(mut self, arbitration: Arbitration) -> Self {
+        self.arbitration = arbitration;
+        self
+    }
+
+    pub fn with_manager(mut self, request: Rx<ApbRequest>, response: Tx<ApbResponse>) -> Self {
+        let checker = ApbChecker::new(
+            self.sim_manager.clone(),
+            &format!("{}.manager{}", self.name, self.managers.len()),
+        );
+        self.managers.push(ManagerPort {
+            request,
+            response,
+            checker,
+            state: ManagerState::default(),
+        });
+        self
+    }
+
+    pub fn with_subordinate(
+        mut self,
+        range: AddressRange,
+        request: Tx<ApbRequest>,
+        response: Rx<ApbResponse>,
+    ) -> Self {
+        let checker = ApbChecker::new(
+            self.sim_manager.clone(),
+            &format!("{}.subordinate{}", self.name, self.subordinates.len()),
+        );
+        self.subordinates.push(SubordinatePort {
+            request,
+            response,
+            checker,
+            arbiter: Arbiter::default(),
+            state: SubordinateState::default(),
+        });
+        self.ranges.push(range);
+        self
+    }
+
+    /// Fails if the address ranges of the subordinates overlap
+    pub fn build(mut self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_ranges(&self.name, &self.ranges)?;
+        for subordinate in self.subordinates.iter_mut() {
+            subordinate.arbiter = Arbiter::new(self.arbitration);
+        }
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn drain_inputs(&mut self) {
+        for manager in self.managers.iter_mut() {
+            manager.request.drain();
+        }
+        for subordinate in self.subordinates.iter_mut() {
+            subordinate.response.drain();
+        }
+    }
+
+    fn on_clock(&mut self) {
+        for manager in self.managers.iter_mut() {
+            let request = manager.request.get_value();
+            let response = manager.response.get_value();
+            manager.checker.check(request, response);
+            manager.state = match manager.state {
+                // the manager saw the response in the cycle before
+                ManagerState::Responding => {
+                    drive(&mut manager.response, ApbResponse::default());
+                    ManagerState::Idle
+                }
+                ManagerState::Idle if request.sel && !request.enable => {
+                    match decode(&self.ranges, request.addr) {
+                        Some(subordinate) => ManagerState::Waiting(subordinate),
+                        None => {
+                            let response = ApbResponse {
+                                ready: true,
+                                rdata: 0,
+                                slverr: true,
+                            };
+                            drive(&mut manager.response, response);
+                            ManagerState::Responding
+                        }
+                    }
+                }
+                state => state,
+            };
+        }
+
+        for (index, subordinate) in self.subordinates.iter_mut().enumerate() {
+            let request = subordinate.request.get_value();
+            let response = subordinate.response.get_value();
+            let transfer = subordinate.checker.check(request, response);
+            let mut next_request = request;
+            subordinate.state = match subordinate.state {
+                SubordinateState::Setup(manager) => {
+                    next_request.enable = true;
+                    SubordinateState::Access(manager)
+                }
+                SubordinateState::Access(manager) if transfer => {
+                    let manager = &mut self.managers[manager];
+                    drive(&mut manager.response, response);
+                    manager.state = ManagerState::Responding;
+                    next_request = ApbRequest::default();
+                    SubordinateState::Idle
+                }
+                state => state,
+            };
+
+            if subordinate.state == SubordinateState::Idle {
+                let requests: Vec<bool> = self
+                    .managers
+                    .iter()
+                    .map(|manager| manager.state == ManagerState::Waiting(index))
+                    .collect();
+                if let Some(granted) = subordinate.arbiter.grant(&requests) {
+                    let manager = &mut self.managers[granted];
+                    next_request = ApbRequest {
+                        sel: true,
+                        enable: false,
+                        ..manager.request.get_value()
+                    };
+                    manager.state = ManagerState::Granted;
+                    subordinate.state = SubordinateState::Setup(granted);
+                }
+            }
+            drive(&mut subordinate.request, next_request);
+        }
+    }
+}
+
+impl Component for ApbInterconnect {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        for (index, manager) in self.managers.iter().enumerate() {
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("manager{}_request", index),
+                    &manager.request,
+                )
+                .unwrap();
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("manager{}_response", index),
+                    &manager.response,
+                )
+                .unwrap();
+        }
+        for (index, subordinate) in self.subordinates.iter().enumerate() {
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("subordinate{}_request", index),
+                    &subordinate.request,
+                )
+                .unwrap();
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("subordinate{}_response", index),
+                    &subordinate.response,
+                )
+                .unwrap();
+        }
+    }
+
+    fn reset(&mut self) {
+        for manager in self.managers.iter_mut() {
+            manager.request.reset();
+            manager.checker.reset();
+            manager.state = ManagerState::default();
+        }
+        for subordinate in self.subordinates.iter_mut() {
+            subordinate.response.reset();
+            subordinate.checker.reset();
+            subordinate.arbiter.reset();
+            subordinate.state = SubordinateState::default();
+        }
+    }
+
+    fn poll_recv(&mut self) {
+        poll_clocked(
+            self,
+            |interconnect| (&interconnect.clock_receiver, &interconnect.ack_sender),
+            Self::drain_inputs,
+            |interconnect, _| interconnect.on_clock(),
+        );
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        let managers = self
+            .managers
+            .iter()
+            .map(|manager| {
+                Ok(json!({
+                    "state": manager.state,
+                    "checker": manager.checker.save_state()?,
+                }))
+            })
+            .collect::<Result<Vec<_>, SimError>>()?;
+        let subordinates = self
+            .subordinates
+            .iter()
+            .map(|subordinate| {
+                Ok(json!({
+                    "state": subordinate.state,
+                    "arbiter": subordinate.arbiter,
+                    "checker": subordinate.checker.save_state()?,
+                }))
+            })
+            .collect::<Result<Vec<_>, SimError>>()?;
+        Ok(json!({
+            "managers": managers,
+            "subordinates": subordinates,
+        }))
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        for (manager, state) in self
+            .managers
+            .iter_mut()
+            .zip(state["managers"].as_array().into_iter().flatten())
+        {
+            manager.state = serde_json::from_value(state["state"].clone())?;
+            manager.checker.restore_state(state["checker"].clone())?;
+        }
+        for (subordinate, state) in self
+            .subordinates
+            .iter_mut()
+            .zip(state["subordinates"].as_array().into_iter().flatten())
+        {
+            subordinate.state = serde_json::from_value(state["state"].clone())?;
+            subordinate.arbiter = serde_json::from_value(state["arbiter"].clone())?;
+            subordinate
+                .checker
+                .restore_state(state["checker"].clone())?;
+        }
+        Ok(())
+    }
+}
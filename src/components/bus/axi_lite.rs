@@ -0,0 +1,598 @@
+use crate::channel::{ready_valid, ReadyValidRx, ReadyValidTx};
+use crate::component::Component;
+use crate::components::bus::{check_ranges, decode, AddressRange, Arbiter, Arbitration};
+use crate::components::poll_clocked;
+use crate::error::SimError;
+use crate::sim_manager::SimManager;
+use crate::types::{ComponentId, EventId, Input, Output};
+use crossbeam_channel::{unbounded, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// `BRESP` and `RRESP`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AxiResponse {
+    #[default]
+    Okay,
+    ExOkay,
+    SlvErr,
+    DecErr,
+}
+
+/// The payload of the write and read address channels, `AWADDR` and `AWPROT` or `ARADDR` and `ARPROT`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxiLiteAddress {
+    pub addr: u64,
+    pub prot: u8,
+}
+
+/// The payload of the write data channel, `WDATA` and `WSTRB`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxiLiteWriteData {
+    pub data: u64,
+    pub strb: u8,
+}
+
+/// The payload of the write response channel, `BRESP`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxiLiteWriteResponse {
+    pub resp: AxiResponse,
+}
+
+/// The payload of the read data channel, `RDATA` and `RRESP`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxiLiteReadData {
+    pub data: u64,
+    pub resp: AxiResponse,
+}
+
+/// Builds the manager and subordinate ends of an AXI4-Lite bus called `name`,
+/// made of five ready/valid channels named `<name>.aw`, `<name>.w`, `<name>.b`, `<name>.ar` and `<name>.r`,
+/// see `ready_valid`.
+///
+/// The receiving end of each channel checks its handshake.
+pub fn axi_lite(
+    sim_manager: Arc<SimManager>,
+    ack_sender: Sender<EventId>,
+    name: &str,
+) -> (AxiLiteManagerPort, AxiLiteSubordinatePort) {
+    let channel_name = |channel: &str| format!("{}.{}", name, channel);
+    let (aw_tx, aw_rx) = ready_valid(sim_manager.clone(), ack_sender.clone(), &channel_name("aw"));
+    let (w_tx, w_rx) = ready_valid(sim_manager.clone(), ack_sender.clone(), &channel_name("w"));
+    let (b_tx, b_rx) = ready_valid(sim_manager.clone(), ack_sender.clone(), &channel_name("b"));
+    let (ar_tx, ar_rx) = ready_valid(sim_manager.clone(), ack_sender.clone(), &channel_name("ar"));
+    let (r_tx, r_rx) = ready_valid(sim_manager, ack_sender, &channel_name("r"));
+    (
+        AxiLiteManagerPort {
+            aw: aw_tx,
+            w: w_tx,
+            b: b_rx,
+            ar: ar_tx,
+            r: r_rx,
+        },
+        AxiLiteSubordinatePort {
+            aw: aw_rx,
+            w: w_rx,
+            b: b_tx,
+            ar: ar_rx,
+            r: r_tx,
+        },
+    )
+}
+
+/// The end of an AXI4-Lite bus a manager drives.
+///
+/// Each channel end must be polled from `Component::poll_recv` and ticked at the start of every clock tick,
+/// see `AxiLiteManagerPort::poll` and `AxiLiteManagerPort::tick`.
+pub struct AxiLiteManagerPort {
+    pub aw: ReadyValidTx<AxiLiteAddress>,
+    pub w: ReadyValidTx<AxiLiteWriteData>,
+    pub b: ReadyValidRx<AxiLiteWriteResponse>,
+    pub ar: ReadyValidTx<AxiLiteAddress>,
+    pub r: ReadyValidRx<AxiLiteReadData>,
+}
+
+impl AxiLiteManagerPort {
+    /// Registers the channels as `<name>_aw`, `<name>_w`, `<name>_b`, `<name>_ar` and `<name>_r`
+    pub fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        self.aw
+            .register_ports(sim_manager, component_id, &format!("{}_aw", name))?;
+        self.w
+            .register_ports(sim_manager, component_id, &format!("{}_w", name))?;
+        self.b
+            .register_ports(sim_manager, component_id, &format!("{}_b", name))?;
+        self.ar
+            .register_ports(sim_manager, component_id, &format!("{}_ar", name))?;
+        self.r
+            .register_ports(sim_manager, component_id, &format!("{}_r", name))
+    }
+
+    pub fn poll(&mut self) {
+        self.aw.poll();
+        self.w.poll();
+        self.b.poll();
+        self.ar.poll();
+        self.r.poll();
+    }
+
+    pub fn tick(&mut self) {
+        self.aw.tick();
+        self.w.tick();
+        self.b.tick();
+        self.ar.tick();
+        self.r.tick();
+    }
+
+    pub fn reset(&mut self) {
+        self.aw.reset();
+        self.w.reset();
+        self.b.reset();
+        self.ar.reset();
+        self.r.reset();
+    }
+
+    /// The state of the channel ends, for `Component::save_state`
+    pub fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "aw": self.aw.save_state()?,
+            "w": self.w.save_state()?,
+            "b": self.b.save_state()?,
+            "ar": self.ar.save_state()?,
+            "r": self.r.save_state()?,
+        }))
+    }
+
+    pub fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.aw.restore_state(state["aw"].clone())?;
+        self.w.restore_state(state["w"].clone())?;
+        self.b.restore_state(state["b"].clone())?;
+        self.ar.restore_state(state["ar"].clone())?;
+        self.r.restore_state(state["r"].clone())
+    }
+}
+
+/// The end of an AXI4-Lite bus a subordinate drives, polled and ticked like `AxiLiteManagerPort`
+pub struct AxiLiteSubordinatePort {
+    pub aw: ReadyValidRx<AxiLiteAddress>,
+    pub w: ReadyValidRx<AxiLiteWriteData>,
+    pub b: ReadyValidTx<AxiLiteWriteResponse>,
+    pub ar: ReadyValidRx<AxiLiteAddress>,
+    pub r: ReadyValidTx<AxiLiteReadData>,
+}
+
+impl AxiLiteSubordinatePort {
+    /// Registers the channels as `<name>_aw`, `<name>_w`, `<name>_b`, `<name>_ar` and `<name>_r`
+    pub fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        self.aw
+            .register_ports(sim_manager, component_id, &format!("{}_aw", name))?;
+        self.w
+            .register_ports(sim_manager, component_id, &format!("{}_w", name))?;
+        self.b
+            .register_ports(sim_manager, component_id, &format!("{}_b", name))?;
+        self.ar
+            .register_ports(sim_manager, component_id, &format!("{}_ar", name))?;
+        self.r
+            .register_ports(sim_manager, component_id, &format!("{}_r", name))
+    }
+
+    pub fn poll(&mut self) {
+        self.aw.poll();
+        self.w.poll();
+        self.b.poll();
+        self.ar.poll();
+        self.r.poll();
+    }
+
+    pub fn tick(&mut self) {
+        self.aw.tick();
+        self.w.tick();
+        self.b.tick();
+        self.ar.tick();
+        self.r.tick();
+    }
+
+    pub fn reset(&mut self) {
+        self.aw.reset();
+        self.w.reset();
+        self.b.reset();
+        self.ar.reset();
+        self.r.reset();
+    }
+
+    /// The state of the channel ends, for `Component::save_state`
+    pub fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "aw": self.aw.save_state()?,
+            "w": self.w.save_state()?,
+            "b": self.b.save_state()?,
+            "ar": self.ar.save_state()?,
+            "r": self.r.save_state()?,
+        }))
+    }
+
+    pub fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.aw.restore_state(state["aw"].clone())?;
+        self.w.restore_state(state["w"].clone())?;
+        self.b.restore_state(state["b"].clone())?;
+        self.ar.restore_state(state["ar"].clone())?;
+        self.r.restore_state(state["r"].clone())
+    }
+}
+
+/// Where the write or the read of a manager is
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum TransactionState {
+    #[default]
+    Idle,
+    /// The request waits for the subordinate to be granted
+    Waiting(usize),
+    /// The request is issued to the subordinate
+    Granted,
+    /// The response is offered to the manager
+    Responding,
+}
+
+struct ManagerPort {
+    port: AxiLiteSubordinatePort,
+    write: TransactionState,
+    read: TransactionState,
+}
+
+struct SubordinatePort {
+    port: AxiLiteManagerPort,
+    /// The managers whose write and read are issued
+    write: Option<usize>,
+    read: Option<usize>,
+    write_arbiter: Arbiter,
+    read_arbiter: Arbiter,
+}
+
+/// An AXI4-Lite interconnect between any number of managers and subordinates.
+///
+/// Each manager is connected to an `AxiLiteSubordinatePort`, each subordinate to an `AxiLiteManagerPort`.
+/// A write is decoded once both its address and its data are received, a read once its address is,
+/// to the subordinate whose range holds `addr`.
+/// The writes and reads of a subordinate are granted to one manager at a time each, see `Arbitration`,
+/// so every manager and every subordinate has at most one write and one read outstanding.
+/// Requests to addresses no subordinate answers to get a `DecErr` response.
+///
+/// Besides the handshakes, checked by the channel ends, responses without a request are reported
+/// under `<name>.subordinate<i>`, see `SimManager::report_violation`.
+/// The channels are registered as `manager<i>_<channel>` and `subordinate<i>_<channel>`,
+/// see `AxiLiteSubordinatePort::register_ports`.
+pub struct AxiLiteInterconnect {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    arbitration: Arbitration,
+    managers: Vec<ManagerPort>,
+    subordinates: Vec<SubordinatePort>,
+    ranges: Vec<AddressRange>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl AxiLiteInterconnect {
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        ack_sender: Sender<EventId>,
+    ) -> Self {
+        let clock_tick_channel = unbounded();
+        AxiLiteInterconnect {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            arbitration: Arbitration::default(),
+            managers: Vec::new(),
+            subordinates: Vec::new(),
+            ranges: Vec::new(),
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }
+    }
+
+    pub fn with_arbitration(mut self, arbitration: Arbitration) -> Self {
+        self.arbitration = arbitration;
+        self
+    }
+
+    pub fn with_manager(mut self, port: AxiLiteSubordinatePort) -> Self {
+        self.managers.push(ManagerPort {
+            port,
+            write: TransactionState::default(),
+            read: TransactionState::default(),
+        });
+        self
+    }
+
+    pub fn with_subordinate(mut self, range: AddressRange, port: AxiLiteManagerPort) -> Self {
+        self.subordinates.push(SubordinatePort {
+            port,
+            write: None,
+            read: None,
+            write_arbiter: Arbiter::default(),
+            read_arbiter: Arbiter::default(),
+        });
+        self.ranges.push(range);
+        self
+    }
+
+    /// Fails if the address ranges of the subordinates overlap
+    pub fn build(mut self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_ranges(&self.name, &self.ranges)?;
+        for subordinate in self.subordinates.iter_mut() {
+            subordinate.write_arbiter = Arbiter::new(self.arbitration);
+            subordinate.read_arbiter = Arbiter::new(self.arbitration);
+        }
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn poll(&mut self) {
+        for manager in self.managers.iter_mut() {
+            manager.port.poll();
+        }
+        for subordinate in self.subordinates.iter_mut() {
+            subordinate.port.poll();
+        }
+    }
+
+    fn report_violation(&self, subordinate: usize, message: String) {
+        self.sim_manager
+            .report_violation(
+                &format!("{}.subordinate{}", self.name, subordinate),
+                message,
+            )
+            .unwrap();
+    }
+
+    fn on_clock(&mut self) {
+        for manager in self.managers.iter_mut() {
+            manager.port.tick();
+        }
+        for subordinate in self.subordinates.iter_mut() {
+            subordinate.port.tick();
+        }
+
+        for manager in self.managers.iter_mut() {
+            let port = &mut manager.port;
+            manager.write = match manager.write {
+                TransactionState::Responding if port.b.can_send() => TransactionState::Idle,
+                TransactionState::Idle => match (port.aw.peek(), port.w.peek()) {
+                    (Some(address), Some(_)) => match decode(&self.ranges, address.addr) {
+                        Some(subordinate) => TransactionState::Waiting(subordinate),
+                        None => {
+                            port.aw.take();
+                            port.w.take();
+                            let response = AxiLiteWriteResponse {
+                                resp: AxiResponse::DecErr,
+                            };
+                            port.b.send(response).unwrap();
+                            TransactionState::Responding
+                        }
+                    },
+                    _ => TransactionState::Idle,
+                },
+                state => state,
+            };
+            manager.read = match manager.read {
+                TransactionState::Responding if port.r.can_send() => TransactionState::Idle,
+                TransactionState::Idle => match port.ar.peek() {
+                    Some(address) => match decode(&self.ranges, address.addr) {
+                        Some(subordinate) => TransactionState::Waiting(subordinate),
+                        None => {
+                            port.ar.take();
+                            let response = AxiLiteReadData {
+                                data: 0,
+                                resp: AxiResponse::DecErr,
+                            };
+                            port.r.send(response).unwrap();
+                            TransactionState::Responding
+                        }
+                    },
+                    None => TransactionState::Idle,
+                },
+                state => state,
+            };
+        }
+
+        let mut violations = Vec::new();
+        for (index, subordinate) in self.subordinates.iter_mut().enumerate() {
+            let port = &mut subordinate.port;
+            if let Some(response) = port.b.take() {
+                match subordinate.write.take() {
+                    Some(granted) => {
+                        if !port.aw.can_send() || !port.w.can_send() {
+                            violations.push((
+                                index,
+                                format!("{:?} before the address and data of the write", response),
+                            ));
+                        }
+                        let manager = &mut self.managers[granted];
+                        manager.port.b.send(response).unwrap();
+                        manager.write = TransactionState::Responding;
+                    }
+                    None => violations.push((index, format!("{:?} without a write", response))),
+                }
+            }
+            if let Some(response) = port.r.take() {
+                match subordinate.read.take() {
+                    Some(granted) => {
+                        if !port.ar.can_send() {
+                            violations.push((
+                                index,
+                                format!("{:?} before the address of the read", response),
+                            ));
+                        }
+                        let manager = &mut self.managers[granted];
+                        manager.port.r.send(response).unwrap();
+                        manager.read = TransactionState::Responding;
+                    }
+                    None => violations.push((index, format!("{:?} without a read", response))),
+                }
+            }
+
+            // a subordinate responding early may still hold a previous request, which must be accepted first
+            if subordinate.write.is_none() && port.aw.can_send() && port.w.can_send() {
+                let requests: Vec<bool> = self
+                    .managers
+                    .iter()
+                    .map(|manager| manager.write == TransactionState::Waiting(index))
+                    .collect();
+                if let Some(granted) = subordinate.write_arbiter.grant(&requests) {
+                    let manager = &mut self.managers[granted];
+                    let address = manager.port.aw.take().unwrap_or_default();
+                    let data = manager.port.w.take().unwrap_or_default();
+                    port.aw.send(address).unwrap();
+                    port.w.send(data).unwrap();
+                    manager.write = TransactionState::Granted;
+                    subordinate.write = Some(granted);
+                }
+            }
+            if subordinate.read.is_none() && port.ar.can_send() {
+                let requests: Vec<bool> = self
+                    .managers
+                    .iter()
+                    .map(|manager| manager.read == TransactionState::Waiting(index))
+                    .collect();
+                if let Some(granted) = subordinate.read_arbiter.grant(&requests) {
+                    let manager = &mut self.managers[granted];
+                    let address = manager.port.ar.take().unwrap_or_default();
+                    port.ar.send(address).unwrap();
+                    manager.read = TransactionState::Granted;
+                    subordinate.read = Some(granted);
+                }
+            }
+        }
+        for (subordinate, message) in violations {
+            self.report_violation(subordinate, message);
+        }
+    }
+}
+
+impl Component for AxiLiteInterconnect {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        for (index, manager) in self.managers.iter().enumerate() {
+            manager
+                .port
+                .register_ports(
+                    &self.sim_manager,
+                    self.component_id,
+                    &format!("manager{}", index),
+                )
+                .unwrap();
+        }
+        for (index, subordinate) in self.subordinates.iter().enumerate() {
+            subordinate
+                .port
+                .register_ports(
+                    &self.sim_manager,
+                    self.component_id,
+                    &format!("subordinate{}", index),
+                )
+                .unwrap();
+        }
+    }
+
+    fn reset(&mut self) {
+        for manager in self.managers.iter_mut() {
+            manager.port.reset();
+            manager.write = TransactionState::default();
+            manager.read = TransactionState::default();
+        }
+        for subordinate in self.subordinates.iter_mut() {
+            subordinate.port.reset();
+            subordinate.write = None;
+            subordinate.read = None;
+            subordinate.write_arbiter.reset();
+            subordinate.read_arbiter.reset();
+        }
+    }
+
+    fn poll_recv(&mut self) {
+        poll_clocked(
+            self,
+            |interconnect| (&interconnect.clock_receiver, &interconnect.ack_sender),
+            Self::poll,
+            |interconnect, _| interconnect.on_clock(),
+        );
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        let managers = self
+            .managers
+            .iter()
+            .map(|manager| {
+                Ok(json!({
+                    "port": manager.port.save_state()?,
+                    "write": manager.write,
+                    "read": manager.read,
+                }))
+            })
+            .collect::<Result<Vec<_>, SimError>>()?;
+        let subordinates = self
+            .subordinates
+            .iter()
+            .map(|subordinate| {
+                Ok(json!({
+                    "port": subordinate.port.save_state()?,
+                    "write": subordinate.write,
+                    "read": subordinate.read,
+                    "write_arbiter": subordinate.write_arbiter,
+                    "read_arbiter": subordinate.read_arbiter,
+                }))
+            })
+            .collect::<Result<Vec<_>, SimError>>()?;
+        Ok(json!({
+            "managers": managers,
+            "subordinates": subordinates,
+        }))
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        for (manager, state) in self
+            .managers
+            .iter_mut()
+            .zip(state["managers"].as_array().into_iter().flatten())
+        {
+            manager.port.restore_state(state["port"].clone())?;
+            manager.write = serde_json::from_value(state["write"].clone())?;
+            manager.read = serde_json::from_value(state["read"].clone())?;
+        }
+        for (subordinate, state) in self
+            .subordinates
+            .iter_mut()
+            .zip(state["subordinates"].as_array().into_iter().flatten())
+        {
+            subordinate.port.restore_state(state["port"].clone())?;
+            subordinate.write = serde_json::from_value(state["write"].clone())?;
+            subordinate.read = serde_json::from_value(state["read"].clone())?;
+            subordinate.write_arbiter = serde_json::from_value(state["write_arbiter"].clone())?;
+            subordinate.read_arbiter = serde_json::from_value(state["read_arbiter"].clone())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+pub mod apb;
+pub mod axi_lite;
+pub mod wishbone;
+
+use crate::error::SimError;
+use crate::event::EventValue;
+use crate::tx::Tx;
+use serde::{Deserialize, Serialize};
+
+/// The `size` bytes from `base` a subordinate of an interconnect answers to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressRange {
+    pub base: u64,
+    pub size: u64,
+}
+
+impl AddressRange {
+    pub fn new(base: u64, size: u64) -> Self {
+        AddressRange { base, size }
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base && address - self.base < self.size
+    }
+
+    fn overlaps(&self, other: &AddressRange) -> bool {
+        self.contains(other.base) || other.contains(self.base)
+    }
+}
+
+/// The subordinate whose range holds `address`, if any
+pub(crate) fn decode(ranges: &[AddressRange], address: u64) -> Option<usize> {
+    ranges.iter().position(|range| range.contains(address))
+}
+
+pub(crate) fn check_ranges(name: &str, ranges: &[AddressRange]) -> Result<(), SimError> {
+    for (index, range) in ranges.iter().enumerate() {
+        if range.size == 0 {
+            return Err(SimError::ComponentError(format!(
+                "{}: subordinate {} has an empty address range",
+                name, index
+            )));
+        }
+        if let Some(other) = ranges[..index]
+            .iter()
+            .position(|other| other.overlaps(range))
+        {
+            return Err(SimError::ComponentError(format!(
+                "{}: the address ranges of subordinates {} and {} overlap",
+                name, other, index
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// How a subordinate is granted to one of the managers requesting it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Arbitration {
+    /// The first manager after the one granted last
+    #[default]
+    RoundRobin,
+    /// The manager with the lowest index
+    FixedPriority,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Arbiter {
+    arbitration: Arbitration,
+    last_grant: Option<usize>,
+}
+
+impl Arbiter {
+    pub fn new(arbitration: Arbitration) -> Self {
+        Arbiter {
+            arbitration,
+            last_grant: None,
+        }
+    }
+
+    /// The index of the manager granted among `requests`, if any requests
+    pub fn grant(&mut self, requests: &[bool]) -> Option<usize> {
+        let start = match (self.arbitration, self.last_grant) {
+            (Arbitration::RoundRobin, Some(last_grant)) => last_grant + 1,
+            _ => 0,
+        };
+        let grant = (0..requests.len())
+            .map(|offset| (start + offset) % requests.len())
+            .find(|index| requests[*index])?;
+        self.last_grant = Some(grant);
+        Some(grant)
+    }
+
+    pub fn reset(&mut self) {
+        self.last_grant = None;
+    }
+}
+
+/// Sends `value` on `tx` unless it already holds it
+pub(crate) fn drive<T: Default + Copy + Send + Sync + PartialEq + 'static + EventValue>(
+    tx: &mut Tx<T>,
+    value: T,
+) {
+    if tx.get_value() != value {
+        tx.send(value, 0);
+    }
+}
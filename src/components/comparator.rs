@@ -0,0 +1,152 @@
+use crate::component::Component;
+use crate::components::{check_width, get_mask, sign_extend};
+use crate::error::SimError;
+use crate::rx::Rx;
+use crate::rx::RxType::NewValue;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::ComponentId;
+use std::cmp::Ordering;
+use std::sync::{Arc, Mutex};
+
+/// A comparator of `width` bit values, unsigned unless built `with_signed`,
+/// setting the `equal`, `less` and `greater` outputs it is given
+pub struct Comparator {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    width: u32,
+    signed: bool,
+    a: Rx<u64>,
+    b: Rx<u64>,
+    equal: Option<Tx<bool>>,
+    less: Option<Tx<bool>>,
+    greater: Option<Tx<bool>>,
+}
+
+impl Comparator {
+    /// A 64 bit unsigned comparator by default
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        a: Rx<u64>,
+        b: Rx<u64>,
+    ) -> Self {
+        Comparator {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            width: u64::BITS,
+            signed: false,
+            a,
+            b,
+            equal: None,
+            less: None,
+            greater: None,
+        }
+    }
+
+    pub fn with_width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Compares the values as two's complement
+    pub fn with_signed(mut self) -> Self {
+        self.signed = true;
+        self
+    }
+
+    /// Set when `a == b`
+    pub fn with_equal(mut self, equal: Tx<bool>) -> Self {
+        self.equal = Some(equal);
+        self
+    }
+
+    /// Set when `a < b`
+    pub fn with_less(mut self, less: Tx<bool>) -> Self {
+        self.less = Some(less);
+        self
+    }
+
+    /// Set when `a > b`
+    pub fn with_greater(mut self, greater: Tx<bool>) -> Self {
+        self.greater = Some(greater);
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_width(&self.name, self.width)?;
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn compare(&self) -> Ordering {
+        let mask = get_mask(self.width);
+        let a = self.a.get_value() & mask;
+        let b = self.b.get_value() & mask;
+        if self.signed {
+            (sign_extend(a, self.width) as i64).cmp(&(sign_extend(b, self.width) as i64))
+        } else {
+            a.cmp(&b)
+        }
+    }
+
+    fn on_comb(&mut self) {
+        let ordering = self.compare();
+        for (output, expected) in [
+            (self.equal.as_mut(), Ordering::Equal),
+            (self.less.as_mut(), Ordering::Less),
+            (self.greater.as_mut(), Ordering::Greater),
+        ] {
+            if let Some(output) = output {
+                output.send(ordering == expected, 0);
+            }
+        }
+    }
+}
+
+impl Component for Comparator {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "a", &self.a)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "b", &self.b)
+            .unwrap();
+        for (name, output) in [
+            ("equal", self.equal.as_ref()),
+            ("less", self.less.as_ref()),
+            ("greater", self.greater.as_ref()),
+        ] {
+            if let Some(output) = output {
+                self.sim_manager
+                    .register_port(self.component_id, name, output)
+                    .unwrap();
+            }
+        }
+        self.on_comb();
+    }
+
+    fn reset(&mut self) {
+        self.a.reset();
+        self.b.reset();
+    }
+
+    fn poll_recv(&mut self) {
+        let mut changed = self.a.try_recv() == NewValue;
+        changed |= self.b.try_recv() == NewValue;
+        if changed {
+            self.on_comb();
+        }
+        self.a.ack();
+        self.b.ack();
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
@@ -0,0 +1,118 @@
+use crate::component::Component;
+use crate::components::check_width;
+use crate::error::SimError;
+use crate::rx::Rx;
+use crate::rx::RxType::NewValue;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::ComponentId;
+use std::sync::{Arc, Mutex};
+
+/// The widest `select` of a decoder, with one output bit per value
+const MAX_SELECT_WIDTH: u32 = 6;
+
+/// A decoder setting the bit of `output` picked by the low `width` bits of `select`,
+/// and no bit when `enable` is given and not set
+pub struct Decoder {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    width: u32,
+    select: Rx<u64>,
+    enable: Option<Rx<bool>>,
+    output: Tx<u64>,
+}
+
+impl Decoder {
+    /// A 6 to 64 decoder by default
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        select: Rx<u64>,
+        output: Tx<u64>,
+    ) -> Self {
+        Decoder {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            width: MAX_SELECT_WIDTH,
+            select,
+            enable: None,
+            output,
+        }
+    }
+
+    /// Decodes `width` bits into `2^width` bits, at most 6
+    pub fn with_width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_enable(mut self, enable: Rx<bool>) -> Self {
+        self.enable = Some(enable);
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_width(&self.name, self.width)?;
+        if self.width > MAX_SELECT_WIDTH {
+            return Err(SimError::ComponentError(format!(
+                "{}: cannot decode {} bits into a 64 bit output",
+                self.name, self.width
+            )));
+        }
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn on_comb(&mut self) {
+        let enabled = self.enable.as_ref().is_none_or(|enable| enable.get_value());
+        let select = self.select.get_value() & ((1 << self.width) - 1);
+        self.output.send(if enabled { 1 << select } else { 0 }, 0);
+    }
+}
+
+impl Component for Decoder {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "select", &self.select)
+            .unwrap();
+        if let Some(enable) = self.enable.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "enable", enable)
+                .unwrap();
+        }
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        self.on_comb();
+    }
+
+    fn reset(&mut self) {
+        self.select.reset();
+        if let Some(enable) = self.enable.as_mut() {
+            enable.reset();
+        }
+    }
+
+    fn poll_recv(&mut self) {
+        let mut changed = self.select.try_recv() == NewValue;
+        if let Some(enable) = self.enable.as_mut() {
+            changed |= enable.try_recv() == NewValue;
+        }
+        if changed {
+            self.on_comb();
+        }
+        self.select.ack();
+        if let Some(enable) = self.enable.as_mut() {
+            enable.ack();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
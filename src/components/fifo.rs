@@ -0,0 +1,399 @@
+use crate::component::Component;
+use crate::components::{check_width, get_mask, poll_clocked};
+use crate::error::SimError;
+use crate::rx::Rx;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::{ComponentId, Cycle, EventId, Input, Output};
+use crossbeam_channel::{unbounded, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// A clock derived from the simulation clock, ticking at the clock ticks of the cycles
+/// `phase`, `phase + period`, `phase + 2 * period`...
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockDomain {
+    pub period: Cycle,
+    pub phase: Cycle,
+}
+
+impl ClockDomain {
+    pub fn new(period: Cycle, phase: Cycle) -> Self {
+        ClockDomain { period, phase }
+    }
+
+    pub fn ticks_at(&self, cycle: Cycle) -> bool {
+        cycle % self.period == self.phase % self.period
+    }
+}
+
+impl Default for ClockDomain {
+    /// The simulation clock itself
+    fn default() -> Self {
+        ClockDomain::new(1, 0)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct FifoState {
+    /// The words written and not read yet, the oldest first
+    entries: VecDeque<u64>,
+    write_count: u64,
+    read_count: u64,
+    /// The samples of `write_count` in the synchronizer of the read clock domain, the oldest first
+    write_count_sync: VecDeque<u64>,
+    /// The samples of `read_count` in the synchronizer of the write clock domain, the oldest first
+    read_count_sync: VecDeque<u64>,
+}
+
+struct WritePort {
+    push: Rx<bool>,
+    data: Rx<u64>,
+}
+
+struct ReadPort {
+    pop: Rx<bool>,
+    data: Tx<u64>,
+}
+
+/// A FIFO of `depth` words of `width` bits.
+///
+/// A word is pushed at the clock ticks of the write clock when `push` is set,
+/// and popped at the clock ticks of the read clock when `pop` is set.
+/// By default the popped word is on `pop_data` after the clock tick,
+/// with first-word-fall-through the word at the head is on `pop_data` before it is popped.
+/// Pushing while full and popping while empty change nothing and are reported as protocol violations,
+/// see `SimManager::report_violation`, except that a full FIFO can be pushed to at the clock tick it is popped.
+///
+/// A dual-clock FIFO, see `Fifo::with_clock_domains`, sees the other side through `sync_stages` flip-flops clocked on its own side:
+/// a word pushed is only seen by the read side `sync_stages` read clock ticks later,
+/// a free slot only seen by the write side `sync_stages` write clock ticks later.
+/// `full` and `almost_full` follow the write side, `empty` the read side.
+///
+/// The ports are registered as `push`, `push_data`, `pop`, `pop_data`, `full`, `empty` and `almost_full`.
+pub struct Fifo {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    width: u32,
+    depth: u64,
+    first_word_fall_through: bool,
+    write_clock: ClockDomain,
+    read_clock: ClockDomain,
+    sync_stages: usize,
+    write_port: Option<WritePort>,
+    read_port: Option<ReadPort>,
+    full: Option<Tx<bool>>,
+    empty: Option<Tx<bool>>,
+    almost_full: Option<(Tx<bool>, u64)>,
+    state: FifoState,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl Fifo {
+    /// A FIFO on the simulation clock
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        ack_sender: Sender<EventId>,
+        width: u32,
+        depth: u64,
+    ) -> Self {
+        let clock_tick_channel = unbounded();
+        Fifo {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            width,
+            depth,
+            first_word_fall_through: false,
+            write_clock: ClockDomain::default(),
+            read_clock: ClockDomain::default(),
+            sync_stages: 0,
+            write_port: None,
+            read_port: None,
+            full: None,
+            empty: None,
+            almost_full: None,
+            state: FifoState::default(),
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }
+    }
+
+    pub fn with_write_port(mut self, push: Rx<bool>, data: Rx<u64>) -> Self {
+        self.write_port = Some(WritePort { push, data });
+        self
+    }
+
+    pub fn with_read_port(mut self, pop: Rx<bool>, data: Tx<u64>) -> Self {
+        self.read_port = Some(ReadPort { pop, data });
+        self
+    }
+
+    /// Makes a dual-clock FIFO crossing from `write_clock` to `read_clock` through `sync_stages` synchronizer flip-flops
+    pub fn with_clock_domains(
+        mut self,
+        write_clock: ClockDomain,
+        read_clock: ClockDomain,
+        sync_stages: usize,
+    ) -> Self {
+        self.write_clock = write_clock;
+        self.read_clock = read_clock;
+        self.sync_stages = sync_stages;
+        self
+    }
+
+    pub fn with_first_word_fall_through(mut self) -> Self {
+        self.first_word_fall_through = true;
+        self
+    }
+
+    pub fn with_full(mut self, full: Tx<bool>) -> Self {
+        self.full = Some(full);
+        self
+    }
+
+    pub fn with_empty(mut self, empty: Tx<bool>) -> Self {
+        self.empty = Some(empty);
+        self
+    }
+
+    /// `almost_full` is set when at least `threshold` words are in the FIFO
+    pub fn with_almost_full(mut self, almost_full: Tx<bool>, threshold: u64) -> Self {
+        self.almost_full = Some((almost_full, threshold));
+        self
+    }
+
+    pub fn build(mut self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_width(&self.name, self.width)?;
+        if self.depth == 0 {
+            return Err(SimError::ComponentError(format!(
+                "{}: a FIFO needs at least one word",
+                self.name
+            )));
+        }
+        if self.write_clock.period == 0 || self.read_clock.period == 0 {
+            return Err(SimError::ComponentError(format!(
+                "{}: a clock domain needs a period of at least 1 cycle",
+                self.name
+            )));
+        }
+        self.state = self.initial_state();
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    /// The number of words pushed and not popped yet
+    pub fn get_len(&self) -> u64 {
+        self.state.entries.len() as u64
+    }
+
+    fn initial_state(&self) -> FifoState {
+        FifoState {
+            write_count_sync: VecDeque::from(vec![0; self.sync_stages]),
+            read_count_sync: VecDeque::from(vec![0; self.sync_stages]),
+            ..Default::default()
+        }
+    }
+
+    /// The write count as seen by the read side
+    fn get_write_count_seen(&self) -> u64 {
+        self.state
+            .write_count_sync
+            .front()
+            .copied()
+            .unwrap_or(self.state.write_count)
+    }
+
+    /// The read count as seen by the write side
+    fn get_read_count_seen(&self) -> u64 {
+        self.state
+            .read_count_sync
+            .front()
+            .copied()
+            .unwrap_or(self.state.read_count)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.get_write_count_seen() == self.state.read_count
+    }
+
+    /// The number of words as seen by the write side
+    fn get_write_len(&self) -> u64 {
+        self.state.write_count - self.get_read_count_seen()
+    }
+
+    fn send_pop_data(&mut self, value: u64) {
+        if let Some(port) = self.read_port.as_mut() {
+            if value != port.data.get_value() {
+                port.data.send(value, 0);
+            }
+        }
+    }
+
+    fn send_flags(&mut self) {
+        let write_len = self.get_write_len();
+        let full = write_len >= self.depth;
+        let empty = self.is_empty();
+        for (port, value) in [(self.full.as_mut(), full), (self.empty.as_mut(), empty)] {
+            if let Some(port) = port.filter(|port| port.get_value() != value) {
+                port.send(value, 0);
+            }
+        }
+        if let Some((almost_full, threshold)) = self.almost_full.as_mut() {
+            let value = write_len >= *threshold;
+            if almost_full.get_value() != value {
+                almost_full.send(value, 0);
+            }
+        }
+    }
+
+    fn drain_inputs(&mut self) {
+        if let Some(port) = self.write_port.as_mut() {
+            port.push.drain();
+            port.data.drain();
+        }
+        if let Some(port) = self.read_port.as_mut() {
+            port.pop.drain();
+        }
+    }
+
+    fn on_clock(&mut self, cycle: Cycle) {
+        let write_tick = self.write_clock.ticks_at(cycle);
+        let read_tick = self.read_clock.ticks_at(cycle);
+        let (write_count, read_count) = (self.state.write_count, self.state.read_count);
+
+        // the read side goes first so that a full FIFO of a single clock can be pushed to while popped
+        let pop = self
+            .read_port
+            .as_ref()
+            .is_some_and(|port| port.pop.get_value());
+        if read_tick && pop {
+            if self.is_empty() {
+                self.sim_manager
+                    .report_violation(&self.name, "pop while empty".to_string())
+                    .unwrap();
+            } else {
+                let value = self.state.entries.pop_front().unwrap_or_default();
+                self.state.read_count += 1;
+                if !self.first_word_fall_through {
+                    self.send_pop_data(value);
+                }
+            }
+        }
+        let push = self
+            .write_port
+            .as_ref()
+            .filter(|port| port.push.get_value())
+            .map(|port| port.data.get_value() & get_mask(self.width));
+        if let Some(value) = push.filter(|_| write_tick) {
+            if self.get_write_len() >= self.depth {
+                self.sim_manager
+                    .report_violation(&self.name, format!("push while full, {:#x} dropped", value))
+                    .unwrap();
+            } else {
+                self.state.entries.push_back(value);
+                self.state.write_count += 1;
+            }
+        }
+
+        // the synchronizers sample the counts from before the clock tick
+        if read_tick && self.sync_stages > 0 {
+            self.state.write_count_sync.pop_front();
+            self.state.write_count_sync.push_back(write_count);
+        }
+        if write_tick && self.sync_stages > 0 {
+            self.state.read_count_sync.pop_front();
+            self.state.read_count_sync.push_back(read_count);
+        }
+
+        if self.first_word_fall_through && !self.is_empty() {
+            if let Some(value) = self.state.entries.front().copied() {
+                self.send_pop_data(value);
+            }
+        }
+        self.send_flags();
+    }
+}
+
+impl Component for Fifo {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        if let Some(port) = self.write_port.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "push", &port.push)
+                .unwrap();
+            self.sim_manager
+                .register_port(self.component_id, "push_data", &port.data)
+                .unwrap();
+        }
+        if let Some(port) = self.read_port.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "pop", &port.pop)
+                .unwrap();
+            self.sim_manager
+                .register_port(self.component_id, "pop_data", &port.data)
+                .unwrap();
+        }
+        if let Some(full) = self.full.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "full", full)
+                .unwrap();
+        }
+        if let Some(empty) = self.empty.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "empty", empty)
+                .unwrap();
+        }
+        if let Some((almost_full, _)) = self.almost_full.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "almost_full", almost_full)
+                .unwrap();
+        }
+        self.send_flags();
+    }
+
+    fn reset(&mut self) {
+        self.state = self.initial_state();
+        if let Some(port) = self.write_port.as_mut() {
+            port.push.reset();
+            port.data.reset();
+        }
+        if let Some(port) = self.read_port.as_mut() {
+            port.pop.reset();
+        }
+        self.send_flags();
+    }
+
+    fn poll_recv(&mut self) {
+        poll_clocked(
+            self,
+            |fifo| (&fifo.clock_receiver, &fifo.ack_sender),
+            Self::drain_inputs,
+            Self::on_clock,
+        );
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(serde_json::to_value(&self.state)?)
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.state = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+use crate::components::memory::parse_init_file;
+use crate::error::SimError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const PT_LOAD: u32 = 1;
+const SHF_ALLOC: u64 = 0x2;
+
+/// The contents of a segment of a memory image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SegmentData {
+    /// Bytes at consecutive byte addresses
+    Bytes(Vec<u8>),
+    /// Words at consecutive word addresses
+    Words(Vec<u64>),
+    /// Bytes at consecutive byte addresses followed by zeros up to `len` bytes,
+    /// e.g. a `.bss` section which takes no room in the file, filled in when loaded
+    ZeroFilled { bytes: Vec<u8>, len: u64 },
+}
+
+/// Contiguous contents of a memory image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageSegment {
+    pub name: String,
+    /// In words for `SegmentData::Words`, in bytes otherwise
+    pub address: u64,
+    pub data: SegmentData,
+}
+
+impl ImageSegment {
+    /// The number of bytes or words
+    pub fn len(&self) -> u64 {
+        match &self.data {
+            SegmentData::Bytes(bytes) => bytes.len() as u64,
+            SegmentData::Words(words) => words.len() as u64,
+            SegmentData::ZeroFilled { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Where a segment of an image was placed in a memory
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacedSegment {
+    pub name: String,
+    /// The address of the segment in the image
+    pub address: u64,
+    /// The first word of the memory written
+    pub start: u64,
+    /// The word after the last one written
+    pub end: u64,
+}
+
+/// A program or data image to load into a memory with `MemoryBackdoor::load_image` or `Memory::with_image`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryImage {
+    segments: Vec<ImageSegment>,
+    entry: Option<u64>,
+}
+
+impl MemoryImage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_segment(mut self, segment: ImageSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn with_entry(mut self, entry: u64) -> Self {
+        self.entry = Some(entry);
+        self
+    }
+
+    pub fn get_segments(&self) -> &[ImageSegment] {
+        &self.segments
+    }
+
+    /// The address execution starts at, if the image has one
+    pub fn get_entry(&self) -> Option<u64> {
+        self.entry
+    }
+
+    /// The loadable segments of a 32 or 64 bit, little or big endian ELF file at their physical addresses,
+    /// with their entry point.
+    /// Each segment is named after the allocated sections it contains, `.bss` being loaded as zeros.
+    pub fn from_elf<P: AsRef<Path>>(path: P) -> Result<Self, SimError> {
+        let path = path.as_ref();
+        Self::parse_elf(&path.display().to_string(), &fs::read(path)?)
+    }
+
+    /// The data records of an Intel HEX file, contiguous records forming a segment,
+    /// with the entry point of its start address record, if any
+    pub fn from_intel_hex<P: AsRef<Path>>(path: P) -> Result<Self, SimError> {
+        let path = path.as_ref();
+        Self::parse_intel_hex(&path.display().to_string(), &fs::read_to_string(path)?)
+    }
+
+    /// The words of a file of hexadecimal words, see `$readmemh` in Verilog and `Memory::with_init_file`,
+    /// contiguous words forming a segment
+    pub fn from_readmemh<P: AsRef<Path>>(path: P) -> Result<Self, SimError> {
+        let mut image = Self::new();
+        for (address, word) in parse_init_file(path)? {
+            match image.segments.last_mut() {
+                Some(ImageSegment {
+                    address: start,
+                    data: SegmentData::Words(words),
+                    ..
+                }) if *start + words.len() as u64 == address => words.push(word),
+                _ => {
+                    let name = format!("segment{}", image.segments.len());
+                    image.segments.push(ImageSegment {
+                        name,
+                        address,
+                        data: SegmentData::Words(vec![word]),
+                    });
+                }
+            }
+        }
+        Ok(image)
+    }
+
+    /// The whole of a flat binary file as a single segment at byte address `address`, named after the file
+    pub fn from_binary<P: AsRef<Path>>(path: P, address: u64) -> Result<Self, SimError> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Ok(Self::new().with_segment(ImageSegment {
+            name,
+            address,
+            data: SegmentData::Bytes(fs::read(path)?),
+        }))
+    }
+
+    pub(crate) fn parse_elf(source: &str, bytes: &[u8]) -> Result<Self, SimError> {
+        let elf = Elf::new(source, bytes)?;
+        let (entry, program_headers, section_headers, string_table_index) = if elf.is_64 {
+            (
+                elf.read_u64(24)?,
+                (elf.read_u64(32)?, elf.read_u16(54)?, elf.read_u16(56)?),
+                (elf.read_u64(40)?, elf.read_u16(58)?, elf.read_u16(60)?),
+                elf.read_u16(62)?,
+            )
+        } else {
+            (
+                elf.read_u32(24)? as u64,
+                (
+                    elf.read_u32(28)? as u64,
+                    elf.read_u16(42)?,
+                    elf.read_u16(44)?,
+                ),
+                (
+                    elf.read_u32(32)? as u64,
+                    elf.read_u16(46)?,
+                    elf.read_u16(48)?,
+                ),
+                elf.read_u16(50)?,
+            )
+        };
+
+        // (name, address) of the allocated sections, to name the segments after
+        let mut sections = Vec::new();
+        let (offset, entry_size, count) = section_headers;
+        if offset != 0 && string_table_index < count {
+            let headers: Vec<ElfSectionHeader> = (0..count)
+                .map(|index| {
+                    elf.section_header(offset.saturating_add(index as u64 * entry_size as u64))
+                })
+                .collect::<Result<_, _>>()?;
+            let string_table = &headers[string_table_index as usize];
+            for header in headers.iter() {
+                if header.flags & SHF_ALLOC != 0 && header.size > 0 {
+                    let name =
+                        elf.read_str(string_table.offset.saturating_add(header.name as u64))?;
+                    sections.push((name, header.address));
+                }
+            }
+        }
+
+        let mut image = Self::new().with_entry(entry);
+        let (offset, entry_size, count) = program_headers;
+        for index in 0..count {
+            let header =
+                elf.program_header(offset.saturating_add(index as u64 * entry_size as u64))?;
+            if header.segment_type != PT_LOAD || header.memory_size == 0 {
+                continue;
+            }
+            if header.file_size > header.memory_size {
+                return Err(SimError::ImageError(format!(
+                    "{}: segment {} has more bytes in the file than in memory",
+                    source, index
+                )));
+            }
+            let bytes = elf.read_bytes(header.offset, header.file_size)?.to_vec();
+            // the zeros are only written by `MemoryBackdoor::load_image`, once it knows they fit
+            let data = if header.memory_size > header.file_size {
+                SegmentData::ZeroFilled {
+                    bytes,
+                    len: header.memory_size,
+                }
+            } else {
+                SegmentData::Bytes(bytes)
+            };
+            let names: Vec<&str> = sections
+                .iter()
+                .filter(|(_, address)| {
+                    *address >= header.virtual_address
+                        && *address - header.virtual_address < header.memory_size
+                })
+                .map(|(name, _)| name.as_str())
+                .collect();
+            let name = if names.is_empty() {
+                format!("segment{}", index)
+            } else {
+                names.join(" ")
+            };
+            image.segments.push(ImageSegment {
+                name,
+                address: header.physical_address,
+                data,
+            });
+        }
+        Ok(image)
+    }
+
+    pub(crate) fn parse_intel_hex(source: &str, text: &str) -> Result<Self, SimError> {
+        let mut image = Self::new();
+        let mut upper_address = 0;
+        for (line_number, line) in text.lines().enumerate() {
+            let error = |msg: &str| {
+                SimError::ImageError(format!("{}:{}: {}", source, line_number + 1, msg))
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let digits = line
+                .strip_prefix(':')
+                .ok_or(error("a record must start with ':'"))?;
+            if digits.len() % 2 != 0 {
+                return Err(error("a record must have an even number of digits"));
+            }
+            let record = (0..digits.len())
+                .step_by(2)
+                .map(|index| u8::from_str_radix(&digits[index..index + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| error("a record must be hexadecimal"))?;
+            if record.len() < 5 || record.len() != record[0] as usize + 5 {
+                return Err(error("the byte count does not match the record"));
+            }
+            if record.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+                return Err(error("the checksum does not match the record"));
+            }
+            let offset = u16::from_be_bytes([record[1], record[2]]) as u64;
+            let data = &record[4..record.len() - 1];
+            let value = || {
+                data.iter()
+                    .fold(0u64, |value, byte| (value << 8) | *byte as u64)
+            };
+            match (record[3], data.len()) {
+                (0x00, _) => {
+                    let address = upper_address + offset;
+                    match image.segments.last_mut() {
+                        Some(ImageSegment {
+                            address: start,
+                            data: SegmentData::Bytes(bytes),
+                            ..
+                        }) if *start + bytes.len() as u64 == address => {
+                            bytes.extend_from_slice(data)
+                        }
+                        _ => {
+                            let name = format!("segment{}", image.segments.len());
+                            image.segments.push(ImageSegment {
+                                name,
+                                address,
+                                data: SegmentData::Bytes(data.to_vec()),
+                            });
+                        }
+                    }
+                }
+                (0x01, 0) => return Ok(image),
+                (0x02, 2) => upper_address = value() << 4,
+                // CS:IP
+                (0x03, 4) => image.entry = Some((value() >> 16 << 4) + (value() & 0xffff)),
+                (0x04, 2) => upper_address = value() << 16,
+                (0x05, 4) => image.entry = Some(value()),
+                (record_type, _) => {
+                    return Err(error(&format!(
+                        "record type {:02x} with {} data bytes is not supported",
+                        record_type,
+                        data.len()
+                    )))
+                }
+            }
+        }
+        Err(SimError::ImageError(format!(
+            "{}: no end of file record",
+            source
+        )))
+    }
+}
+
+struct ElfProgramHeader {
+    segment_type: u32,
+    offset: u64,
+    virtual_address: u64,
+    physical_address: u64,
+    file_size: u64,
+    memory_size: u64,
+}
+
+struct ElfSectionHeader {
+    name: u32,
+    flags: u64,
+    address: u64,
+    offset: u64,
+    size: u64,
+}
+
+/// Reads the fields of an ELF file in its class and byte order
+struct Elf<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    is_64: bool,
+    is_big_endian: bool,
+}
+
+impl<'a> Elf<'a> {
+    fn new(source: &'a str, bytes: &'a [u8]) -> Result<Self, SimError> {
+        if !bytes.starts_with(b"\x7fELF") || bytes.len() < 6 {
+            return Err(SimError::ImageError(format!("{}: not an ELF file", source)));
+        }
+        let is_64 = match bytes[4] {
+            1 => false,
+            2 => true,
+            class => {
+                return Err(SimError::ImageError(format!(
+                    "{}: ELF class {} is not supported",
+                    source, class
+                )))
+            }
+        };
+        let is_big_endian = match bytes[5] {
+            1 => false,
+            2 => true,
+            encoding => {
+                return Err(SimError::ImageError(format!(
+                    "{}: ELF data encoding {} is not supported",
+                    source, encoding
+                )))
+            }
+        };
+        Ok(Elf {
+            source,
+            bytes,
+            is_64,
+            is_big_endian,
+        })
+    }
+
+    fn read_bytes(&self, offset: u64, len: u64) -> Result<&'a [u8], SimError> {
+        usize::try_from(offset)
+            .ok()
+            .zip(usize::try_from(len).ok())
+            .and_then(|(offset, len)| self.bytes.get(offset..offset.checked_add(len)?))
+            .ok_or(SimError::ImageError(format!(
+                "{}: {} bytes at offset {:#x} are past the end of the file",
+                self.source, len, offset
+            )))
+    }
+
+    fn read_value(&self, offset: u64, len: u64) -> Result<u64, SimError> {
+        let bytes = self.read_bytes(offset, len)?;
+        let fold = |value: u64, byte: &u8| (value << 8) | *byte as u64;
+        Ok(if self.is_big_endian {
+            bytes.iter().fold(0, fold)
+        } else {
+            bytes.iter().rev().fold(0, fold)
+        })
+    }
+
+    fn read_u16(&self, offset: u64) -> Result<u16, SimError> {
+        Ok(self.read_value(offset, 2)? as u16)
+    }
+
+    fn read_u32(&self, offset: u64) -> Result<u32, SimError> {
+        Ok(self.read_value(offset, 4)? as u32)
+    }
+
+    fn read_u64(&self, offset: u64) -> Result<u64, SimError> {
+        self.read_value(offset, 8)
+    }
+
+    /// An address, offset or size, 4 or 8 bytes depending on the class
+    fn read_word(&self, offset: u64) -> Result<u64, SimError> {
+        self.read_value(offset, if self.is_64 { 8 } else { 4 })
+    }
+
+    /// A null terminated string
+    fn read_str(&self, offset: u64) -> Result<String, SimError> {
+        let tail = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| self.bytes.get(offset..))
+            .unwrap_or_default();
+        let len = tail
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(SimError::ImageError(format!(
+                "{}: no string at offset {:#x}",
+                self.source, offset
+            )))?;
+        Ok(String::from_utf8_lossy(&tail[..len]).to_string())
+    }
+
+    fn program_header(&self, offset: u64) -> Result<ElfProgramHeader, SimError> {
+        let fields = if self.is_64 {
+            [8, 16, 24, 32, 40]
+        } else {
+            [4, 8, 12, 16, 20]
+        }
+        .map(|field| self.read_word(offset.saturating_add(field)));
+        let [offset_in_file, virtual_address, physical_address, file_size, memory_size] = fields;
+        Ok(ElfProgramHeader {
+            segment_type: self.read_u32(offset)?,
+            offset: offset_in_file?,
+            virtual_address: virtual_address?,
+            physical_address: physical_address?,
+            file_size: file_size?,
+            memory_size: memory_size?,
+        })
+    }
+
+    fn section_header(&self, offset: u64) -> Result<ElfSectionHeader, SimError> {
+        let fields = if self.is_64 {
+            [8, 16, 24, 32]
+        } else {
+            [8, 12, 16, 20]
+        }
+        .map(|field| self.read_word(offset.saturating_add(field)));
+        let [flags, address, offset_in_file, size] = fields;
+        Ok(ElfSectionHeader {
+            name: self.read_u32(offset)?,
+            flags: flags?,
+            address: address?,
+            offset: offset_in_file?,
+            size: size?,
+        })
+    }
+}
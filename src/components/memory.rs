@@ -0,0 +1,562 @@
+use crate::component::Component;
+use crate::components::image::{MemoryImage, PlacedSegment, SegmentData};
+use crate::components::{check_width, get_mask, poll_clocked};
+use crate::error::SimError;
+use crate::rx::Rx;
+use crate::rx::RxType::NewValue;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::{ComponentId, Cycle, EventId, Input, Output};
+use crossbeam_channel::{unbounded, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// What a synchronous read port returns when a write port writes its address at the same clock tick
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReadMode {
+    /// The value before the write
+    #[default]
+    ReadFirst,
+    /// The value written
+    WriteFirst,
+}
+
+struct ReadPort {
+    address: Rx<u64>,
+    data: Tx<u64>,
+}
+
+struct WritePort {
+    address: Rx<u64>,
+    data: Rx<u64>,
+    enable: Rx<bool>,
+}
+
+/// Reads and writes the contents of a memory without going through its ports, e.g. from a testbench.
+///
+/// Asynchronous read ports do not see backdoor writes until their address changes.
+#[derive(Clone, Debug)]
+pub struct MemoryBackdoor {
+    name: String,
+    width: u32,
+    contents: Arc<Mutex<Vec<u64>>>,
+}
+
+impl MemoryBackdoor {
+    pub fn get_depth(&self) -> u64 {
+        self.contents.lock().unwrap().len() as u64
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    fn check_range(&self, address: u64, len: u64) -> Result<(), SimError> {
+        let depth = self.get_depth();
+        if address.checked_add(len).is_none_or(|end| end > depth) {
+            return Err(SimError::ComponentError(format!(
+                "{}: {} words at address {:#x} do not fit in {} words",
+                self.name, len, address, depth
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn read(&self, address: u64) -> Result<u64, SimError> {
+        self.check_range(address, 1)?;
+        Ok(self.contents.lock()?[address as usize])
+    }
+
+    /// Writes the low `width` bits of `value`
+    pub fn write(&self, address: u64, value: u64) -> Result<(), SimError> {
+        self.load(address, &[value])
+    }
+
+    /// Writes `values` from `address` on
+    pub fn load(&self, address: u64, values: &[u64]) -> Result<(), SimError> {
+        self.check_range(address, values.len() as u64)?;
+        let mask = get_mask(self.width);
+        let mut contents = self.contents.lock()?;
+        for (word, value) in contents[address as usize..].iter_mut().zip(values) {
+            *word = value & mask;
+        }
+        Ok(())
+    }
+
+    /// Loads the segments of `image` into the memory, its first word being at address `base` of the image:
+    /// a byte address for byte segments, a word address for word segments.
+    /// Bytes are packed into words least significant first, `width` rounded up to bytes per word.
+    ///
+    /// Returns where each segment was placed.
+    /// Fails without writing anything if a segment does not fit in the memory or overlaps another one.
+    pub fn load_image(
+        &self,
+        image: &MemoryImage,
+        base: u64,
+    ) -> Result<Vec<PlacedSegment>, SimError> {
+        let bytes_per_word = self.width.div_ceil(8) as u64;
+        let depth = self.get_depth();
+
+        // the bytes of the memory each segment covers, from its first word
+        let mut ranges = Vec::new();
+        for segment in image
+            .get_segments()
+            .iter()
+            .filter(|segment| !segment.is_empty())
+        {
+            let range = segment.address.checked_sub(base).and_then(|offset| {
+                let (start, len) = match &segment.data {
+                    SegmentData::Bytes(bytes) => (offset, bytes.len() as u64),
+                    SegmentData::ZeroFilled { len, .. } => (offset, *len),
+                    SegmentData::Words(words) => (
+                        offset.checked_mul(bytes_per_word)?,
+                        words.len() as u64 * bytes_per_word,
+                    ),
+                };
+                Some((start, start.checked_add(len)?))
+            });
+            match range {
+                Some((start, end)) if end <= depth.saturating_mul(bytes_per_word) => {
+                    ranges.push((start, end, segment))
+                }
+                _ => {
+                    return Err(SimError::ImageError(format!(
+                        "{}: segment \"{}\" at address {:#x} does not fit in {} words from address {:#x}",
+                        self.name, segment.name, segment.address, depth, base
+                    )))
+                }
+            }
+        }
+        ranges.sort_by_key(|(start, _, _)| *start);
+        for pair in ranges.windows(2) {
+            let ((_, end, first), (start, _, second)) = (&pair[0], &pair[1]);
+            if start < end {
+                return Err(SimError::ImageError(format!(
+                    "{}: segments \"{}\" and \"{}\" overlap",
+                    self.name, first.name, second.name
+                )));
+            }
+        }
+
+        let mask = get_mask(self.width);
+        let mut contents = self.contents.lock()?;
+        let mut placed = Vec::new();
+        for (start, end, segment) in ranges {
+            match &segment.data {
+                SegmentData::Bytes(bytes) | SegmentData::ZeroFilled { bytes, .. } => {
+                    // past its bytes, a zero filled segment is zeros up to its end
+                    let bytes = bytes.iter().copied().chain(std::iter::repeat(0));
+                    for (offset, byte) in (start..end).zip(bytes) {
+                        let word = &mut contents[(offset / bytes_per_word) as usize];
+                        let shift = offset % bytes_per_word * 8;
+                        *word = ((*word & !(0xff << shift)) | ((byte as u64) << shift)) & mask;
+                    }
+                }
+                SegmentData::Words(words) => {
+                    let start = (start / bytes_per_word) as usize;
+                    for (word, value) in contents[start..].iter_mut().zip(words) {
+                        *word = value & mask;
+                    }
+                }
+            }
+            placed.push(PlacedSegment {
+                name: segment.name.clone(),
+                address: segment.address,
+                start: start / bytes_per_word,
+                end: end.div_ceil(bytes_per_word),
+            });
+        }
+        Ok(placed)
+    }
+
+    /// A copy of every word
+    pub fn dump(&self) -> Result<Vec<u64>, SimError> {
+        Ok(self.contents.lock()?.clone())
+    }
+}
+
+/// Parses a memory initialization file: hexadecimal words separated by white space,
+/// `@<hex address>` moving to another word address, `//` starting a comment.
+///
+/// Returns the words at their addresses.
+pub(crate) fn parse_init_file<P: AsRef<Path>>(path: P) -> Result<Vec<(u64, u64)>, SimError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let mut words = Vec::new();
+    let mut address = 0;
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default();
+        for token in line.split_whitespace() {
+            let parse = |digits: &str| {
+                u64::from_str_radix(&digits.replace('_', ""), 16).map_err(|_| {
+                    SimError::ComponentError(format!(
+                        "{}:{}: \"{}\" is not a hexadecimal value",
+                        path.display(),
+                        line_number + 1,
+                        token
+                    ))
+                })
+            };
+            if let Some(digits) = token.strip_prefix('@') {
+                address = parse(digits)?;
+            } else {
+                words.push((address, parse(token)?));
+                address += 1;
+            }
+        }
+    }
+    Ok(words)
+}
+
+/// A memory of `depth` words of `width` bits with any number of read and write ports.
+///
+/// Write ports write `data` at `address` at the clock ticks when `enable` is set, in order, the last one winning.
+/// Read ports are synchronous by default: the address sampled at a clock tick is read onto `data`
+/// with a delay of `read_latency` cycles, see `ReadMode`.
+/// With a read latency of 0 they are asynchronous, `data` following `address` and the writes with no delay.
+/// Out of range reads return 0 and out of range writes are ignored.
+///
+/// The read ports are registered as `read<n>_address` and `read<n>_data`,
+/// the write ports as `write<n>_address`, `write<n>_data` and `write<n>_enable`.
+pub struct Memory {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    width: u32,
+    depth: u64,
+    read_latency: Cycle,
+    read_mode: ReadMode,
+    read_only: bool,
+    init_file: Option<PathBuf>,
+    image: Option<(MemoryImage, u64)>,
+    placed_segments: Vec<PlacedSegment>,
+    contents: Arc<Mutex<Vec<u64>>>,
+    /// The contents after `build`, restored by `Component::reset`
+    initial_contents: Vec<u64>,
+    read_ports: Vec<ReadPort>,
+    write_ports: Vec<WritePort>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl Memory {
+    /// A memory with synchronous reads and a read latency of 1
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        ack_sender: Sender<EventId>,
+        width: u32,
+        depth: u64,
+    ) -> Self {
+        let clock_tick_channel = unbounded();
+        Memory {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            width,
+            depth,
+            read_latency: 1,
+            read_mode: ReadMode::default(),
+            read_only: false,
+            init_file: None,
+            image: None,
+            placed_segments: Vec::new(),
+            contents: Arc::new(Mutex::new(Vec::new())),
+            initial_contents: Vec::new(),
+            read_ports: Vec::new(),
+            write_ports: Vec::new(),
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }
+    }
+
+    /// Same as `Memory::new`
+    pub fn sram(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        ack_sender: Sender<EventId>,
+        width: u32,
+        depth: u64,
+    ) -> Self {
+        Self::new(component_id, sim_manager, name, ack_sender, width, depth)
+    }
+
+    /// A memory without write ports, its contents coming from `Memory::with_init_file` or the backdoor
+    pub fn rom(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        ack_sender: Sender<EventId>,
+        width: u32,
+        depth: u64,
+    ) -> Self {
+        let mut rom = Self::new(component_id, sim_manager, name, ack_sender, width, depth);
+        rom.read_only = true;
+        rom
+    }
+
+    /// A memory with asynchronous reads
+    pub fn register_file(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        ack_sender: Sender<EventId>,
+        width: u32,
+        depth: u64,
+    ) -> Self {
+        Self::new(component_id, sim_manager, name, ack_sender, width, depth).with_read_latency(0)
+    }
+
+    pub fn with_read_port(mut self, address: Rx<u64>, data: Tx<u64>) -> Self {
+        self.read_ports.push(ReadPort { address, data });
+        self
+    }
+
+    pub fn with_write_port(mut self, address: Rx<u64>, data: Rx<u64>, enable: Rx<bool>) -> Self {
+        self.write_ports.push(WritePort {
+            address,
+            data,
+            enable,
+        });
+        self
+    }
+
+    pub fn with_read_latency(mut self, read_latency: Cycle) -> Self {
+        self.read_latency = read_latency;
+        self
+    }
+
+    pub fn with_read_mode(mut self, read_mode: ReadMode) -> Self {
+        self.read_mode = read_mode;
+        self
+    }
+
+    /// Initializes the contents from a file of hexadecimal words, see `$readmemh` in Verilog
+    pub fn with_init_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.init_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Loads `image` after the init file, if any, see `MemoryBackdoor::load_image`
+    pub fn with_image(mut self, image: MemoryImage, base: u64) -> Self {
+        self.image = Some((image, base));
+        self
+    }
+
+    /// Loads the init file and the image, if any
+    pub fn build(mut self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_width(&self.name, self.width)?;
+        if self.depth == 0 {
+            return Err(SimError::ComponentError(format!(
+                "{}: a memory needs at least one word",
+                self.name
+            )));
+        }
+        if self.read_only && !self.write_ports.is_empty() {
+            return Err(SimError::ComponentError(format!(
+                "{}: a ROM cannot have write ports",
+                self.name
+            )));
+        }
+        *self.contents.lock()? = vec![0; self.depth as usize];
+        if let Some(init_file) = self.init_file.as_ref() {
+            let backdoor = self.get_backdoor();
+            for (address, word) in parse_init_file(init_file)? {
+                backdoor.write(address, word)?;
+            }
+        }
+        if let Some((image, base)) = self.image.take() {
+            self.placed_segments = self.get_backdoor().load_image(&image, base)?;
+        }
+        self.initial_contents = self.contents.lock()?.clone();
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    pub fn get_backdoor(&self) -> MemoryBackdoor {
+        MemoryBackdoor {
+            name: self.name.clone(),
+            width: self.width,
+            contents: self.contents.clone(),
+        }
+    }
+
+    /// Where the segments of the image given to `Memory::with_image` were placed
+    pub fn get_placed_segments(&self) -> &[PlacedSegment] {
+        &self.placed_segments
+    }
+
+    fn read(&self, address: u64) -> u64 {
+        let contents = self.contents.lock().unwrap();
+        usize::try_from(address)
+            .ok()
+            .and_then(|address| contents.get(address))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn write(&mut self) {
+        let mask = get_mask(self.width);
+        let mut contents = self.contents.lock().unwrap();
+        for port in self.write_ports.iter() {
+            if !port.enable.get_value() {
+                continue;
+            }
+            let word = usize::try_from(port.address.get_value())
+                .ok()
+                .and_then(|address| contents.get_mut(address));
+            if let Some(word) = word {
+                *word = port.data.get_value() & mask;
+            }
+        }
+    }
+
+    /// Sends the words at the addresses of the read ports
+    fn read_all(&mut self) {
+        let values: Vec<u64> = self
+            .read_ports
+            .iter()
+            .map(|port| self.read(port.address.get_value()))
+            .collect();
+        for (port, value) in self.read_ports.iter_mut().zip(values) {
+            port.data.send(value, self.read_latency);
+        }
+    }
+
+    fn drain_inputs(&mut self) {
+        for port in self.write_ports.iter_mut() {
+            port.address.drain();
+            port.data.drain();
+            port.enable.drain();
+        }
+        if self.read_latency > 0 {
+            for port in self.read_ports.iter_mut() {
+                port.address.drain();
+            }
+        }
+    }
+
+    fn on_clock(&mut self) {
+        if self.read_latency == 0 {
+            self.write();
+            self.read_all();
+            return;
+        }
+        match self.read_mode {
+            ReadMode::ReadFirst => {
+                self.read_all();
+                self.write();
+            }
+            ReadMode::WriteFirst => {
+                self.write();
+                self.read_all();
+            }
+        }
+    }
+}
+
+impl Component for Memory {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        for (index, port) in self.read_ports.iter().enumerate() {
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("read{}_address", index),
+                    &port.address,
+                )
+                .unwrap();
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("read{}_data", index),
+                    &port.data,
+                )
+                .unwrap();
+        }
+        for (index, port) in self.write_ports.iter().enumerate() {
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("write{}_address", index),
+                    &port.address,
+                )
+                .unwrap();
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("write{}_data", index),
+                    &port.data,
+                )
+                .unwrap();
+            self.sim_manager
+                .register_port(
+                    self.component_id,
+                    &format!("write{}_enable", index),
+                    &port.enable,
+                )
+                .unwrap();
+        }
+        if self.read_latency == 0 {
+            self.read_all();
+        }
+    }
+
+    fn reset(&mut self) {
+        *self.contents.lock().unwrap() = self.initial_contents.clone();
+        for port in self.read_ports.iter_mut() {
+            port.address.reset();
+        }
+        for port in self.write_ports.iter_mut() {
+            port.address.reset();
+            port.data.reset();
+            port.enable.reset();
+        }
+    }
+
+    fn poll_recv(&mut self) {
+        // asynchronous read ports follow their address
+        if self.read_latency == 0 {
+            let mut changed = false;
+            for port in self.read_ports.iter_mut() {
+                changed |= port.address.try_recv() == NewValue;
+            }
+            if changed {
+                self.read_all();
+            }
+            for port in self.read_ports.iter_mut() {
+                port.address.ack();
+            }
+        }
+
+        poll_clocked(
+            self,
+            |memory| (&memory.clock_receiver, &memory.ack_sender),
+            Self::drain_inputs,
+            |memory, _| memory.on_clock(),
+        );
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(serde_json::to_value(&*self.contents.lock()?)?)
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        *self.contents.lock()? = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+pub mod adder;
+pub mod bus;
+pub mod comparator;
+pub mod decoder;
+pub mod fifo;
+pub mod image;
+pub mod memory;
+pub mod mux;
+pub mod register;
+pub mod shifter;
+pub mod sign_extender;
+
+use crate::error::SimError;
+use crate::types::{Cycle, EventId, Input};
+use crossbeam_channel::Sender;
+
+/// The bits of a `width` bit wide value
+pub(crate) fn get_mask(width: u32) -> u64 {
+    if width >= u64::BITS {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// Sign extends the low `width` bits of `value` to 64 bits
+pub(crate) fn sign_extend(value: u64, width: u32) -> u64 {
+    let shift = u64::BITS - width;
+    (((value << shift) as i64) >> shift) as u64
+}
+
+pub(crate) fn check_width(name: &str, width: u32) -> Result<(), SimError> {
+    if width == 0 || width > u64::BITS {
+        return Err(SimError::ComponentError(format!(
+            "{}: width {} is not between 1 and 64",
+            name, width
+        )));
+    }
+    Ok(())
+}
+
+/// The `poll_recv` of a clocked component, `get_clock` returning its clock receiver and ack sender.
+///
+/// The inputs are drained with `drain` on every poll. When the clock ticks they are drained again
+/// before calling `on_clock` with the cycle of the tick, since the values due at the clock tick
+/// were sent before it and may have arrived in between.
+pub(crate) fn poll_clocked<C>(
+    component: &mut C,
+    get_clock: fn(&C) -> (&Input, &Sender<EventId>),
+    mut drain: impl FnMut(&mut C),
+    on_clock: impl FnOnce(&mut C, Cycle),
+) {
+    drain(component);
+    if let Ok(event) = get_clock(component).0.try_recv() {
+        drain(component);
+        on_clock(component, event.get_scheduled_time());
+        get_clock(component).1.send(event.get_event_id()).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use crate::component::Component;
+use crate::error::SimError;
+use crate::event::EventValue;
+use crate::rx::Rx;
+use crate::rx::RxType::NewValue;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::ComponentId;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// An N-way multiplexer, sending the input picked by `select` on `output`, or `T::default()` if there is no such input.
+///
+/// The inputs are registered as `input0`, `input1`, ...
+/// Like the other combinational components, the mux drives the value of its inputs from `init`.
+pub struct Mux<
+    T: Default
+        + Copy
+        + Send
+        + Sync
+        + PartialEq
+        + Debug
+        + Serialize
+        + DeserializeOwned
+        + 'static
+        + EventValue,
+> {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    inputs: Vec<Rx<T>>,
+    select: Rx<u64>,
+    output: Tx<T>,
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > Mux<T>
+{
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        inputs: Vec<Rx<T>>,
+        select: Rx<u64>,
+        output: Tx<T>,
+    ) -> Self {
+        Mux {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            inputs,
+            select,
+            output,
+        }
+    }
+
+    pub fn build(self) -> Result<Arc<Mutex<Self>>, SimError> {
+        if self.inputs.is_empty() {
+            return Err(SimError::ComponentError(format!(
+                "{}: a mux needs at least one input",
+                self.name
+            )));
+        }
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn on_comb(&mut self) {
+        let value = usize::try_from(self.select.get_value())
+            .ok()
+            .and_then(|select| self.inputs.get(select))
+            .map(|input| input.get_value())
+            .unwrap_or_default();
+        self.output.send(value, 0);
+    }
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > Component for Mux<T>
+{
+    fn init(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        for (index, input) in self.inputs.iter().enumerate() {
+            self.sim_manager
+                .register_port(self.component_id, &format!("input{}", index), input)
+                .unwrap();
+        }
+        self.sim_manager
+            .register_port(self.component_id, "select", &self.select)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        self.on_comb();
+    }
+
+    fn reset(&mut self) {
+        self.inputs.iter_mut().for_each(|input| input.reset());
+        self.select.reset();
+    }
+
+    fn poll_recv(&mut self) {
+        let mut changed = self.select.try_recv() == NewValue;
+        for input in self.inputs.iter_mut() {
+            changed |= input.try_recv() == NewValue;
+        }
+        if changed {
+            self.on_comb();
+        }
+        self.inputs.iter_mut().for_each(|input| input.ack());
+        self.select.ack();
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
@@ -0,0 +1,204 @@
+use crate::component::Component;
+use crate::components::poll_clocked;
+use crate::error::SimError;
+use crate::event::EventValue;
+use crate::rx::Rx;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::{ComponentId, EventId, Input, Output};
+use crossbeam_channel::{unbounded, Sender};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// A register loading `data` at every clock tick, or only when `enable` is set if given one.
+///
+/// With a `reset` input, the register goes back to its reset value at the clock ticks when `reset` is set,
+/// `reset` taking precedence over `enable`.
+///
+/// The register drives its value from `init`, so the components reading `output` see the reset value
+/// before the first clock tick, as the combinational components drive the value of their inputs from `init`.
+pub struct Register<
+    T: Default
+        + Copy
+        + Send
+        + Sync
+        + PartialEq
+        + Debug
+        + Serialize
+        + DeserializeOwned
+        + 'static
+        + EventValue,
+> {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    data: Rx<T>,
+    enable: Option<Rx<bool>>,
+    reset: Option<Rx<bool>>,
+    output: Tx<T>,
+    reset_value: T,
+    value: T,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > Register<T>
+{
+    /// The register registers itself as `name`, it holds `T::default()` until the first clock tick
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        data: Rx<T>,
+        output: Tx<T>,
+        ack_sender: Sender<EventId>,
+    ) -> Self {
+        let clock_tick_channel = unbounded();
+        Register {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            data,
+            enable: None,
+            reset: None,
+            output,
+            reset_value: T::default(),
+            value: T::default(),
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }
+    }
+
+    pub fn with_enable(mut self, enable: Rx<bool>) -> Self {
+        self.enable = Some(enable);
+        self
+    }
+
+    /// Holds `reset_value` from the start, and again after a clock tick with `reset` set
+    pub fn with_reset(mut self, reset: Rx<bool>, reset_value: T) -> Self {
+        self.reset = Some(reset);
+        self.reset_value = reset_value;
+        self.value = reset_value;
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Mutex<Self>>, SimError> {
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    pub fn get_value(&self) -> T {
+        self.value
+    }
+
+    fn drain_inputs(&mut self) {
+        self.data.drain();
+        if let Some(enable) = self.enable.as_mut() {
+            enable.drain();
+        }
+        if let Some(reset) = self.reset.as_mut() {
+            reset.drain();
+        }
+    }
+
+    fn on_clock(&mut self) {
+        let value = if self.reset.as_ref().is_some_and(|reset| reset.get_value()) {
+            self.reset_value
+        } else if self.enable.as_ref().is_none_or(|enable| enable.get_value()) {
+            self.data.get_value()
+        } else {
+            self.value
+        };
+        if value != self.value {
+            self.value = value;
+            self.output.send(value, 0);
+        }
+    }
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > Component for Register<T>
+{
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "data", &self.data)
+            .unwrap();
+        if let Some(enable) = self.enable.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "enable", enable)
+                .unwrap();
+        }
+        if let Some(reset) = self.reset.as_ref() {
+            self.sim_manager
+                .register_port(self.component_id, "reset", reset)
+                .unwrap();
+        }
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        self.output.send(self.value, 0);
+    }
+
+    fn reset(&mut self) {
+        self.value = self.reset_value;
+        self.data.reset();
+        if let Some(enable) = self.enable.as_mut() {
+            enable.reset();
+        }
+        if let Some(reset) = self.reset.as_mut() {
+            reset.reset();
+        }
+    }
+
+    fn poll_recv(&mut self) {
+        poll_clocked(
+            self,
+            |register| (&register.clock_receiver, &register.ack_sender),
+            Self::drain_inputs,
+            |register, _| register.on_clock(),
+        );
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(serde_json::to_value(self.value)?)
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.value = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
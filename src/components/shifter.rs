@@ -0,0 +1,122 @@
+use crate::component::Component;
+use crate::components::{check_width, get_mask, sign_extend};
+use crate::error::SimError;
+use crate::rx::Rx;
+use crate::rx::RxType::NewValue;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::ComponentId;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShiftKind {
+    #[default]
+    Left,
+    LogicalRight,
+    /// Shifts in copies of the sign bit
+    ArithmeticRight,
+}
+
+/// A shifter of `width` bit values, shifting `value` by `amount` bits.
+///
+/// Shifting by `width` bits or more shifts every bit out.
+pub struct Shifter {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    width: u32,
+    kind: ShiftKind,
+    value: Rx<u64>,
+    amount: Rx<u64>,
+    output: Tx<u64>,
+}
+
+impl Shifter {
+    /// A 64 bit left shifter by default
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        value: Rx<u64>,
+        amount: Rx<u64>,
+        output: Tx<u64>,
+    ) -> Self {
+        Shifter {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            width: u64::BITS,
+            kind: ShiftKind::default(),
+            value,
+            amount,
+            output,
+        }
+    }
+
+    pub fn with_width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: ShiftKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_width(&self.name, self.width)?;
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn on_comb(&mut self) {
+        let mask = get_mask(self.width);
+        let value = self.value.get_value() & mask;
+        let amount = self.amount.get_value().min(self.width as u64) as u32;
+        let shifted = match self.kind {
+            ShiftKind::Left => value.checked_shl(amount).unwrap_or(0),
+            ShiftKind::LogicalRight => value.checked_shr(amount).unwrap_or(0),
+            ShiftKind::ArithmeticRight => {
+                ((sign_extend(value, self.width) as i64) >> amount.min(u64::BITS - 1)) as u64
+            }
+        };
+        self.output.send(shifted & mask, 0);
+    }
+}
+
+impl Component for Shifter {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "value", &self.value)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "amount", &self.amount)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        self.on_comb();
+    }
+
+    fn reset(&mut self) {
+        self.value.reset();
+        self.amount.reset();
+    }
+
+    fn poll_recv(&mut self) {
+        let mut changed = self.value.try_recv() == NewValue;
+        changed |= self.amount.try_recv() == NewValue;
+        if changed {
+            self.on_comb();
+        }
+        self.value.ack();
+        self.amount.ack();
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
@@ -0,0 +1,94 @@
+use crate::component::Component;
+use crate::components::{check_width, get_mask, sign_extend};
+use crate::error::SimError;
+use crate::rx::Rx;
+use crate::rx::RxType::NewValue;
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::ComponentId;
+use std::sync::{Arc, Mutex};
+
+/// Sign extends the low `from_width` bits of `input` to `to_width` bits, e.g. the immediates of an instruction
+pub struct SignExtender {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    from_width: u32,
+    to_width: u32,
+    input: Rx<u64>,
+    output: Tx<u64>,
+}
+
+impl SignExtender {
+    /// Extends to 64 bits by default
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        from_width: u32,
+        input: Rx<u64>,
+        output: Tx<u64>,
+    ) -> Self {
+        SignExtender {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            from_width,
+            to_width: u64::BITS,
+            input,
+            output,
+        }
+    }
+
+    pub fn with_width(mut self, to_width: u32) -> Self {
+        self.to_width = to_width;
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Mutex<Self>>, SimError> {
+        check_width(&self.name, self.from_width)?;
+        check_width(&self.name, self.to_width)?;
+        if self.from_width > self.to_width {
+            return Err(SimError::ComponentError(format!(
+                "{}: cannot extend {} bits to {} bits",
+                self.name, self.from_width, self.to_width
+            )));
+        }
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    fn on_comb(&mut self) {
+        let value = sign_extend(self.input.get_value(), self.from_width);
+        self.output.send(value & get_mask(self.to_width), 0);
+    }
+}
+
+impl Component for SignExtender {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "input", &self.input)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        self.on_comb();
+    }
+
+    fn reset(&mut self) {
+        self.input.reset();
+    }
+
+    fn poll_recv(&mut self) {
+        if self.input.try_recv() == NewValue {
+            self.on_comb();
+        }
+        self.input.ack();
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
@@ -0,0 +1,497 @@
+use crate::error::SimError;
+use crate::types::Cycle;
+use crate::util::parse_integer;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BinKind {
+    /// Values equal to the given one, compared as in `CompareOp::holds`
+    Value(String),
+    /// Integer values within the inclusive range
+    Range(i128, i128),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bin {
+    pub name: String,
+    pub kind: BinKind,
+}
+
+impl Bin {
+    pub fn value(name: &str, value: &str) -> Self {
+        Bin {
+            name: name.to_string(),
+            kind: BinKind::Value(value.to_string()),
+        }
+    }
+
+    pub fn range(name: &str, low: i128, high: i128) -> Self {
+        Bin {
+            name: name.to_string(),
+            kind: BinKind::Range(low, high),
+        }
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        match &self.kind {
+            BinKind::Value(expected) => match (parse_integer(value), parse_integer(expected)) {
+                (Some(value), Some(expected)) => value == expected,
+                _ => value == expected,
+            },
+            BinKind::Range(low, high) => {
+                parse_integer(value).is_some_and(|value| *low <= value && value <= *high)
+            }
+        }
+    }
+}
+
+/// The values of a registered port, sorted into bins.
+/// Without bins, every distinct value gets its own bin, named after the value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coverpoint {
+    pub name: String,
+    pub port: String,
+    pub bins: Vec<Bin>,
+}
+
+impl Coverpoint {
+    pub fn new(name: &str, port: &str, bins: Vec<Bin>) -> Self {
+        Coverpoint {
+            name: name.to_string(),
+            port: port.to_string(),
+            bins,
+        }
+    }
+
+    /// The bin the value falls in, if any
+    fn get_bin(&self, value: &str) -> Option<String> {
+        if self.bins.is_empty() {
+            return Some(value.to_string());
+        }
+        self.bins
+            .iter()
+            .find(|bin| bin.matches(value))
+            .map(|bin| bin.name.clone())
+    }
+}
+
+/// The combinations of the bins of coverpoints of the same covergroup
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cross {
+    pub name: String,
+    pub coverpoints: Vec<String>,
+}
+
+impl Cross {
+    pub fn new(name: &str, coverpoints: &[&str]) -> Self {
+        Cross {
+            name: name.to_string(),
+            coverpoints: coverpoints.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+/// Coverpoints and crosses sampled together at the clock tick of every `sample_interval` cycles
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Covergroup {
+    pub name: String,
+    pub coverpoints: Vec<Coverpoint>,
+    pub crosses: Vec<Cross>,
+    pub sample_interval: Cycle,
+}
+
+impl Covergroup {
+    pub fn new(name: &str, coverpoints: Vec<Coverpoint>, crosses: Vec<Cross>) -> Self {
+        Covergroup {
+            name: name.to_string(),
+            coverpoints,
+            crosses,
+            sample_interval: 1,
+        }
+    }
+
+    fn get_coverpoint(&self, name: &str) -> Option<&Coverpoint> {
+        self.coverpoints
+            .iter()
+            .find(|coverpoint| coverpoint.name == name)
+    }
+
+    /// Checks the sample interval and that the crosses refer to coverpoints of the group
+    pub(crate) fn validate(&self) -> Result<(), SimError> {
+        if self.sample_interval == 0 {
+            return Err(SimError::CoverageError(format!(
+                "covergroup \"{}\" has a sample interval of 0",
+                self.name
+            )));
+        }
+        for cross in self.crosses.iter() {
+            if let Some(name) = cross
+                .coverpoints
+                .iter()
+                .find(|name| self.get_coverpoint(name).is_none())
+            {
+                return Err(SimError::CoverageError(format!(
+                    "cross \"{}\" refers to unknown coverpoint \"{}\"",
+                    cross.name, name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The coverage of the group before any sample, every explicit bin at 0
+    fn empty_coverage(&self) -> GroupCoverage {
+        let coverpoints = self
+            .coverpoints
+            .iter()
+            .map(|coverpoint| {
+                let bins = coverpoint.bins.iter().map(|bin| vec![bin.name.clone()]);
+                (coverpoint.name.clone(), PointCoverage::new(bins))
+            })
+            .collect();
+        let crosses = self
+            .crosses
+            .iter()
+            .map(|cross| {
+                let bins = cross
+                    .coverpoints
+                    .iter()
+                    .fold(vec![Vec::new()], |combos, name| {
+                        let bins = &self.get_coverpoint(name).unwrap().bins;
+                        combos
+                            .iter()
+                            .flat_map(|combo| {
+                                bins.iter().map(move |bin| {
+                                    [combo.clone(), vec![bin.name.clone()]].concat()
+                                })
+                            })
+                            .collect()
+                    });
+                // a cross over an automatic coverpoint is automatic too
+                let automatic = cross
+                    .coverpoints
+                    .iter()
+                    .any(|name| self.get_coverpoint(name).unwrap().bins.is_empty());
+                let bins = if automatic { Vec::new() } else { bins };
+                (cross.name.clone(), PointCoverage::new(bins.into_iter()))
+            })
+            .collect();
+        GroupCoverage {
+            samples: 0,
+            coverpoints,
+            crosses,
+        }
+    }
+}
+
+/// Hit counts of the bins of a coverpoint or a cross, cross bins are named `a x b`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PointCoverage {
+    pub bins: BTreeMap<String, u64>,
+    /// Whether bins are created from the values seen, in which case there is no coverage goal
+    pub automatic: bool,
+}
+
+impl PointCoverage {
+    fn new<I: Iterator<Item = Vec<String>>>(bins: I) -> Self {
+        let bins: BTreeMap<String, u64> = bins.map(|names| (names.join(" x "), 0)).collect();
+        PointCoverage {
+            automatic: bins.is_empty(),
+            bins,
+        }
+    }
+
+    /// The fraction of bins hit, `None` for automatic bins
+    pub fn get_coverage(&self) -> Option<f64> {
+        if self.automatic {
+            return None;
+        }
+        let hit = self.bins.values().filter(|count| **count > 0).count();
+        Some(hit as f64 / self.bins.len() as f64)
+    }
+
+    fn merge(&mut self, other: &PointCoverage) {
+        self.automatic &= other.automatic;
+        for (bin, count) in other.bins.iter() {
+            *self.bins.entry(bin.clone()).or_insert(0) += count;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroupCoverage {
+    pub samples: u64,
+    pub coverpoints: BTreeMap<String, PointCoverage>,
+    pub crosses: BTreeMap<String, PointCoverage>,
+}
+
+impl GroupCoverage {
+    /// The mean coverage of the coverpoints and crosses with explicit bins, `None` if there are none
+    pub fn get_coverage(&self) -> Option<f64> {
+        let coverages: Vec<f64> = self
+            .coverpoints
+            .values()
+            .chain(self.crosses.values())
+            .filter_map(|point| point.get_coverage())
+            .collect();
+        if coverages.is_empty() {
+            return None;
+        }
+        Some(coverages.iter().sum::<f64>() / coverages.len() as f64)
+    }
+
+    fn merge(&mut self, other: &GroupCoverage) {
+        self.samples += other.samples;
+        for (name, point) in other.coverpoints.iter() {
+            self.coverpoints
+                .entry(name.clone())
+                .or_insert_with(|| PointCoverage {
+                    automatic: true,
+                    ..Default::default()
+                })
+                .merge(point);
+        }
+        for (name, point) in other.crosses.iter() {
+            self.crosses
+                .entry(name.clone())
+                .or_insert_with(|| PointCoverage {
+                    automatic: true,
+                    ..Default::default()
+                })
+                .merge(point);
+        }
+    }
+}
+
+/// The functional coverage of one or more runs
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub runs: u64,
+    /// The master seeds of the runs that drew random numbers, see `SimManager::get_seed`,
+    /// followed by the seeds of the components drawing from a stream of their own, see `SimManager::register_seed`
+    #[serde(default)]
+    pub seeds: Vec<u64>,
+    pub groups: BTreeMap<String, GroupCoverage>,
+}
+
+impl CoverageReport {
+    /// Adds the hits of `other` to this report, bins are matched by name
+    pub fn merge(&mut self, other: &CoverageReport) {
+        self.runs += other.runs;
+        self.seeds.extend(other.seeds.iter());
+        for (name, group) in other.groups.iter() {
+            self.groups.entry(name.clone()).or_default().merge(group);
+        }
+    }
+
+    /// Merges the JSON reports of several runs
+    pub fn merge_files<P: AsRef<Path>>(paths: &[P]) -> Result<CoverageReport, SimError> {
+        let mut report = CoverageReport::default();
+        for path in paths {
+            report.merge(&CoverageReport::load_json(path)?);
+        }
+        Ok(report)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<CoverageReport, SimError> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    pub fn save_text<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+fn format_coverage(coverage: Option<f64>) -> String {
+    match coverage {
+        Some(coverage) => format!("{:.2}%", coverage * 100.0),
+        None => "automatic bins".to_string(),
+    }
+}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "functional coverage over {} run(s)", self.runs)?;
+        if !self.seeds.is_empty() {
+            let seeds: Vec<String> = self.seeds.iter().map(|seed| seed.to_string()).collect();
+            writeln!(f, "seeds: {}", seeds.join(", "))?;
+        }
+        for (name, group) in self.groups.iter() {
+            writeln!(
+                f,
+                "covergroup {}: {} samples, {}",
+                name,
+                group.samples,
+                format_coverage(group.get_coverage())
+            )?;
+            let points = group
+                .coverpoints
+                .iter()
+                .map(|point| ("coverpoint", point))
+                .chain(group.crosses.iter().map(|point| ("cross", point)));
+            for (kind, (name, point)) in points {
+                writeln!(
+                    f,
+                    "  {} {}: {}",
+                    kind,
+                    name,
+                    format_coverage(point.get_coverage())
+                )?;
+                for (bin, count) in point.bins.iter() {
+                    writeln!(
+                        f,
+                        "    {}: {}{}",
+                        bin,
+                        count,
+                        if *count == 0 { " (missed)" } else { "" }
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The covergroups of a simulation and the coverage collected so far
+#[derive(Debug, Default)]
+pub struct CoverageCollector {
+    groups: Vec<Covergroup>,
+    report: CoverageReport,
+    report_path: Option<PathBuf>,
+    seed: Option<u64>,
+    component_seeds: Vec<u64>,
+}
+
+impl CoverageCollector {
+    pub fn add(&mut self, group: Covergroup) {
+        self.report.runs = 1;
+        self.report
+            .groups
+            .insert(group.name.clone(), group.empty_coverage());
+        self.groups.push(group);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Every port sampled by a covergroup
+    pub fn get_ports(&self) -> Vec<String> {
+        let mut ports: Vec<String> = self
+            .groups
+            .iter()
+            .flat_map(|group| group.coverpoints.iter().map(|point| point.port.clone()))
+            .collect();
+        ports.sort();
+        ports.dedup();
+        ports
+    }
+
+    /// Samples the covergroups due in `cycle`
+    pub fn sample(&mut self, cycle: Cycle, values: &BTreeMap<String, String>) {
+        for group in self.groups.iter() {
+            if !cycle.is_multiple_of(group.sample_interval) {
+                continue;
+            }
+            let coverage = self.report.groups.get_mut(&group.name).unwrap();
+            coverage.samples += 1;
+
+            let mut hits = BTreeMap::new();
+            for coverpoint in group.coverpoints.iter() {
+                let Some(bin) = values
+                    .get(&coverpoint.port)
+                    .and_then(|value| coverpoint.get_bin(value))
+                else {
+                    continue;
+                };
+                *coverage
+                    .coverpoints
+                    .get_mut(&coverpoint.name)
+                    .unwrap()
+                    .bins
+                    .entry(bin.clone())
+                    .or_insert(0) += 1;
+                hits.insert(coverpoint.name.as_str(), bin);
+            }
+            for cross in group.crosses.iter() {
+                let bins: Option<Vec<&str>> = cross
+                    .coverpoints
+                    .iter()
+                    .map(|name| hits.get(name.as_str()).map(|bin| bin.as_str()))
+                    .collect();
+                if let Some(bins) = bins {
+                    *coverage
+                        .crosses
+                        .get_mut(&cross.name)
+                        .unwrap()
+                        .bins
+                        .entry(bins.join(" x "))
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    pub fn get_report(&self) -> &CoverageReport {
+        &self.report
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+        self.update_seeds();
+    }
+
+    pub fn add_component_seed(&mut self, seed: u64) {
+        self.component_seeds.push(seed);
+        self.update_seeds();
+    }
+
+    fn update_seeds(&mut self) {
+        self.report.seeds = self
+            .seed
+            .into_iter()
+            .chain(self.component_seeds.iter().copied())
+            .collect();
+    }
+
+    pub(crate) fn save(&self) -> BTreeMap<String, GroupCoverage> {
+        self.report.groups.clone()
+    }
+
+    /// Brings the hits back to `groups`, the covergroups added since start over
+    pub(crate) fn restore(&mut self, groups: &BTreeMap<String, GroupCoverage>) {
+        for group in self.groups.iter() {
+            let coverage = groups
+                .get(&group.name)
+                .cloned()
+                .unwrap_or_else(|| group.empty_coverage());
+            self.report.groups.insert(group.name.clone(), coverage);
+        }
+    }
+
+    pub fn set_report_path(&mut self, path: Option<PathBuf>) {
+        self.report_path = path;
+    }
+
+    /// Writes the report as text and JSON, if a path was set
+    pub fn write_report(&self) -> Result<(), SimError> {
+        if let Some(path) = self.report_path.as_ref() {
+            self.report.save_text(path.with_extension("txt"))?;
+            self.report.save_json(path.with_extension("json"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,333 @@
+use crate::error::SimError;
+use crate::event::EventValue;
+use crate::rx::{Rx, RxType};
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::{ComponentId, Cycle, EventId};
+use crossbeam_channel::Sender;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Builds the two ends of a link called `name` with credit-based flow control,
+/// the name its protocol violations are reported under.
+///
+/// Values take `latency` cycles to reach the receiving end, which has a buffer of `depth` values.
+/// The sending end starts with `depth` credits, spends one per value sent,
+/// and gets it back `latency` cycles after the value is taken out of the buffer.
+/// Each end must call `poll` from `Component::poll_recv`, and again when a clock tick is received
+/// so that the values and credits due in the cycle are seen.
+pub fn credit_link<
+    T: Default
+        + Copy
+        + Send
+        + Sync
+        + PartialEq
+        + Debug
+        + Serialize
+        + DeserializeOwned
+        + 'static
+        + EventValue,
+>(
+    sim_manager: Arc<SimManager>,
+    ack_sender: Sender<EventId>,
+    name: &str,
+    latency: Cycle,
+    depth: u32,
+) -> Result<(CreditTx<T>, CreditRx<T>), SimError> {
+    if latency == 0 {
+        return Err(SimError::ProtocolError(format!(
+            "link \"{}\" needs a latency of at least 1 cycle",
+            name
+        )));
+    }
+    if depth == 0 {
+        return Err(SimError::ProtocolError(format!(
+            "link \"{}\" needs a buffer of at least 1 value",
+            name
+        )));
+    }
+    let mut data = Tx::new(sim_manager.clone(), ack_sender.clone());
+    let mut credit = Tx::new(sim_manager.clone(), ack_sender);
+    let data_rx = data.add_rx();
+    let credit_rx = credit.add_rx();
+    Ok((
+        CreditTx::new(sim_manager.clone(), name, data, credit_rx, latency, depth),
+        CreditRx::new(sim_manager, name, data_rx, credit, latency, depth),
+    ))
+}
+
+/// The sending end of a credit-based link, each event on `credit` returning as many credits as its value
+pub struct CreditTx<
+    T: Default
+        + Copy
+        + Send
+        + Sync
+        + PartialEq
+        + Debug
+        + Serialize
+        + DeserializeOwned
+        + 'static
+        + EventValue,
+> {
+    sim_manager: Arc<SimManager>,
+    name: String,
+    data: Tx<T>,
+    credit: Rx<u32>,
+    latency: Cycle,
+    depth: u32,
+    credits: u32,
+    sent_count: u64,
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > CreditTx<T>
+{
+    /// The end of the link called `name` made of existing ports, e.g. to check a receiver not using `CreditRx`
+    pub fn new(
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        data: Tx<T>,
+        credit: Rx<u32>,
+        latency: Cycle,
+        depth: u32,
+    ) -> Self {
+        CreditTx {
+            sim_manager,
+            name: name.to_string(),
+            data,
+            credit,
+            latency,
+            depth,
+            credits: depth,
+            sent_count: 0,
+        }
+    }
+
+    /// Registers the values as `name` and the credits as `<name>_credit`
+    pub fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        sim_manager.register_port(component_id, name, &self.data)?;
+        sim_manager.register_port(component_id, &format!("{}_credit", name), &self.credit)
+    }
+
+    /// Receives the credits returned, reporting a credit overflow if there are more than the buffer holds
+    pub fn poll(&mut self) {
+        while self.credit.try_recv() != RxType::NoValue {
+            let returned = self.credit.get_value();
+            self.credit.ack();
+            let credits = self.credits.saturating_add(returned);
+            if credits > self.depth {
+                self.sim_manager
+                    .report_violation(
+                        &self.name,
+                        format!(
+                            "credit overflow, {} credits for a buffer of {}",
+                            credits, self.depth
+                        ),
+                    )
+                    .unwrap();
+            }
+            self.credits = credits.min(self.depth);
+        }
+    }
+
+    /// Whether there is a credit to send a value
+    pub fn can_send(&self) -> bool {
+        self.credits > 0
+    }
+
+    pub fn get_credits(&self) -> u32 {
+        self.credits
+    }
+
+    /// Sends `value` for a credit, without a credit it is not sent and a credit underflow is reported
+    pub fn send(&mut self, value: T) -> Result<(), SimError> {
+        if self.credits == 0 {
+            let message = format!("credit underflow, cannot send {:?} with no credit", value);
+            self.sim_manager
+                .report_violation(&self.name, message.clone())?;
+            return Err(SimError::ProtocolError(message));
+        }
+        self.credits -= 1;
+        self.sent_count += 1;
+        self.data.send(value, self.latency);
+        Ok(())
+    }
+
+    pub fn get_sent_count(&self) -> u64 {
+        self.sent_count
+    }
+
+    pub fn reset(&mut self) {
+        self.credits = self.depth;
+        self.sent_count = 0;
+        self.credit.reset();
+    }
+
+    /// The state of the end, for `Component::save_state`
+    pub fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "credits": self.credits,
+            "sent_count": self.sent_count,
+        }))
+    }
+
+    pub fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.credits = serde_json::from_value(state["credits"].clone())?;
+        self.sent_count = serde_json::from_value(state["sent_count"].clone())?;
+        Ok(())
+    }
+}
+
+/// The receiving end of a credit-based link, holding the values received until they are taken
+pub struct CreditRx<
+    T: Default
+        + Copy
+        + Send
+        + Sync
+        + PartialEq
+        + Debug
+        + Serialize
+        + DeserializeOwned
+        + 'static
+        + EventValue,
+> {
+    sim_manager: Arc<SimManager>,
+    name: String,
+    data: Rx<T>,
+    credit: Tx<u32>,
+    latency: Cycle,
+    depth: u32,
+    buffer: VecDeque<T>,
+    received_count: u64,
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > CreditRx<T>
+{
+    /// The end of the link called `name` made of existing ports, e.g. to check a sender not using `CreditTx`
+    pub fn new(
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        data: Rx<T>,
+        credit: Tx<u32>,
+        latency: Cycle,
+        depth: u32,
+    ) -> Self {
+        CreditRx {
+            sim_manager,
+            name: name.to_string(),
+            data,
+            credit,
+            latency,
+            depth,
+            buffer: VecDeque::new(),
+            received_count: 0,
+        }
+    }
+
+    /// Registers the values as `name` and the credits as `<name>_credit`
+    pub fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        sim_manager.register_port(component_id, name, &self.data)?;
+        sim_manager.register_port(component_id, &format!("{}_credit", name), &self.credit)
+    }
+
+    /// Receives the values sent, a value arriving with the buffer full is dropped and reported as an overflow
+    pub fn poll(&mut self) {
+        while self.data.try_recv() != RxType::NoValue {
+            let value = self.data.get_value();
+            self.data.ack();
+            self.received_count += 1;
+            if self.buffer.len() < self.depth as usize {
+                self.buffer.push_back(value);
+            } else {
+                self.sim_manager
+                    .report_violation(
+                        &self.name,
+                        format!(
+                            "buffer overflow, {:?} received with {} values buffered",
+                            value, self.depth
+                        ),
+                    )
+                    .unwrap();
+            }
+        }
+    }
+
+    /// The oldest value received, if not taken yet
+    pub fn peek(&self) -> Option<T> {
+        self.buffer.front().copied()
+    }
+
+    /// Takes the oldest value received, returning its credit
+    pub fn take(&mut self) -> Option<T> {
+        let value = self.buffer.pop_front()?;
+        self.credit.send(1, self.latency);
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn get_received_count(&self) -> u64 {
+        self.received_count
+    }
+
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.received_count = 0;
+        self.data.reset();
+    }
+
+    /// The state of the end, for `Component::save_state`
+    pub fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "buffer": self.buffer,
+            "received_count": self.received_count,
+        }))
+    }
+
+    pub fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.buffer = serde_json::from_value(state["buffer"].clone())?;
+        self.received_count = serde_json::from_value(state["received_count"].clone())?;
+        Ok(())
+    }
+}
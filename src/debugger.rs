@@ -0,0 +1,449 @@
+use crate::error::SimError;
+use crate::sim_manager::SimManager;
+use crate::types::Cycle;
+use crate::util::parse_integer;
+use crate::watchpoint::{CompareOp, WatchCondition, WatchpointId};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    /// Stops at the end of the given cycle
+    Cycle(Cycle),
+    /// Stops at the end of any cycle where the port value compares to the given value
+    Value {
+        port: String,
+        op: CompareOp,
+        value: String,
+    },
+    /// Stops at the end of any cycle where the port value is different from the cycle before
+    Changed { port: String },
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::Cycle(cycle) => write!(f, "cycle {}", cycle),
+            Condition::Value { port, op, value } => write!(f, "{} {} {}", port, op, value),
+            Condition::Changed { port } => write!(f, "{} changed", port),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub condition: Condition,
+    pub enabled: bool,
+}
+
+const HELP: &str = "\
+commands:
+  step [n]                    run n cycles (default 1), ignoring breakpoints
+  run <n>                     run n cycles, stopping at breakpoints
+  continue                    run until a breakpoint or the end of the simulation
+  back [n]                    go back n cycles (default 1), needs snapshots
+  goto <cycle>                go to the end of the given cycle, needs snapshots to go back
+  break cycle <n>             stop at the end of cycle n
+  break <port> <op> <value>   stop when the port compares to the value, op is one of == != < <= > >=
+  break <port> changed        stop when the port value changes
+  breakpoints                 list breakpoints
+  enable <id> | disable <id>  enable or disable a breakpoint
+  delete <id>                 remove a breakpoint
+  wpoint <port> [<op> <value>]
+                              stop at the end of a cycle where the output port is written,
+                              optionally only with a value comparing to the given one
+  wpoints                     list watchpoints
+  wenable <id> | wdisable <id>
+                              enable or disable a watchpoint
+  wdelete <id>                remove a watchpoint
+  print <port>                print the value of a port
+  watch <port>                print the port every time it changes
+  unwatch <port>              stop watching the port
+  list [prefix]               list components and their ports
+  cycle                       print the current cycle
+  help                        print this message
+  quit                        leave the debugger";
+
+/// A `Debugger` is a command line interface driving a `SimManager` cycle by cycle,
+/// with breakpoints on cycles and port values, see `HELP` or the `help` command.
+///
+/// The netlist is built by the user as usual, the debugger then takes the place of `SimManager::run`:
+/// ```ignore
+/// sim_dispatchers.iter().for_each(|s| s.init());
+/// // spawn the dispatchers
+/// Debugger::new(sim_manager.clone()).run_stdio()?;
+/// ```
+/// Ports and components must be registered to be visible, see `SimManager::register_port`.
+/// `examples/rsim_dbg.rs` is a complete entry point, run with `cargo run --example rsim_dbg`.
+pub struct Debugger {
+    sim_manager: Arc<SimManager>,
+    breakpoints: Vec<Breakpoint>,
+    next_breakpoint_id: usize,
+    watches: Vec<String>,
+    last_values: HashMap<String, String>,
+}
+
+impl Debugger {
+    pub fn new(sim_manager: Arc<SimManager>) -> Self {
+        Debugger {
+            sim_manager,
+            breakpoints: Vec::new(),
+            next_breakpoint_id: 1,
+            watches: Vec::new(),
+            last_values: HashMap::new(),
+        }
+    }
+
+    /// Runs the debugger on the standard input and output
+    pub fn run_stdio(&mut self) -> Result<(), SimError> {
+        let stdin = std::io::stdin();
+        self.run(stdin.lock(), std::io::stdout())
+    }
+
+    /// Reads commands from `input` until `quit` or the end of the input
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<(), SimError> {
+        let mut lines = input.lines();
+        loop {
+            write!(output, "(rsim) ")?;
+            output.flush()?;
+            let Some(line) = lines.next() else {
+                writeln!(output)?;
+                return Ok(());
+            };
+            match self.execute(&line?, &mut output) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(SimError::IoError(e)) => return Err(SimError::IoError(e)),
+                Err(e) => writeln!(output, "error: {}", e)?,
+            }
+        }
+    }
+
+    /// Executes a single command, returns whether the debugger should quit
+    pub fn execute<W: Write>(&mut self, line: &str, output: &mut W) -> Result<bool, SimError> {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = args.split_first() else {
+            return Ok(false);
+        };
+        match (*command, args) {
+            ("step" | "s", []) => self.advance(1, false, output)?,
+            ("step" | "s", [n]) => self.advance(parse_cycle(n)?, false, output)?,
+            ("run" | "r", [n]) => self.advance(parse_cycle(n)?, true, output)?,
+            ("continue" | "c", []) => self.advance(Cycle::MAX, true, output)?,
+            ("back" | "b", []) => self.travel(self.sim_manager.step_back(1), output)?,
+            ("back" | "b", [n]) => {
+                self.travel(self.sim_manager.step_back(parse_cycle(n)?), output)?
+            }
+            ("goto", [cycle]) => {
+                self.travel(self.sim_manager.goto_cycle(parse_cycle(cycle)?), output)?
+            }
+            ("break", ["cycle", cycle]) => {
+                self.add_breakpoint(Condition::Cycle(parse_cycle(cycle)?), output)?
+            }
+            ("break", [port, "changed"]) => {
+                self.check_port(port)?;
+                self.add_breakpoint(
+                    Condition::Changed {
+                        port: port.to_string(),
+                    },
+                    output,
+                )?
+            }
+            ("break", [port, op, value @ ..]) if !value.is_empty() => {
+                self.check_port(port)?;
+                let op = CompareOp::parse(op).ok_or(usage(line))?;
+                self.add_breakpoint(
+                    Condition::Value {
+                        port: port.to_string(),
+                        op,
+                        value: value.join(" "),
+                    },
+                    output,
+                )?
+            }
+            ("breakpoints" | "info", []) => {
+                if self.breakpoints.is_empty() {
+                    writeln!(output, "no breakpoints")?;
+                }
+                for breakpoint in self.breakpoints.iter() {
+                    writeln!(
+                        output,
+                        "{}: {}{}",
+                        breakpoint.id,
+                        breakpoint.condition,
+                        if breakpoint.enabled {
+                            ""
+                        } else {
+                            " (disabled)"
+                        }
+                    )?;
+                }
+            }
+            ("enable", [id]) => self.get_breakpoint(id)?.enabled = true,
+            ("disable", [id]) => self.get_breakpoint(id)?.enabled = false,
+            ("delete" | "d", [id]) => {
+                let id = self.get_breakpoint(id)?.id;
+                self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+            }
+            ("wpoint", [port]) => {
+                let id = self
+                    .sim_manager
+                    .add_watchpoint(port, WatchCondition::AnyWrite)?;
+                writeln!(output, "watchpoint {}: {} any write", id, port)?;
+            }
+            ("wpoint", [port, op, value @ ..]) if !value.is_empty() => {
+                let op = CompareOp::parse(op).ok_or(usage(line))?;
+                let condition = WatchCondition::Compare(op, value.join(" "));
+                let id = self.sim_manager.add_watchpoint(port, condition.clone())?;
+                writeln!(output, "watchpoint {}: {} {}", id, port, condition)?;
+            }
+            ("wpoints", []) => {
+                let watchpoints = self.sim_manager.get_watchpoints()?;
+                if watchpoints.is_empty() {
+                    writeln!(output, "no watchpoints")?;
+                }
+                for watchpoint in watchpoints.iter() {
+                    writeln!(
+                        output,
+                        "{}: {} {}{}",
+                        watchpoint.id,
+                        watchpoint.port,
+                        watchpoint.condition,
+                        if watchpoint.enabled {
+                            ""
+                        } else {
+                            " (disabled)"
+                        }
+                    )?;
+                }
+            }
+            ("wenable", [id]) => self
+                .sim_manager
+                .set_watchpoint_enabled(parse_watchpoint_id(id)?, true)?,
+            ("wdisable", [id]) => self
+                .sim_manager
+                .set_watchpoint_enabled(parse_watchpoint_id(id)?, false)?,
+            ("wdelete", [id]) => self
+                .sim_manager
+                .remove_watchpoint(parse_watchpoint_id(id)?)?,
+            ("print" | "p", [port]) => {
+                let value = self.sim_manager.peek_as_string(port)?;
+                writeln!(output, "{} = {}", port, value)?;
+            }
+            ("watch" | "w", [port]) => {
+                let value = self.sim_manager.peek_as_string(port)?;
+                self.last_values.insert(port.to_string(), value);
+                if !self.watches.iter().any(|watch| watch == port) {
+                    self.watches.push(port.to_string());
+                }
+            }
+            ("unwatch", [port]) => self.watches.retain(|watch| watch != port),
+            ("list" | "ls", []) => self.list("", output)?,
+            ("list" | "ls", [prefix]) => self.list(prefix, output)?,
+            ("cycle", []) => writeln!(output, "cycle {}", self.sim_manager.get_curr_cycle())?,
+            ("help" | "h", _) => writeln!(output, "{}", HELP)?,
+            ("quit" | "q", []) => return Ok(true),
+            _ => return Err(usage(line)),
+        }
+        Ok(false)
+    }
+
+    pub fn get_breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Runs up to `cycles` cycles, stopping early at the end of the simulation or,
+    /// if `use_breakpoints`, at the end of a cycle where a breakpoint hits
+    fn advance<W: Write>(
+        &mut self,
+        cycles: Cycle,
+        use_breakpoints: bool,
+        output: &mut W,
+    ) -> Result<(), SimError> {
+        self.sync_values();
+        self.sim_manager.take_watchpoint_hits()?;
+        let mut remaining = cycles;
+        while remaining > 0 {
+            if self.sim_manager.sim_can_end() {
+                writeln!(
+                    output,
+                    "simulation ended at cycle {}",
+                    self.sim_manager.get_curr_cycle()
+                )?;
+                return Ok(());
+            }
+            self.sim_manager.run_cycle()?;
+            self.sim_manager.run_cycle_end()?;
+            remaining -= 1;
+
+            let (hits, watchpoint_hits) = if use_breakpoints {
+                (self.get_hits(), self.sim_manager.take_watchpoint_hits()?)
+            } else {
+                self.sim_manager.take_watchpoint_hits()?;
+                (Vec::new(), Vec::new())
+            };
+            self.print_watches(output)?;
+            self.sync_values();
+            if !hits.is_empty() || !watchpoint_hits.is_empty() {
+                for id in hits {
+                    writeln!(
+                        output,
+                        "breakpoint {} hit at cycle {}",
+                        id,
+                        self.sim_manager.get_curr_cycle()
+                    )?;
+                }
+                for hit in watchpoint_hits {
+                    writeln!(
+                        output,
+                        "watchpoint {} hit at cycle {}: {} = {}",
+                        hit.id, hit.cycle, hit.port, hit.value
+                    )?;
+                }
+                return Ok(());
+            }
+        }
+        writeln!(output, "cycle {}", self.sim_manager.get_curr_cycle())?;
+        Ok(())
+    }
+
+    fn travel<W: Write>(
+        &mut self,
+        result: Result<(), SimError>,
+        output: &mut W,
+    ) -> Result<(), SimError> {
+        result?;
+        self.sync_values();
+        writeln!(output, "cycle {}", self.sim_manager.get_curr_cycle())?;
+        Ok(())
+    }
+
+    /// Returns the ids of the enabled breakpoints that hold at the current cycle
+    fn get_hits(&self) -> Vec<usize> {
+        let curr_cycle = self.sim_manager.get_curr_cycle();
+        self.breakpoints
+            .iter()
+            .filter(|breakpoint| breakpoint.enabled)
+            .filter(|breakpoint| match &breakpoint.condition {
+                Condition::Cycle(cycle) => *cycle == curr_cycle,
+                Condition::Value { port, op, value } => self
+                    .sim_manager
+                    .peek_as_string(port)
+                    .is_ok_and(|port_value| op.holds(&port_value, value)),
+                Condition::Changed { port } => self
+                    .sim_manager
+                    .peek_as_string(port)
+                    .is_ok_and(|port_value| self.last_values.get(port) != Some(&port_value)),
+            })
+            .map(|breakpoint| breakpoint.id)
+            .collect()
+    }
+
+    fn print_watches<W: Write>(&self, output: &mut W) -> Result<(), SimError> {
+        for port in self.watches.iter() {
+            let value = self.sim_manager.peek_as_string(port)?;
+            if self.last_values.get(port) != Some(&value) {
+                writeln!(
+                    output,
+                    "[{}] {} = {}",
+                    self.sim_manager.get_curr_cycle(),
+                    port,
+                    value
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers the current value of every port watched or under a `changed` breakpoint
+    fn sync_values(&mut self) {
+        let ports =
+            self.watches
+                .iter()
+                .cloned()
+                .chain(self.breakpoints.iter().filter_map(
+                    |breakpoint| match &breakpoint.condition {
+                        Condition::Changed { port } => Some(port.clone()),
+                        _ => None,
+                    },
+                ))
+                .collect::<Vec<_>>();
+        for port in ports {
+            if let Ok(value) = self.sim_manager.peek_as_string(&port) {
+                self.last_values.insert(port, value);
+            }
+        }
+    }
+
+    fn add_breakpoint<W: Write>(
+        &mut self,
+        condition: Condition,
+        output: &mut W,
+    ) -> Result<(), SimError> {
+        let id = self.next_breakpoint_id;
+        self.next_breakpoint_id += 1;
+        writeln!(output, "breakpoint {}: {}", id, condition)?;
+        self.breakpoints.push(Breakpoint {
+            id,
+            condition,
+            enabled: true,
+        });
+        self.sync_values();
+        Ok(())
+    }
+
+    fn get_breakpoint(&mut self, id: &str) -> Result<&mut Breakpoint, SimError> {
+        let id: usize = id
+            .parse()
+            .map_err(|_| SimError::DebuggerError(format!("invalid breakpoint id \"{}\"", id)))?;
+        self.breakpoints
+            .iter_mut()
+            .find(|breakpoint| breakpoint.id == id)
+            .ok_or(SimError::DebuggerError(format!("no breakpoint {}", id)))
+    }
+
+    fn check_port(&self, port: &str) -> Result<(), SimError> {
+        self.sim_manager.peek_as_string(port).map(|_| ())
+    }
+
+    fn list<W: Write>(&self, prefix: &str, output: &mut W) -> Result<(), SimError> {
+        let components = self.sim_manager.get_registry().get_components();
+        for component in components
+            .iter()
+            .filter(|component| component.name.starts_with(prefix))
+        {
+            writeln!(output, "{} (id {})", component.name, component.component_id)?;
+            for port in component.ports.iter() {
+                let value = self.sim_manager.peek_as_string(&port.name)?;
+                writeln!(
+                    output,
+                    "  {} {:?} {} = {}",
+                    port.name, port.direction, port.type_name, value
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_cycle(value: &str) -> Result<Cycle, SimError> {
+    parse_integer(value)
+        .and_then(|value| Cycle::try_from(value).ok())
+        .ok_or(SimError::DebuggerError(format!(
+            "invalid cycle count \"{}\"",
+            value
+        )))
+}
+
+fn parse_watchpoint_id(value: &str) -> Result<WatchpointId, SimError> {
+    value
+        .parse()
+        .map_err(|_| SimError::DebuggerError(format!("invalid watchpoint id \"{}\"", value)))
+}
+
+fn usage(line: &str) -> SimError {
+    SimError::DebuggerError(format!("invalid command \"{}\", try \"help\"", line.trim()))
+}
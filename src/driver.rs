@@ -0,0 +1,269 @@
+use crate::component::Component;
+use crate::error::SimError;
+use crate::event::EventValue;
+use crate::random::{Distribution, RandomValue, Rng};
+use crate::sim_manager::SimManager;
+use crate::tx::Tx;
+use crate::types::{ComponentId, Cycle, EventId, Input, Output};
+use crossbeam_channel::{unbounded, Sender};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// Number of values drawn for a clock tick before giving up on the constraints
+const MAX_TRIES: usize = 10_000;
+
+type Generator<T> = Box<dyn FnMut(&mut Rng) -> T + Send + Sync>;
+type Constraint<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// A component sending a random value on its `output` port at every clock tick, like `SimpleSender`.
+///
+/// Values come from a generator, e.g. a `Distribution`, and are drawn again until they satisfy every constraint.
+/// The driver draws from its stream of `SimManager::get_rng`, so a failing run can be reproduced
+/// from the master seed, unless given its own seed with `with_seed`, which is then reported in the stats.
+/// The stream is taken when the driver is built, so the master seed must be set before.
+///
+/// If no value satisfies the constraints, the driver stops sending and lets the simulation end,
+/// the error is then returned by `get_error`.
+pub struct RandomDriver<
+    T: Default
+        + Copy
+        + Send
+        + Sync
+        + PartialEq
+        + Debug
+        + Serialize
+        + DeserializeOwned
+        + 'static
+        + EventValue,
+> {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    name: String,
+    output: Tx<T>,
+    seed: Option<u64>,
+    initial_rng: Rng,
+    rng: Rng,
+    generator: Generator<T>,
+    constraints: Vec<Constraint<T>>,
+    /// Number of values to send before letting the simulation end, `None` to send forever
+    num_values: Option<u64>,
+    sent_count: u64,
+    delay: Cycle,
+    error: Option<SimError>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > RandomDriver<T>
+{
+    /// The driver registers itself as `name`, it sends values with a delay of 1 and never ends by default
+    pub fn new<F: FnMut(&mut Rng) -> T + Send + Sync + 'static>(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        output: Tx<T>,
+        ack_sender: Sender<EventId>,
+        generator: F,
+    ) -> Self {
+        let clock_tick_channel = unbounded();
+        RandomDriver {
+            component_id,
+            sim_manager,
+            name: name.to_string(),
+            output,
+            seed: None,
+            initial_rng: Rng::new(0),
+            rng: Rng::new(0),
+            generator: Box::new(generator),
+            constraints: Vec::new(),
+            num_values: None,
+            sent_count: 0,
+            delay: 1,
+            error: None,
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }
+    }
+
+    /// A driver drawing its values from `distribution`
+    pub fn from_distribution(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        name: &str,
+        output: Tx<T>,
+        ack_sender: Sender<EventId>,
+        distribution: Distribution<T>,
+    ) -> Self
+    where
+        T: RandomValue,
+    {
+        Self::new(
+            component_id,
+            sim_manager,
+            name,
+            output,
+            ack_sender,
+            move |rng| distribution.sample(rng),
+        )
+    }
+
+    /// Draws from a stream of its own instead of the one of `SimManager::get_rng`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Only sends values for which `constraint` holds
+    pub fn with_constraint<F: Fn(&T) -> bool + Send + Sync + 'static>(
+        mut self,
+        constraint: F,
+    ) -> Self {
+        self.constraints.push(Box::new(constraint));
+        self
+    }
+
+    /// Holds the end of the simulation until `num_values` values are sent
+    pub fn with_num_values(mut self, num_values: u64) -> Self {
+        self.num_values = Some(num_values);
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Cycle) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn build(mut self) -> Result<Arc<Mutex<Self>>, SimError> {
+        self.initial_rng = match self.seed {
+            Some(seed) => Rng::new(seed),
+            None => self.sim_manager.get_rng(self.component_id)?,
+        };
+        self.rng = self.initial_rng.clone();
+        Ok(Arc::new(Mutex::new(self)))
+    }
+
+    /// The seed given with `with_seed`, if any
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn get_sent_count(&self) -> u64 {
+        self.sent_count
+    }
+
+    /// Why the driver stopped sending, if it did before sending its values
+    pub fn get_error(&self) -> Option<&SimError> {
+        self.error.as_ref()
+    }
+
+    fn generate(&mut self) -> Result<T, SimError> {
+        for _ in 0..MAX_TRIES {
+            let value = (self.generator)(&mut self.rng);
+            if self.constraints.iter().all(|constraint| constraint(&value)) {
+                return Ok(value);
+            }
+        }
+        Err(SimError::StimulusError(format!(
+            "{}: no value satisfying the constraints after {} tries",
+            self.name, MAX_TRIES
+        )))
+    }
+
+    fn on_clock(&mut self) {
+        if self.error.is_some()
+            || self
+                .num_values
+                .is_some_and(|num_values| self.sent_count >= num_values)
+        {
+            self.sim_manager.register_can_end(self.component_id);
+            return;
+        }
+        match self.generate() {
+            Ok(value) => {
+                self.output.send(value, self.delay);
+                self.sent_count += 1;
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self.sim_manager.register_can_end(self.component_id);
+            }
+        }
+    }
+}
+
+impl<
+        T: Default
+            + Copy
+            + Send
+            + Sync
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > Component for RandomDriver<T>
+{
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, &self.name)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        if let Some(seed) = self.seed {
+            self.sim_manager.register_seed(&self.name, seed).unwrap();
+        }
+        if self.num_values.is_some() {
+            self.sim_manager.register_do_not_end(self.component_id);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sent_count = 0;
+        self.error = None;
+        self.rng = self.initial_rng.clone();
+    }
+
+    fn poll_recv(&mut self) {
+        if let Ok(event) = self.clock_receiver.try_recv() {
+            self.on_clock();
+            self.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(json!({
+            "sent_count": self.sent_count,
+            "rng": self.rng,
+        }))
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.sent_count = serde_json::from_value(state["sent_count"].clone())?;
+        self.rng = serde_json::from_value(state["rng"].clone())?;
+        Ok(())
+    }
+}
@@ -6,11 +6,47 @@ use std::sync::PoisonError;
 #[derive(Debug)]
 pub enum SimError {
     SimManagerError,
+    RegistryError(String),
+    ProbeError(String),
+    CheckpointError(String),
+    ReplayError(String),
+    DebuggerError(String),
+    GdbError(String),
+    CoverageError(String),
+    ScoreboardError(String),
+    StimulusError(String),
+    FaultError(String),
+    SweepError(String),
+    ProtocolError(String),
+    ComponentError(String),
+    ImageError(String),
+    AssertionError(String),
+    IoError(std::io::Error),
+    SerializationError(serde_json::Error),
 }
 
 impl Display for SimError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "SimError")
+        match self {
+            SimManagerError => write!(f, "SimError"),
+            SimError::RegistryError(msg) => write!(f, "RegistryError: {}", msg),
+            SimError::ProbeError(msg) => write!(f, "ProbeError: {}", msg),
+            SimError::CheckpointError(msg) => write!(f, "CheckpointError: {}", msg),
+            SimError::ReplayError(msg) => write!(f, "ReplayError: {}", msg),
+            SimError::DebuggerError(msg) => write!(f, "DebuggerError: {}", msg),
+            SimError::GdbError(msg) => write!(f, "GdbError: {}", msg),
+            SimError::CoverageError(msg) => write!(f, "CoverageError: {}", msg),
+            SimError::ScoreboardError(msg) => write!(f, "ScoreboardError: {}", msg),
+            SimError::StimulusError(msg) => write!(f, "StimulusError: {}", msg),
+            SimError::FaultError(msg) => write!(f, "FaultError: {}", msg),
+            SimError::SweepError(msg) => write!(f, "SweepError: {}", msg),
+            SimError::ProtocolError(msg) => write!(f, "ProtocolError: {}", msg),
+            SimError::ComponentError(msg) => write!(f, "ComponentError: {}", msg),
+            SimError::ImageError(msg) => write!(f, "ImageError: {}", msg),
+            SimError::AssertionError(msg) => write!(f, "AssertionError: {}", msg),
+            SimError::IoError(e) => write!(f, "IoError: {}", e),
+            SimError::SerializationError(e) => write!(f, "SerializationError: {}", e),
+        }
     }
 }
 
@@ -21,3 +57,15 @@ impl<T> From<PoisonError<T>> for SimError {
         SimManagerError
     }
 }
+
+impl From<std::io::Error> for SimError {
+    fn from(value: std::io::Error) -> Self {
+        SimError::IoError(value)
+    }
+}
+
+impl From<serde_json::Error> for SimError {
+    fn from(value: serde_json::Error) -> Self {
+        SimError::SerializationError(value)
+    }
+}
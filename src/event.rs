@@ -16,3 +16,52 @@ pub trait Event: Send + Sync + Debug {
 pub trait EventValue {
     fn build_event(&self, event_id: EventId, scheduled_time: Cycle) -> Box<dyn Event>;
 }
+
+/// A generic `Event` holding a port value,
+/// used when the framework rebuilds events, e.g. when restoring a checkpoint
+#[derive(Debug, Clone)]
+pub struct ValueEvent<T: Copy + Send + Sync + Debug + 'static> {
+    event_id: EventId,
+    scheduled_time: Cycle,
+    value: T,
+}
+
+impl<T: Copy + Send + Sync + Debug + 'static> ValueEvent<T> {
+    pub fn new(scheduled_time: Cycle, value: T, event_id: EventId) -> Self {
+        ValueEvent {
+            event_id,
+            scheduled_time,
+            value,
+        }
+    }
+}
+
+impl<T: Copy + Send + Sync + Debug + 'static> Event for ValueEvent<T> {
+    fn get_event_id(&self) -> EventId {
+        self.event_id
+    }
+
+    fn get_scheduled_time(&self) -> Cycle {
+        self.scheduled_time
+    }
+
+    fn get_data_as_any(&self) -> Box<dyn Any> {
+        Box::new(self.value)
+    }
+}
+
+/// Implements `EventValue` for types sent as is, wrapped in a `ValueEvent`
+macro_rules! impl_event_value {
+    ($($t:ty),*) => {
+        $(
+            impl EventValue for $t {
+                fn build_event(&self, event_id: EventId, scheduled_time: Cycle) -> Box<dyn Event> {
+                    Box::new(ValueEvent::new(scheduled_time, *self, event_id))
+                }
+            }
+        )*
+    };
+}
+
+// Primitive values can be sent as is, e.g. by a `RandomDriver<u32>` or on the ports of the library components
+impl_event_value!(bool, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
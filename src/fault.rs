@@ -0,0 +1,480 @@
+use crate::activity::NESTED_INTEGER_WIDTH;
+use crate::error::SimError;
+use crate::random::Rng;
+use crate::types::Cycle;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// What a fault does to the events sent on its port
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FaultKind {
+    /// Forces a bit of the value, named as in `PortActivity::bits`, e.g. `[3]` or `data[3]`
+    StuckAt { bit: String, value: bool },
+    /// Inverts a bit of the value
+    BitFlip { bit: String },
+    /// The event is never sent and the port keeps its previous value
+    Drop,
+    /// The event arrives `cycles` cycles late
+    Delay { cycles: Cycle },
+}
+
+fn always() -> f64 {
+    1.0
+}
+
+/// A fault on a registered output port, applied by `Tx::send` while active, see `SimManager::inject_fault`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Fault {
+    pub name: String,
+    pub port: String,
+    pub kind: FaultKind,
+    /// First cycle the fault is active
+    #[serde(default)]
+    pub start: Cycle,
+    /// Last cycle the fault is active, `None` for a permanent fault
+    #[serde(default)]
+    pub end: Option<Cycle>,
+    /// Probability for every event sent while the fault is active to be affected
+    #[serde(default = "always")]
+    pub probability: f64,
+}
+
+impl Fault {
+    /// A permanent fault affecting every event
+    pub fn new(name: &str, port: &str, kind: FaultKind) -> Self {
+        Fault {
+            name: name.to_string(),
+            port: port.to_string(),
+            kind,
+            start: 0,
+            end: None,
+            probability: 1.0,
+        }
+    }
+
+    pub fn stuck_at(name: &str, port: &str, bit: &str, value: bool) -> Self {
+        Self::new(
+            name,
+            port,
+            FaultKind::StuckAt {
+                bit: bit.to_string(),
+                value,
+            },
+        )
+    }
+
+    /// A transient bit flip of the event sent at `cycle`
+    pub fn bit_flip(name: &str, port: &str, bit: &str, cycle: Cycle) -> Self {
+        Self::new(
+            name,
+            port,
+            FaultKind::BitFlip {
+                bit: bit.to_string(),
+            },
+        )
+        .with_cycles(cycle, Some(cycle))
+    }
+
+    pub fn drop(name: &str, port: &str) -> Self {
+        Self::new(name, port, FaultKind::Drop)
+    }
+
+    pub fn delay(name: &str, port: &str, cycles: Cycle) -> Self {
+        Self::new(name, port, FaultKind::Delay { cycles })
+    }
+
+    /// Only active from `start` to `end` included
+    pub fn with_cycles(mut self, start: Cycle, end: Option<Cycle>) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    pub fn with_probability(mut self, probability: f64) -> Self {
+        self.probability = probability;
+        self
+    }
+
+    pub fn is_active(&self, cycle: Cycle) -> bool {
+        cycle >= self.start && self.end.is_none_or(|end| cycle <= end)
+    }
+
+    pub(crate) fn validate(&self, bit_names: &[String]) -> Result<(), SimError> {
+        if !(0.0..=1.0).contains(&self.probability) {
+            return Err(SimError::FaultError(format!(
+                "fault \"{}\" has an invalid probability {}",
+                self.name, self.probability
+            )));
+        }
+        match &self.kind {
+            FaultKind::StuckAt { bit, .. } | FaultKind::BitFlip { bit }
+                if !bit_names.contains(bit) =>
+            {
+                Err(SimError::FaultError(format!(
+                    "fault \"{}\": port \"{}\" has no bit \"{}\"",
+                    self.name, self.port, bit
+                )))
+            }
+            // values are serialized as 64 bit numbers, the upper bits of a wider port can't be set
+            FaultKind::StuckAt { bit, .. } | FaultKind::BitFlip { bit }
+                if get_bit_index(bit).is_some_and(|index| index >= NESTED_INTEGER_WIDTH) =>
+            {
+                Err(SimError::FaultError(format!(
+                    "fault \"{}\": bit \"{}\" of port \"{}\" is beyond the first {} bits",
+                    self.name, bit, self.port, NESTED_INTEGER_WIDTH
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum BitFault {
+    StuckAt(String, bool),
+    Flip(String),
+}
+
+/// The effect of the active faults on an event
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ActiveFaults {
+    pub(crate) bits: Vec<BitFault>,
+    pub(crate) drop: bool,
+    pub(crate) delay: Cycle,
+}
+
+/// A fault affecting an event
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaultInjection {
+    pub fault: String,
+    pub port: String,
+    pub cycle: Cycle,
+}
+
+/// A random stream for the fault called `name`, FNV-1a so that it does not change between builds
+pub(crate) fn get_fault_stream(name: &str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[derive(Debug)]
+struct InjectedFault {
+    fault: Fault,
+    /// Only needed by faults with a probability, each fault has its own stream
+    /// so that its draws do not depend on the events sent on other ports
+    rng: Option<Rng>,
+}
+
+/// The random streams of the faults and the number of injections at some cycle, see `FaultInjector::save`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FaultState {
+    streams: Vec<(String, Rng)>,
+    injections: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct FaultInjector {
+    faults: Vec<InjectedFault>,
+    injections: Vec<FaultInjection>,
+}
+
+impl FaultInjector {
+    pub(crate) fn add(&mut self, fault: Fault, rng: Option<Rng>) -> Result<(), SimError> {
+        if self
+            .faults
+            .iter()
+            .any(|other| other.fault.name == fault.name)
+        {
+            return Err(SimError::FaultError(format!(
+                "fault \"{}\" already exists",
+                fault.name
+            )));
+        }
+        self.faults.push(InjectedFault { fault, rng });
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        let len = self.faults.len();
+        self.faults.retain(|injected| injected.fault.name != name);
+        self.faults.len() != len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    pub(crate) fn get_faults(&self) -> Vec<Fault> {
+        self.faults
+            .iter()
+            .map(|injected| injected.fault.clone())
+            .collect()
+    }
+
+    pub(crate) fn get_injections(&self) -> &[FaultInjection] {
+        &self.injections
+    }
+
+    pub(crate) fn save(&self) -> FaultState {
+        FaultState {
+            streams: self
+                .faults
+                .iter()
+                .filter_map(|injected| {
+                    let rng = injected.rng.clone()?;
+                    Some((injected.fault.name.clone(), rng))
+                })
+                .collect(),
+            injections: self.injections.len(),
+        }
+    }
+
+    /// Brings the streams back to `state` so that the same draws are made again,
+    /// the faults added since keep theirs
+    pub(crate) fn restore(&mut self, state: &FaultState) {
+        for InjectedFault { fault, rng } in self.faults.iter_mut() {
+            if let Some((_, saved)) = state.streams.iter().find(|(name, _)| *name == fault.name) {
+                *rng = Some(saved.clone());
+            }
+        }
+        self.injections.truncate(state.injections);
+    }
+
+    /// Returns what the faults do to an event sent on `port` at `cycle`, `None` if nothing
+    pub(crate) fn get_active(&mut self, port: &str, cycle: Cycle) -> Option<ActiveFaults> {
+        if self.faults.is_empty() {
+            return None;
+        }
+        let mut active = ActiveFaults::default();
+        let mut affected = false;
+        for InjectedFault { fault, rng } in self.faults.iter_mut() {
+            if fault.port != port || !fault.is_active(cycle) {
+                continue;
+            }
+            if let Some(rng) = rng.as_mut().filter(|_| fault.probability < 1.0) {
+                if !rng.gen_bool(fault.probability) {
+                    continue;
+                }
+            }
+            match &fault.kind {
+                FaultKind::StuckAt { bit, value } => {
+                    active.bits.push(BitFault::StuckAt(bit.clone(), *value))
+                }
+                FaultKind::BitFlip { bit } => active.bits.push(BitFault::Flip(bit.clone())),
+                FaultKind::Drop => active.drop = true,
+                FaultKind::Delay { cycles } => active.delay += cycles,
+            }
+            affected = true;
+            self.injections.push(FaultInjection {
+                fault: fault.name.clone(),
+                port: port.to_string(),
+                cycle,
+            });
+        }
+        affected.then_some(active)
+    }
+}
+
+/// Applies bit faults to a value through its serialized form, `None` if they cannot be applied,
+/// e.g. when setting a bit beyond the width of a field nested in a structure.
+///
+/// The serialized form does not tell signed integers apart, so they are tried as unsigned first.
+pub(crate) fn apply_bit_faults<T: Serialize + DeserializeOwned>(
+    value: &T,
+    faults: &[BitFault],
+) -> Option<T> {
+    let serialized = serde_json::to_value(value).ok()?;
+    let width = (std::mem::size_of::<T>() * 8).clamp(1, 128);
+    [false, true].into_iter().find_map(|signed| {
+        let mut serialized = serialized.clone();
+        for fault in faults {
+            let (bit, op): (&str, &dyn Fn(bool) -> bool) = match fault {
+                BitFault::StuckAt(bit, value) => (bit, &move |_| *value),
+                BitFault::Flip(bit) => (bit, &|bit: bool| !bit),
+            };
+            set_bit(&mut serialized, bit, width, signed, op)?;
+        }
+        serde_json::from_value(serialized).ok()
+    })
+}
+
+/// The index of an integer bit named as in `activity::get_bits`, e.g. 3 for `data[3]`
+fn get_bit_index(bit: &str) -> Option<usize> {
+    let (_, index) = bit.strip_suffix(']')?.rsplit_once('[')?;
+    index.parse().ok()
+}
+
+/// Finds a bit named as in `activity::get_bits` and replaces it with `op(bit)`
+fn set_bit(
+    value: &mut Value,
+    bit: &str,
+    width: usize,
+    signed: bool,
+    op: &dyn Fn(bool) -> bool,
+) -> Option<()> {
+    let (path, index) = match bit.strip_suffix(']').and_then(|bit| bit.rsplit_once('[')) {
+        Some((path, _)) => (path, Some(get_bit_index(bit)?)),
+        None => (bit, None),
+    };
+    let mut target = value;
+    let mut width = width;
+    if !(path.is_empty() || (path == "value" && target.is_boolean())) {
+        for segment in path.split('.') {
+            target = match target {
+                Value::Object(fields) => fields.get_mut(segment)?,
+                Value::Array(items) => items.get_mut(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+            width = NESTED_INTEGER_WIDTH;
+        }
+    }
+    match (target, index) {
+        (Value::Bool(value), None) => *value = op(*value),
+        (Value::Number(number), Some(index)) if index < width.min(NESTED_INTEGER_WIDTH) => {
+            *number = set_number_bit(number, index, width, signed, op)?
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+fn set_number_bit(
+    number: &Number,
+    index: usize,
+    width: usize,
+    signed: bool,
+    op: &dyn Fn(bool) -> bool,
+) -> Option<Number> {
+    let set = |raw: u128| {
+        let mask = 1u128 << index;
+        if op(raw & mask != 0) {
+            raw | mask
+        } else {
+            raw & !mask
+        }
+    };
+    if let Some(value) = number.as_u64().filter(|_| !signed) {
+        return Some(Number::from(set(value as u128) as u64));
+    }
+    if let Some(value) = number.as_i64() {
+        // sign extension from the width of the value
+        let shift = 128 - width.min(64);
+        let raw = ((set(value as i128 as u128) << shift) as i128) >> shift;
+        return Some(Number::from(i64::try_from(raw).ok()?));
+    }
+    let value = number.as_f64()?;
+    Number::from_f64(f64::from_bits(set(value.to_bits() as u128) as u64))
+}
+
+/// The faults to inject, one simulation each, see `FaultCampaign::run`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultCampaign {
+    pub faults: Vec<Fault>,
+}
+
+/// The outcome of a simulation with a single fault
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FaultResult {
+    pub fault: Fault,
+    pub outcome: String,
+    /// Whether the outcome differs from the fault-free simulation
+    pub changed: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CampaignReport {
+    /// The outcome of the fault-free simulation
+    pub golden: String,
+    pub results: Vec<FaultResult>,
+}
+
+impl FaultCampaign {
+    pub fn new(faults: Vec<Fault>) -> Self {
+        FaultCampaign { faults }
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<FaultCampaign, SimError> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Runs the fault-free simulation, then one simulation per fault, comparing their outcomes.
+    ///
+    /// `simulate` builds and runs a whole simulation with the faults it is given,
+    /// see `SimManager::inject_fault`, and returns its outcome, e.g. the final state or the scoreboard results.
+    pub fn run<O: PartialEq + Debug, F: FnMut(&[Fault]) -> Result<O, SimError>>(
+        &self,
+        mut simulate: F,
+    ) -> Result<CampaignReport, SimError> {
+        let golden = simulate(&[])?;
+        let mut report = CampaignReport {
+            golden: format!("{:?}", golden),
+            results: Vec::new(),
+        };
+        for fault in self.faults.iter() {
+            let outcome = simulate(std::slice::from_ref(fault))?;
+            report.results.push(FaultResult {
+                fault: fault.clone(),
+                changed: outcome != golden,
+                outcome: format!("{:?}", outcome),
+            });
+        }
+        Ok(report)
+    }
+}
+
+impl CampaignReport {
+    /// The faults that changed the outcome of the simulation
+    pub fn get_changed(&self) -> Vec<&FaultResult> {
+        self.results
+            .iter()
+            .filter(|result| result.changed)
+            .collect()
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Display for CampaignReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} of {} faults changed the outcome",
+            self.get_changed().len(),
+            self.results.len()
+        )?;
+        writeln!(f, "golden: {}", self.golden)?;
+        for result in self.results.iter() {
+            writeln!(
+                f,
+                "  {} on {}: {}{}",
+                result.fault.name,
+                result.fault.port,
+                if result.changed {
+                    "changed, "
+                } else {
+                    "masked"
+                },
+                if result.changed { &result.outcome } else { "" }
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+use crate::error::SimError;
+use crate::sim_manager::SimManager;
+use std::collections::BTreeSet;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+
+/// Number of cycles simulated between two checks for an interrupt from gdb while continuing
+const INTERRUPT_CHECK_INTERVAL: u64 = 256;
+
+/// The view of a CPU component needed by `GdbServer`.
+///
+/// Registers are numbered as in the gdb target description of the architecture,
+/// e.g. for rv32i `x0`-`x31` are 0-31 and `pc` is 32.
+pub trait GdbTarget: Send {
+    /// Number of registers sent in a `g` packet
+    fn get_num_registers(&self) -> usize;
+
+    /// Width of every register in bytes
+    fn get_register_width(&self) -> usize {
+        4
+    }
+
+    fn read_register(&self, register: usize) -> Result<u64, SimError>;
+
+    fn write_register(&mut self, register: usize, value: u64) -> Result<(), SimError>;
+
+    fn read_memory(&self, address: u64, len: usize) -> Result<Vec<u8>, SimError>;
+
+    fn write_memory(&mut self, address: u64, data: &[u8]) -> Result<(), SimError>;
+
+    /// Address of the next instruction to retire, compared against the breakpoints
+    fn get_pc(&self) -> u64;
+
+    /// Number of instructions retired so far, a single step runs cycles until it changes
+    fn get_instructions_retired(&self) -> u64;
+}
+
+/// A byte stream gdb is connected through
+pub trait GdbConnection: Read + Write {
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()>;
+}
+
+impl GdbConnection for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+#[cfg(unix)]
+impl GdbConnection for UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        UnixStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+/// Why the target stopped, reported to gdb after `c`, `s` and `?`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum StopState {
+    /// SIGTRAP, after a step, a breakpoint or a watchpoint
+    Trap,
+    /// SIGINT, after gdb sent an interrupt
+    Interrupted,
+    /// The simulation ended
+    Exited,
+}
+
+/// A gdb remote serial protocol stub driving a `SimManager` cycle by cycle.
+///
+/// The simulation only advances on `continue` and `step` from gdb,
+/// between them the CPU component is inspected and modified through `GdbTarget`.
+/// Breakpoints are kept by the stub, the simulation stops at the end of any cycle
+/// where an instruction retired and `GdbTarget::get_pc` is on a breakpoint.
+/// Framework watchpoints, see `SimManager::add_watchpoint`, also stop a `continue`.
+///
+/// As with `Debugger`, the netlist is built and the dispatchers spawned as usual:
+/// ```ignore
+/// let cpu = Cpu::new(...);
+/// let sim_dispatcher = SimDispatcher::new(Arc::downgrade(&sim_manager), vec![cpu.clone()]);
+/// // init and spawn the dispatchers
+/// GdbServer::new(sim_manager.clone(), cpu).listen_tcp("127.0.0.1:1234")?;
+/// ```
+pub struct GdbServer<C: GdbTarget> {
+    sim_manager: Arc<SimManager>,
+    cpu: Arc<Mutex<C>>,
+    breakpoints: BTreeSet<u64>,
+    stop_state: StopState,
+}
+
+impl<C: GdbTarget> GdbServer<C> {
+    pub fn new(sim_manager: Arc<SimManager>, cpu: Arc<Mutex<C>>) -> Self {
+        GdbServer {
+            sim_manager,
+            cpu,
+            breakpoints: BTreeSet::new(),
+            stop_state: StopState::Trap,
+        }
+    }
+
+    /// Waits for gdb on a TCP address, e.g. `target remote localhost:1234`,
+    /// and serves it until it detaches or kills the target
+    pub fn listen_tcp<A: ToSocketAddrs>(&mut self, address: A) -> Result<(), SimError> {
+        let (stream, _) = TcpListener::bind(address)?.accept()?;
+        stream.set_nodelay(true)?;
+        self.serve(stream)
+    }
+
+    /// Waits for gdb on a Unix socket, e.g. `target remote /tmp/rsim.sock`,
+    /// and serves it until it detaches or kills the target
+    #[cfg(unix)]
+    pub fn listen_unix<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<(), SimError> {
+        let (stream, _) = UnixListener::bind(path)?.accept()?;
+        self.serve(stream)
+    }
+
+    /// Serves gdb on an established connection until it detaches or kills the target
+    pub fn serve<S: GdbConnection>(&mut self, mut stream: S) -> Result<(), SimError> {
+        while let Some(packet) = read_packet(&mut stream)? {
+            let Some(reply) = self.handle_packet(&packet, &mut stream)? else {
+                return Ok(());
+            };
+            write_packet(&mut stream, &reply)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the reply to the packet, or `None` if the connection should be closed
+    fn handle_packet<S: GdbConnection>(
+        &mut self,
+        packet: &[u8],
+        stream: &mut S,
+    ) -> Result<Option<String>, SimError> {
+        let (command, args) = packet.split_at(packet.len().min(1));
+        let reply = match command {
+            b"?" => self.stop_reply(),
+            b"g" => self.or_error(|server| server.read_registers()),
+            b"G" => self.or_error(|server| server.write_registers(args).map(|_| "OK".to_string())),
+            b"p" => self.or_error(|server| {
+                let register = parse_hex(args)? as usize;
+                let cpu = server.cpu.lock()?;
+                let width = cpu.get_register_width();
+                Ok(encode_register(cpu.read_register(register)?, width))
+            }),
+            b"P" => self.or_error(|server| {
+                let (register, value) = split_once(args, b'=').ok_or(invalid_packet(packet))?;
+                let mut cpu = server.cpu.lock()?;
+                let value = decode_register(value, cpu.get_register_width())?;
+                cpu.write_register(parse_hex(register)? as usize, value)?;
+                Ok("OK".to_string())
+            }),
+            b"m" => self.or_error(|server| {
+                let (address, len) = split_once(args, b',').ok_or(invalid_packet(packet))?;
+                let data = server
+                    .cpu
+                    .lock()?
+                    .read_memory(parse_hex(address)?, parse_hex(len)? as usize)?;
+                Ok(encode_hex(&data))
+            }),
+            b"M" => self.or_error(|server| {
+                let (location, data) = split_once(args, b':').ok_or(invalid_packet(packet))?;
+                let (address, _) = split_once(location, b',').ok_or(invalid_packet(packet))?;
+                server
+                    .cpu
+                    .lock()?
+                    .write_memory(parse_hex(address)?, &decode_hex(data)?)?;
+                Ok("OK".to_string())
+            }),
+            b"Z" | b"z" => self.or_error(|server| {
+                let mut fields = args.split(|byte| *byte == b',');
+                let (Some(kind), Some(address)) = (fields.next(), fields.next()) else {
+                    return Err(invalid_packet(packet));
+                };
+                // software and hardware breakpoints are the same to the stub
+                if kind != b"0" && kind != b"1" {
+                    return Ok(String::new());
+                }
+                let address = parse_hex(address)?;
+                if command == b"Z" {
+                    server.breakpoints.insert(address);
+                } else {
+                    server.breakpoints.remove(&address);
+                }
+                Ok("OK".to_string())
+            }),
+            b"c" => {
+                self.stop_state = self.resume(false, stream)?;
+                self.stop_reply()
+            }
+            b"s" => {
+                self.stop_state = self.resume(true, stream)?;
+                self.stop_reply()
+            }
+            // gdb does not wait for a reply to a kill
+            b"k" => return Ok(None),
+            b"D" => {
+                write_packet(stream, "OK")?;
+                return Ok(None);
+            }
+            b"H" => "OK".to_string(),
+            b"q" if args.starts_with(b"Supported") => "PacketSize=4000".to_string(),
+            b"q" if args == b"Attached" => "1".to_string(),
+            b"q" if args == b"C" => "QC1".to_string(),
+            b"q" if args == b"fThreadInfo" => "m1".to_string(),
+            b"q" if args == b"sThreadInfo" => "l".to_string(),
+            b"T" => "OK".to_string(),
+            _ => String::new(),
+        };
+        Ok(Some(reply))
+    }
+
+    fn or_error<F: FnOnce(&mut Self) -> Result<String, SimError>>(&mut self, f: F) -> String {
+        f(self).unwrap_or_else(|_| "E01".to_string())
+    }
+
+    fn stop_reply(&self) -> String {
+        match self.stop_state {
+            StopState::Trap => "S05".to_string(),
+            StopState::Interrupted => "S02".to_string(),
+            StopState::Exited => "W00".to_string(),
+        }
+    }
+
+    fn read_registers(&self) -> Result<String, SimError> {
+        let cpu = self.cpu.lock()?;
+        let width = cpu.get_register_width();
+        (0..cpu.get_num_registers())
+            .map(|register| Ok(encode_register(cpu.read_register(register)?, width)))
+            .collect()
+    }
+
+    fn write_registers(&self, data: &[u8]) -> Result<(), SimError> {
+        let mut cpu = self.cpu.lock()?;
+        let width = cpu.get_register_width();
+        if data.len() != cpu.get_num_registers() * width * 2 {
+            return Err(SimError::GdbError(format!(
+                "expected {} registers",
+                cpu.get_num_registers()
+            )));
+        }
+        for register in 0..cpu.get_num_registers() {
+            let value = &data[register * width * 2..(register + 1) * width * 2];
+            cpu.write_register(register, decode_register(value, width)?)?;
+        }
+        Ok(())
+    }
+
+    /// Runs cycles until the next instruction retires if `single_step`,
+    /// otherwise until a breakpoint, a watchpoint, an interrupt or the end of the simulation
+    fn resume<S: GdbConnection>(
+        &mut self,
+        single_step: bool,
+        stream: &mut S,
+    ) -> Result<StopState, SimError> {
+        self.sim_manager.take_watchpoint_hits()?;
+        let mut retired = self.cpu.lock()?.get_instructions_retired();
+        let mut cycles: u64 = 0;
+        loop {
+            if self.sim_manager.sim_can_end() {
+                return Ok(StopState::Exited);
+            }
+            self.sim_manager.run_cycle()?;
+            self.sim_manager.run_cycle_end()?;
+            cycles += 1;
+
+            let (curr_retired, pc) = {
+                let cpu = self.cpu.lock()?;
+                (cpu.get_instructions_retired(), cpu.get_pc())
+            };
+            if curr_retired != retired {
+                if single_step || self.breakpoints.contains(&pc) {
+                    return Ok(StopState::Trap);
+                }
+                retired = curr_retired;
+            }
+            if !self.sim_manager.take_watchpoint_hits()?.is_empty() {
+                return Ok(StopState::Trap);
+            }
+            if cycles.is_multiple_of(INTERRUPT_CHECK_INTERVAL) && poll_interrupt(stream)? {
+                return Ok(StopState::Interrupted);
+            }
+        }
+    }
+}
+
+/// Reads the payload of the next packet, acknowledging it, returns `None` once the connection is closed.
+/// Interrupts received while stopped are ignored.
+fn read_packet<S: Read + Write>(stream: &mut S) -> Result<Option<Vec<u8>>, SimError> {
+    loop {
+        let Some(byte) = read_byte(stream)? else {
+            return Ok(None);
+        };
+        if byte != b'$' {
+            continue;
+        }
+        let mut data = Vec::new();
+        loop {
+            match read_byte(stream)? {
+                Some(b'#') => break,
+                Some(byte) => data.push(byte),
+                None => return Ok(None),
+            }
+        }
+        let (Some(high), Some(low)) = (read_byte(stream)?, read_byte(stream)?) else {
+            return Ok(None);
+        };
+        let checksum = std::str::from_utf8(&[high, low])
+            .ok()
+            .and_then(|checksum| u8::from_str_radix(checksum, 16).ok());
+        if checksum != Some(compute_checksum(&data)) {
+            stream.write_all(b"-")?;
+            continue;
+        }
+        stream.write_all(b"+")?;
+        return Ok(Some(data));
+    }
+}
+
+/// Sends a packet, retransmitting it until gdb acknowledges it with `+`.
+/// Any other byte received meanwhile, e.g. an interrupt, is ignored.
+fn write_packet<S: Read + Write>(stream: &mut S, data: &str) -> Result<(), SimError> {
+    let packet = format!("${}#{:02x}", data, compute_checksum(data.as_bytes()));
+    'send: loop {
+        stream.write_all(packet.as_bytes())?;
+        stream.flush()?;
+        loop {
+            match read_byte(stream)? {
+                Some(b'+') | None => return Ok(()),
+                Some(b'-') => continue 'send,
+                Some(_) => continue,
+            }
+        }
+    }
+}
+
+fn read_byte<S: Read>(stream: &mut S) -> Result<Option<u8>, SimError> {
+    let mut byte = [0u8];
+    loop {
+        match stream.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Returns whether gdb sent an interrupt (`0x03`) without blocking
+fn poll_interrupt<S: GdbConnection>(stream: &mut S) -> Result<bool, SimError> {
+    stream.set_nonblocking(true)?;
+    let mut byte = [0u8];
+    let result = stream.read(&mut byte);
+    stream.set_nonblocking(false)?;
+    match result {
+        Ok(1) => Ok(byte[0] == 0x03),
+        Ok(_) => Ok(false),
+        Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn compute_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn invalid_packet(packet: &[u8]) -> SimError {
+    SimError::GdbError(format!(
+        "invalid packet \"{}\"",
+        String::from_utf8_lossy(packet)
+    ))
+}
+
+/// Splits `data` around the first `separator`
+fn split_once(data: &[u8], separator: u8) -> Option<(&[u8], &[u8])> {
+    let index = data.iter().position(|byte| *byte == separator)?;
+    Some((&data[..index], &data[index + 1..]))
+}
+
+fn parse_hex(value: &[u8]) -> Result<u64, SimError> {
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(|value| u64::from_str_radix(value, 16).ok())
+        .ok_or(SimError::GdbError(format!(
+            "invalid hex number \"{}\"",
+            String::from_utf8_lossy(value)
+        )))
+}
+
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(data: &[u8]) -> Result<Vec<u8>, SimError> {
+    let invalid = || {
+        SimError::GdbError(format!(
+            "invalid hex data \"{}\"",
+            String::from_utf8_lossy(data)
+        ))
+    };
+    let nibble = |digit: u8| (digit as char).to_digit(16).ok_or_else(invalid);
+    if !data.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    data.chunks(2)
+        .map(|pair| Ok((nibble(pair[0])? << 4 | nibble(pair[1])?) as u8))
+        .collect()
+}
+
+/// Registers are sent in target byte order, which is assumed little endian
+fn encode_register(value: u64, width: usize) -> String {
+    encode_hex(&value.to_le_bytes()[..width.min(8)])
+}
+
+fn decode_register(value: &[u8], width: usize) -> Result<u64, SimError> {
+    let bytes = decode_hex(value)?;
+    if bytes.len() != width || width > 8 {
+        return Err(SimError::GdbError(format!(
+            "invalid register value \"{}\"",
+            String::from_utf8_lossy(value)
+        )));
+    }
+    Ok(bytes
+        .iter()
+        .rev()
+        .fold(0u64, |value, byte| (value << 8) | *byte as u64))
+}
@@ -1,10 +1,32 @@
+pub mod activity;
+pub mod assertion;
+pub mod channel;
+pub mod checkpoint;
 pub mod clock_event;
 pub mod component;
+pub mod components;
+pub mod coverage;
+pub mod credit;
+pub mod debugger;
+pub mod driver;
 pub mod error;
 pub mod event;
+pub mod fault;
+pub mod gdb;
+pub mod probe;
+pub mod profile;
+pub mod protocol;
+pub mod random;
+pub mod registry;
 pub mod rx;
+pub mod scoreboard;
 pub mod sim_dispatcher;
 pub mod sim_manager;
+pub mod stats;
+pub mod sweep;
 pub mod task;
+pub mod trace;
 pub mod tx;
 pub mod types;
+pub mod util;
+pub mod watchpoint;
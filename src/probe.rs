@@ -0,0 +1,45 @@
+use crate::event::Event;
+use crate::types::{Cycle, EventId};
+use std::any::Any;
+use std::sync::Arc;
+
+/// A `ProbeCommand` is delivered to an input port in place of a regular value,
+/// see `SimManager::force` and `SimManager::release`
+#[derive(Debug, Clone)]
+pub enum ProbeCommand {
+    /// Overrides the value of the port until released, whatever the driver sends
+    Force(Arc<dyn Any + Send + Sync>),
+    /// Hands the port back to its driver, restoring the last driven value
+    Release,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeEvent {
+    event_id: EventId,
+    scheduled_time: Cycle,
+    command: ProbeCommand,
+}
+
+impl ProbeEvent {
+    pub fn new(scheduled_time: Cycle, command: ProbeCommand, event_id: EventId) -> ProbeEvent {
+        ProbeEvent {
+            event_id,
+            scheduled_time,
+            command,
+        }
+    }
+}
+
+impl Event for ProbeEvent {
+    fn get_event_id(&self) -> EventId {
+        self.event_id
+    }
+
+    fn get_scheduled_time(&self) -> Cycle {
+        self.scheduled_time
+    }
+
+    fn get_data_as_any(&self) -> Box<dyn Any> {
+        Box::new(self.command.clone())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::types::ComponentId;
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+thread_local! {
+    /// Events received and sent by the component being polled on this thread
+    static EVENT_COUNTERS: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+/// Called by `Rx::try_recv` for every event received
+pub(crate) fn count_received() {
+    EVENT_COUNTERS.with(|counters| {
+        let (received, sent) = counters.get();
+        counters.set((received + 1, sent));
+    });
+}
+
+/// Called by `Tx::send` for every event sent
+pub(crate) fn count_sent(events: usize) {
+    EVENT_COUNTERS.with(|counters| {
+        let (received, sent) = counters.get();
+        counters.set((received, sent + events as u64));
+    });
+}
+
+/// Returns and resets the events received and sent on this thread
+pub(crate) fn take_event_counters() -> (u64, u64) {
+    EVENT_COUNTERS.with(|counters| counters.replace((0, 0)))
+}
+
+/// Wall-clock time and events of a single component, measured by its `SimDispatcher`.
+///
+/// `poll_recv` is the only entry point of a component, so its time is split by what the poll did:
+/// the poll taking the clock tick of a cycle out of the clock channel is counted as `on_clock`,
+/// other polls receiving events as `on_comb`, and the rest as idle polling.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ComponentProfile {
+    pub component_id: ComponentId,
+    /// The registered name, if any
+    pub name: Option<String>,
+    pub polls: u64,
+    pub clock_time: Duration,
+    pub comb_time: Duration,
+    pub idle_time: Duration,
+    pub events_received: u64,
+    pub events_sent: u64,
+}
+
+impl ComponentProfile {
+    pub fn new(component_id: ComponentId) -> Self {
+        ComponentProfile {
+            component_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_total_time(&self) -> Duration {
+        self.clock_time + self.comb_time + self.idle_time
+    }
+
+    pub(crate) fn record(&mut self, elapsed: Duration, took_clock: bool, received: u64, sent: u64) {
+        self.polls += 1;
+        self.events_received += received;
+        self.events_sent += sent;
+        if took_clock {
+            self.clock_time += elapsed;
+        } else if received > 0 {
+            self.comb_time += elapsed;
+        } else {
+            self.idle_time += elapsed;
+        }
+    }
+}
+
+/// The profiles of every component, slowest first
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    pub components: Vec<ComponentProfile>,
+}
+
+impl ProfileReport {
+    pub fn new(mut components: Vec<ComponentProfile>) -> Self {
+        components.sort_by(|a, b| {
+            b.get_total_time()
+                .cmp(&a.get_total_time())
+                .then(a.component_id.cmp(&b.component_id))
+        });
+        ProfileReport { components }
+    }
+
+    pub fn get_component(&self, component_id: ComponentId) -> Option<&ComponentProfile> {
+        self.components
+            .iter()
+            .find(|profile| profile.component_id == component_id)
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>12} {:>12} {:>12} {:>12} {:>10} {:>10}",
+            "component", "total (ms)", "clock (ms)", "comb (ms)", "idle (ms)", "received", "sent"
+        )?;
+        for profile in self.components.iter() {
+            let name = profile
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", profile.component_id));
+            writeln!(
+                f,
+                "{:<24} {:>12.3} {:>12.3} {:>12.3} {:>12.3} {:>10} {:>10}",
+                name,
+                profile.get_total_time().as_secs_f64() * 1000.0,
+                profile.clock_time.as_secs_f64() * 1000.0,
+                profile.comb_time.as_secs_f64() * 1000.0,
+                profile.idle_time.as_secs_f64() * 1000.0,
+                profile.events_received,
+                profile.events_sent
+            )?;
+        }
+        Ok(())
+    }
+}
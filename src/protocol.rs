@@ -0,0 +1,22 @@
+use crate::types::Cycle;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// A broken rule of a protocol, e.g. a ready/valid handshake, found by the checker of a channel or a bus
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolViolation {
+    /// The name of the channel or bus the checker watches
+    pub checker: String,
+    pub cycle: Cycle,
+    pub message: String,
+}
+
+impl Display for ProtocolViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at cycle {}: {}",
+            self.checker, self.cycle, self.message
+        )
+    }
+}
@@ -0,0 +1,163 @@
+use crate::error::SimError;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A small seeded random number generator (xoshiro256**), so that a seed always gives the same values
+/// regardless of the platform or of the version of an external crate
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+/// Expands a seed into the state of `Rng`
+fn split_mix(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *seed;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// A seed taken from the wall-clock time, for runs not given a seed
+pub fn get_time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut seed = seed;
+        Rng {
+            state: [
+                split_mix(&mut seed),
+                split_mix(&mut seed),
+                split_mix(&mut seed),
+                split_mix(&mut seed),
+            ],
+        }
+    }
+
+    /// An independent stream for each `stream` from the same seed, e.g. one per component
+    pub fn from_stream(seed: u64, stream: u64) -> Self {
+        let mut stream = stream;
+        Rng::new(seed ^ split_mix(&mut stream))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    pub fn next_u128(&mut self) -> u128 {
+        ((self.next_u64() as u128) << 64) | self.next_u64() as u128
+    }
+
+    /// Uniform in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// `true` with the given probability
+    pub fn gen_bool(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    /// Uniform in `range`, which must not be empty
+    pub fn gen_range<T: RandomValue>(&mut self, range: RangeInclusive<T>) -> T {
+        T::sample_range(self, *range.start(), *range.end())
+    }
+}
+
+/// A value `Rng` can draw uniformly from a range
+pub trait RandomValue: Copy + PartialOrd {
+    /// Uniform in `low..=high`, with `low <= high`
+    fn sample_range(rng: &mut Rng, low: Self, high: Self) -> Self;
+}
+
+macro_rules! impl_random_integer {
+    ($($t:ty),*) => {
+        $(
+            impl RandomValue for $t {
+                fn sample_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+                    // signed values are sign extended so the span is right modulo 2^128
+                    let span = (high as u128).wrapping_sub(low as u128);
+                    let offset = match span.checked_add(1) {
+                        Some(size) => rng.next_u128() % size,
+                        None => rng.next_u128(),
+                    };
+                    (low as u128).wrapping_add(offset) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_random_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl RandomValue for bool {
+    fn sample_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+        if low == high {
+            low
+        } else {
+            rng.gen_bool(0.5)
+        }
+    }
+}
+
+/// A weighted distribution over ranges of values,
+/// like `dist { [0:9] :/ 3, 100 :/ 1 }` in SystemVerilog: the weight is shared by the values of a range
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution<T> {
+    ranges: Vec<(RangeInclusive<T>, u64)>,
+    total_weight: u64,
+}
+
+impl<T: RandomValue> Distribution<T> {
+    /// Uniform in `range`
+    pub fn range(range: RangeInclusive<T>) -> Result<Self, SimError> {
+        Self::weighted(vec![(range, 1)])
+    }
+
+    /// Picks one of `ranges` with a probability proportional to its weight, then a value uniformly in it
+    pub fn weighted(ranges: Vec<(RangeInclusive<T>, u64)>) -> Result<Self, SimError> {
+        if ranges.iter().any(|(range, _)| range.is_empty()) {
+            return Err(SimError::StimulusError(
+                "empty range in distribution".to_string(),
+            ));
+        }
+        let total_weight = ranges
+            .iter()
+            .try_fold(0u64, |total, (_, weight)| total.checked_add(*weight))
+            .ok_or_else(|| SimError::StimulusError("distribution weights overflow".to_string()))?;
+        if total_weight == 0 {
+            return Err(SimError::StimulusError(
+                "distribution with no weight".to_string(),
+            ));
+        }
+        Ok(Distribution {
+            ranges,
+            total_weight,
+        })
+    }
+
+    pub fn sample(&self, rng: &mut Rng) -> T {
+        let mut target = rng.next_u64() % self.total_weight;
+        for (range, weight) in self.ranges.iter() {
+            if target < *weight {
+                return rng.gen_range(range.clone());
+            }
+            target -= weight;
+        }
+        unreachable!("the weights add up to total_weight")
+    }
+}
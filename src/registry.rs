@@ -0,0 +1,343 @@
+use crate::activity::PortActivity;
+use crate::component::Component;
+use crate::error::SimError;
+use crate::error::SimError::{ProbeError, RegistryError};
+use crate::event::Event;
+use crate::types::{ComponentHandle, ComponentId, Cycle, EventId, Output};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+}
+
+/// Called with the cycle and the value of every event sent on an output port, see `PortProbe::add_monitor`
+pub type PortMonitor = Box<dyn FnMut(Cycle, &dyn Any) + Send>;
+
+/// A `PortProbe` is a type-erased view of the state a port currently holds.
+/// It is what allows the registry to read and save ports without knowing their types.
+pub trait PortProbe: Send + Sync {
+    fn get_value_as_any(&self) -> Box<dyn Any>;
+    fn get_value_as_string(&self) -> String;
+    /// Serializes everything the port holds, for checkpoints
+    fn save_state(&self) -> Result<Value, SimError>;
+    fn restore_state(&self, state: Value) -> Result<(), SimError>;
+    /// Serializes the payload of an event carrying a value of the port's type
+    fn serialize_data(&self, data: &dyn Any) -> Result<Value, SimError>;
+    fn deserialize_data(&self, data: Value) -> Result<Arc<dyn Any + Send + Sync>, SimError>;
+    /// Rebuilds an event carrying a serialized value of the port's type
+    fn build_event(
+        &self,
+        data: Value,
+        event_id: EventId,
+        scheduled_time: Cycle,
+    ) -> Result<Box<dyn Event>, SimError>;
+    /// Starts counting events, value changes and bit toggles, see `SimManager::enable_activity_stats`
+    fn enable_activity(&self);
+    fn get_activity(&self) -> Option<PortActivity>;
+    /// The names of the bits of the current value, as in `PortActivity::bits`
+    fn get_bit_names(&self) -> Vec<String>;
+    /// Calls `monitor` on every value sent, only output ports can be monitored
+    fn add_monitor(&self, monitor: PortMonitor) -> Result<(), SimError>;
+}
+
+/// Implementation of `PortProbe::serialize_data` shared by the ports
+pub(crate) fn serialize_data<T: Serialize + 'static>(data: &dyn Any) -> Result<Value, SimError> {
+    let data = data.downcast_ref::<T>().ok_or(ProbeError(format!(
+        "expected payload of type {}",
+        std::any::type_name::<T>()
+    )))?;
+    Ok(serde_json::to_value(data)?)
+}
+
+/// Implementation of `PortProbe::deserialize_data` shared by the ports
+pub(crate) fn deserialize_data<T: DeserializeOwned + Send + Sync + 'static>(
+    data: Value,
+) -> Result<Arc<dyn Any + Send + Sync>, SimError> {
+    Ok(Arc::new(serde_json::from_value::<T>(data)?))
+}
+
+/// The registered name of an input port, shared with the `Tx` feeding it
+/// so that its events are addressed by name, see `Task::port`
+pub(crate) type PortName = Arc<OnceLock<Arc<str>>>;
+
+/// A `Port` is anything that can be registered with the registry, namely `Tx` and `Rx`
+pub trait Port {
+    fn get_direction(&self) -> PortDirection;
+    fn get_type_name(&self) -> &'static str;
+    fn get_type_id(&self) -> TypeId;
+    fn get_probe(&self) -> Arc<dyn PortProbe>;
+    /// The channel feeding an input port, used to force values onto it
+    fn get_input_sender(&self) -> Option<Output>;
+    /// Called once the port is registered, with its full name
+    fn set_registered_name(&self, name: &str);
+}
+
+#[derive(Clone, Debug)]
+pub struct PortInfo {
+    pub name: String,
+    pub component_id: ComponentId,
+    pub direction: PortDirection,
+    pub type_name: &'static str,
+}
+
+#[derive(Clone, Debug)]
+pub struct ComponentInfo {
+    pub name: String,
+    pub component_id: ComponentId,
+    pub ports: Vec<PortInfo>,
+}
+
+/// A handle to a registered port, for the parts of the framework going through every port
+#[derive(Clone)]
+pub(crate) struct PortHandle {
+    pub(crate) name: String,
+    pub(crate) probe: Arc<dyn PortProbe>,
+    pub(crate) input_sender: Option<Output>,
+}
+
+struct PortEntry {
+    info: PortInfo,
+    type_id: TypeId,
+    probe: Arc<dyn PortProbe>,
+    input_sender: Option<Output>,
+}
+
+struct ComponentEntry {
+    name: String,
+    handle: Option<Weak<Mutex<dyn Component>>>,
+}
+
+/// The `Registry` keeps track of every component and port of the simulation by their hierarchical name,
+/// e.g. `cpu.alu` for a component and `cpu.alu.out` for one of its ports.
+///
+/// Components fill it in during `Component::init`, see `SimManager::register_component`
+/// and `SimManager::register_port`.
+#[derive(Default)]
+pub struct Registry {
+    components: HashMap<ComponentId, ComponentEntry>,
+    component_names: BTreeMap<String, ComponentId>,
+    ports: BTreeMap<String, PortEntry>,
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("components", &self.component_names)
+            .field("ports", &self.ports.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn register_component(
+        &mut self,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        if !is_valid_name(name) {
+            return Err(RegistryError(format!(
+                "invalid component name \"{}\"",
+                name
+            )));
+        }
+        if self.component_names.contains_key(name) {
+            return Err(RegistryError(format!(
+                "duplicate component name \"{}\"",
+                name
+            )));
+        }
+        if let Some(entry) = self.components.get(&component_id) {
+            return Err(RegistryError(format!(
+                "component {} is already registered as \"{}\"",
+                component_id, entry.name
+            )));
+        }
+        self.component_names.insert(name.to_string(), component_id);
+        self.components.insert(
+            component_id,
+            ComponentEntry {
+                name: name.to_string(),
+                handle: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Registers `port` under the name of its component, i.e. `<component name>.<port_name>`
+    pub fn register_port(
+        &mut self,
+        component_id: ComponentId,
+        port_name: &str,
+        port: &dyn Port,
+    ) -> Result<(), SimError> {
+        let component_name = &self
+            .components
+            .get(&component_id)
+            .ok_or(RegistryError(format!(
+                "component {} is not registered",
+                component_id
+            )))?
+            .name;
+        if !is_valid_name(port_name) || port_name.contains('.') {
+            return Err(RegistryError(format!(
+                "invalid port name \"{}\"",
+                port_name
+            )));
+        }
+        let name = format!("{}.{}", component_name, port_name);
+        if self.ports.contains_key(&name) {
+            return Err(RegistryError(format!("duplicate port name \"{}\"", name)));
+        }
+        port.set_registered_name(&name);
+        self.ports.insert(
+            name.clone(),
+            PortEntry {
+                info: PortInfo {
+                    name,
+                    component_id,
+                    direction: port.get_direction(),
+                    type_name: port.get_type_name(),
+                },
+                type_id: port.get_type_id(),
+                probe: port.get_probe(),
+                input_sender: port.get_input_sender(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Attaches the handle of an already registered component, done by `SimDispatcher::init`
+    pub fn attach_component_handle(
+        &mut self,
+        component_id: ComponentId,
+        handle: Weak<Mutex<dyn Component>>,
+    ) {
+        if let Some(entry) = self.components.get_mut(&component_id) {
+            entry.handle = Some(handle);
+        }
+    }
+
+    /// Returns all registered components, sorted by name
+    pub fn get_components(&self) -> Vec<ComponentInfo> {
+        self.component_names
+            .keys()
+            .filter_map(|name| self.get_component(name))
+            .collect()
+    }
+
+    pub fn get_component(&self, name: &str) -> Option<ComponentInfo> {
+        let component_id = *self.component_names.get(name)?;
+        Some(ComponentInfo {
+            name: name.to_string(),
+            component_id,
+            ports: self.get_ports(name),
+        })
+    }
+
+    pub fn get_component_name(&self, component_id: ComponentId) -> Option<String> {
+        self.components
+            .get(&component_id)
+            .map(|entry| entry.name.clone())
+    }
+
+    pub fn get_component_handle(&self, component_id: ComponentId) -> Option<ComponentHandle> {
+        self.components
+            .get(&component_id)?
+            .handle
+            .as_ref()?
+            .upgrade()
+    }
+
+    /// Returns the components directly or indirectly under `prefix`, e.g. `cpu` gives `cpu.alu` and `cpu.alu.adder`
+    pub fn get_children(&self, prefix: &str) -> Vec<ComponentInfo> {
+        let prefix = format!("{}.", prefix);
+        self.component_names
+            .range(prefix.clone()..)
+            .take_while(|(name, _)| name.starts_with(&prefix))
+            .filter_map(|(name, _)| self.get_component(name))
+            .collect()
+    }
+
+    pub fn get_port(&self, name: &str) -> Option<PortInfo> {
+        self.ports.get(name).map(|entry| entry.info.clone())
+    }
+
+    /// Returns the ports of the component called `component_name`, sorted by name
+    pub fn get_ports(&self, component_name: &str) -> Vec<PortInfo> {
+        let prefix = format!("{}.", component_name);
+        self.ports
+            .range(prefix.clone()..)
+            .take_while(|(name, _)| name.starts_with(&prefix))
+            .filter(|(name, _)| !name[prefix.len()..].contains('.'))
+            .map(|(_, entry)| entry.info.clone())
+            .collect()
+    }
+
+    pub fn get_port_value_as_string(&self, name: &str) -> Option<String> {
+        self.ports
+            .get(name)
+            .map(|entry| entry.probe.get_value_as_string())
+    }
+
+    pub fn get_port_value_as_any(&self, name: &str) -> Option<Box<dyn Any>> {
+        self.ports
+            .get(name)
+            .map(|entry| entry.probe.get_value_as_any())
+    }
+
+    pub fn get_port_probe(&self, name: &str) -> Option<Arc<dyn PortProbe>> {
+        self.ports.get(name).map(|entry| entry.probe.clone())
+    }
+
+    /// Returns a handle to every registered port, sorted by name
+    pub(crate) fn get_port_handles(&self) -> Vec<PortHandle> {
+        self.ports
+            .iter()
+            .map(|(name, entry)| PortHandle {
+                name: name.clone(),
+                probe: entry.probe.clone(),
+                input_sender: entry.input_sender.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the name and handle of every registered component, sorted by name
+    pub(crate) fn get_component_handles(&self) -> Vec<(String, Option<ComponentHandle>)> {
+        self.component_names
+            .iter()
+            .map(|(name, component_id)| (name.clone(), self.get_component_handle(*component_id)))
+            .collect()
+    }
+
+    pub fn get_port_type_id(&self, name: &str) -> Option<TypeId> {
+        self.ports.get(name).map(|entry| entry.type_id)
+    }
+
+    /// Returns the channel feeding the input port called `name`, `None` for output ports
+    pub fn get_port_input_sender(&self, name: &str) -> Option<Output> {
+        self.ports.get(name)?.input_sender.clone()
+    }
+}
+
+/// A valid hierarchical name is a non-empty list of non-empty segments separated by `.`
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '[' || c == ']')
+        })
+}
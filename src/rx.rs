@@ -1,8 +1,21 @@
-use crate::event::Event;
+use crate::activity::{get_bits, ActivityCounter, PortActivity};
+use crate::error::SimError;
+use crate::event::{Event, ValueEvent};
+use crate::probe::ProbeCommand;
+use crate::profile;
+use crate::registry::{
+    deserialize_data, serialize_data, Port, PortDirection, PortMonitor, PortName, PortProbe,
+};
 use crate::rx::RxType::{NewValue, NoValue, OldValue};
-use crate::types::EventId;
+use crate::types::{Cycle, EventId, Output};
 use crossbeam_channel::{Receiver, Sender};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::fmt::Debug;
 use std::ops::Deref;
+use std::sync::{Arc, Mutex};
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum RxType {
@@ -11,33 +24,93 @@ pub enum RxType {
     NoValue,
 }
 
-pub struct Rx<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static> {
+/// The part of an `Rx` shared with the registry
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct RxState<T> {
     value: T,
     value_old: Option<T>,
+    value_driven: T,
+    forced: bool,
+    /// Not part of the simulation state, kept across restores and resets
+    #[serde(skip, default = "no_activity")]
+    activity: Option<ActivityCounter<T>>,
+}
+
+fn no_activity<T>() -> Option<ActivityCounter<T>> {
+    None
+}
+
+impl<T: Copy> RxState<T> {
+    fn record_activity(&mut self, previous: T, changed: bool) {
+        let value = self.value;
+        if let Some(activity) = self.activity.as_mut() {
+            activity.record(&previous, &value, changed);
+        }
+    }
+}
+
+pub struct Rx<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static> {
+    state: Arc<Mutex<RxState<T>>>,
     event_id: Option<EventId>,
     receiver: Receiver<Box<dyn Event>>,
+    sender: Option<Output>,
     ack_sender: Sender<EventId>,
+    name: PortName,
 }
 
 impl<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static> Rx<T> {
     pub fn new(receiver: Receiver<Box<dyn Event>>, ack_sender: Sender<EventId>) -> Self {
         Self {
-            value: Default::default(),
-            value_old: None,
+            state: Arc::new(Mutex::new(Default::default())),
             event_id: None,
             ack_sender,
             receiver,
+            sender: None,
+            name: PortName::default(),
         }
     }
 
+    /// Keeps a handle to the sending end of the channel so the port can be forced once registered
+    pub(crate) fn set_sender(&mut self, sender: Output) {
+        self.sender = Some(sender);
+    }
+
+    pub(crate) fn get_name(&self) -> PortName {
+        self.name.clone()
+    }
+
     pub fn try_recv(&mut self) -> RxType {
         if let Ok(event) = self.receiver.try_recv() {
+            profile::count_received();
             self.event_id = Some(event.get_event_id());
-            self.value = get_inner::<T>(&*event);
-            if self.value_old.is_some() && self.value == self.value_old.unwrap() {
+            let mut state = self.state.lock().unwrap();
+            let previous = state.value;
+            let value = match event.get_data_as_any().downcast::<T>() {
+                Ok(value) => {
+                    state.value_driven = *value;
+                    if state.forced {
+                        state.record_activity(previous, false);
+                        return OldValue;
+                    }
+                    *value
+                }
+                Err(data) => match *data.downcast::<ProbeCommand>().unwrap() {
+                    ProbeCommand::Force(value) => {
+                        state.forced = true;
+                        *value.downcast_ref::<T>().unwrap()
+                    }
+                    ProbeCommand::Release => {
+                        state.forced = false;
+                        state.value_driven
+                    }
+                },
+            };
+            state.value = value;
+            state.record_activity(previous, value != previous);
+            if state.value_old.is_some() && value == state.value_old.unwrap() {
                 OldValue
             } else {
-                self.value_old = Some(self.value);
+                state.value_old = Some(value);
                 NewValue
             }
         } else {
@@ -46,7 +119,7 @@ impl<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static> Rx<T> {
     }
 
     pub fn get_value(&self) -> T {
-        self.value
+        self.state.lock().unwrap().value
     }
 
     pub fn ack(&mut self) {
@@ -55,11 +128,132 @@ impl<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static> Rx<T> {
         }
     }
 
+    /// Receives and acks every pending event, the latest value is then read with `Rx::get_value`
+    pub fn drain(&mut self) {
+        while self.try_recv() != NoValue {
+            self.ack();
+        }
+    }
+
     pub fn reset(&mut self) {
-        self.value = Default::default();
-        self.value_old = None;
+        let mut state = self.state.lock().unwrap();
+        *state = RxState {
+            activity: state.activity.take(),
+            ..Default::default()
+        };
         self.event_id = None;
     }
+
+    /// Whether the port is currently overridden, see `SimManager::force`
+    pub fn is_forced(&self) -> bool {
+        self.state.lock().unwrap().forced
+    }
+}
+
+impl<
+        T: Default
+            + Clone
+            + Copy
+            + Sync
+            + Send
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static,
+    > Port for Rx<T>
+{
+    fn get_direction(&self) -> PortDirection {
+        PortDirection::Input
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn get_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn get_probe(&self) -> Arc<dyn PortProbe> {
+        self.state.clone()
+    }
+
+    fn get_input_sender(&self) -> Option<Output> {
+        self.sender.clone()
+    }
+
+    fn set_registered_name(&self, name: &str) {
+        let _ = self.name.set(name.into());
+    }
+}
+
+impl<T: Copy + Sync + Send + Debug + Serialize + DeserializeOwned + 'static> PortProbe
+    for Mutex<RxState<T>>
+{
+    fn get_value_as_any(&self) -> Box<dyn Any> {
+        Box::new(self.lock().unwrap().value)
+    }
+
+    fn get_value_as_string(&self) -> String {
+        format!("{:?}", self.lock().unwrap().value)
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(serde_json::to_value(&*self.lock()?)?)
+    }
+
+    fn restore_state(&self, state: Value) -> Result<(), SimError> {
+        let mut state: RxState<T> = serde_json::from_value(state)?;
+        let mut current = self.lock()?;
+        state.activity = current.activity.take();
+        *current = state;
+        Ok(())
+    }
+
+    fn serialize_data(&self, data: &dyn Any) -> Result<Value, SimError> {
+        serialize_data::<T>(data)
+    }
+
+    fn deserialize_data(&self, data: Value) -> Result<Arc<dyn Any + Send + Sync>, SimError> {
+        deserialize_data::<T>(data)
+    }
+
+    fn build_event(
+        &self,
+        data: Value,
+        event_id: EventId,
+        scheduled_time: Cycle,
+    ) -> Result<Box<dyn Event>, SimError> {
+        let value: T = serde_json::from_value(data)?;
+        Ok(Box::new(ValueEvent::new(scheduled_time, value, event_id)))
+    }
+
+    fn enable_activity(&self) {
+        let mut state = self.lock().unwrap();
+        state.activity = Some(ActivityCounter::new(get_bits::<T>, &state.value));
+    }
+
+    fn get_activity(&self) -> Option<PortActivity> {
+        self.lock()
+            .unwrap()
+            .activity
+            .as_ref()
+            .map(|activity| activity.get_activity())
+    }
+
+    fn get_bit_names(&self) -> Vec<String> {
+        get_bits(&self.lock().unwrap().value)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    fn add_monitor(&self, _monitor: PortMonitor) -> Result<(), SimError> {
+        Err(SimError::ProbeError(
+            "only output ports can be monitored".to_string(),
+        ))
+    }
 }
 
 /// A helper function that extracts the inner data from the event
@@ -0,0 +1,246 @@
+use crate::types::Cycle;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScoreboardMode {
+    /// Every transaction must be the next one of the reference model
+    InOrder,
+    /// A transaction may match any transaction of the reference model not matched yet,
+    /// e.g. for out-of-order completion
+    OutOfOrder,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// The transaction differs from the one the reference model expected
+    Different { expected: String, actual: String },
+    /// The transaction was sent while the reference model expected nothing more,
+    /// or, out of order, nothing like it
+    Unexpected { actual: String },
+    /// The reference model expected the transaction but it was never sent
+    Missing { expected: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub scoreboard: String,
+    pub port: String,
+    /// The cycle the transaction was sent, or the cycle the scoreboard was checked for missing transactions
+    pub cycle: Cycle,
+    pub kind: MismatchKind,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "scoreboard {} on {} at cycle {}: ",
+            self.scoreboard, self.port, self.cycle
+        )?;
+        match &self.kind {
+            MismatchKind::Different { expected, actual } => {
+                write!(f, "expected {}, got {}", expected, actual)
+            }
+            MismatchKind::Unexpected { actual } => write!(f, "unexpected {}", actual),
+            MismatchKind::Missing { expected } => write!(f, "missing {}", expected),
+        }
+    }
+}
+
+/// Produces the expected transactions one at a time, `None` once the reference model is done
+pub type ReferenceModel<T> = Box<dyn FnMut() -> Option<T> + Send>;
+
+struct ScoreboardState<T> {
+    name: String,
+    mode: ScoreboardMode,
+    reference: ReferenceModel<T>,
+    /// Transactions pulled from the reference model but not matched yet
+    pending: VecDeque<T>,
+    port: String,
+    matched: u64,
+    mismatches: Vec<Mismatch>,
+    /// The transactions sent up to this cycle were recorded before `SimManager::goto_cycle` went back,
+    /// the reference model cannot go back with them so they are not recorded again
+    skip_until: Option<Cycle>,
+}
+
+impl<T: PartialEq + Debug> ScoreboardState<T> {
+    fn mismatch(&mut self, cycle: Cycle, kind: MismatchKind) {
+        self.mismatches.push(Mismatch {
+            scoreboard: self.name.clone(),
+            port: self.port.clone(),
+            cycle,
+            kind,
+        });
+    }
+
+    fn record(&mut self, cycle: Cycle, actual: &T) {
+        if self
+            .skip_until
+            .is_some_and(|skip_until| cycle <= skip_until)
+        {
+            return;
+        }
+        let expected = match self.mode {
+            ScoreboardMode::InOrder => self.pending.pop_front().or_else(|| (self.reference)()),
+            ScoreboardMode::OutOfOrder => {
+                let mut position = self.pending.iter().position(|expected| expected == actual);
+                while position.is_none() {
+                    match (self.reference)() {
+                        Some(expected) => {
+                            if expected == *actual {
+                                position = Some(self.pending.len());
+                            }
+                            self.pending.push_back(expected);
+                        }
+                        None => break,
+                    }
+                }
+                position.and_then(|position| self.pending.remove(position))
+            }
+        };
+        match expected {
+            Some(expected) if expected == *actual => self.matched += 1,
+            Some(expected) => self.mismatch(
+                cycle,
+                MismatchKind::Different {
+                    expected: format!("{:?}", expected),
+                    actual: format!("{:?}", actual),
+                },
+            ),
+            None => self.mismatch(
+                cycle,
+                MismatchKind::Unexpected {
+                    actual: format!("{:?}", actual),
+                },
+            ),
+        }
+    }
+
+    fn finish(&mut self, cycle: Cycle) {
+        while let Some(expected) = (self.reference)() {
+            self.pending.push_back(expected);
+        }
+        while let Some(expected) = self.pending.pop_front() {
+            self.mismatch(
+                cycle,
+                MismatchKind::Missing {
+                    expected: format!("{:?}", expected),
+                },
+            );
+        }
+    }
+}
+
+/// Compares the transactions sent on an output port against a reference model,
+/// see `SimManager::add_scoreboard`.
+///
+/// Clones share the same state, so a clone can be kept to look at the results.
+pub struct Scoreboard<T> {
+    state: Arc<Mutex<ScoreboardState<T>>>,
+}
+
+impl<T> Clone for Scoreboard<T> {
+    fn clone(&self) -> Self {
+        Scoreboard {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T> Debug for Scoreboard<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("Scoreboard")
+            .field("name", &state.name)
+            .field("mode", &state.mode)
+            .field("port", &state.port)
+            .field("matched", &state.matched)
+            .field("mismatches", &state.mismatches.len())
+            .finish()
+    }
+}
+
+impl<T: PartialEq + Debug + Send + 'static> Scoreboard<T> {
+    pub fn new<F: FnMut() -> Option<T> + Send + 'static>(
+        name: &str,
+        mode: ScoreboardMode,
+        reference: F,
+    ) -> Self {
+        Scoreboard {
+            state: Arc::new(Mutex::new(ScoreboardState {
+                name: name.to_string(),
+                mode,
+                reference: Box::new(reference),
+                pending: VecDeque::new(),
+                port: String::new(),
+                matched: 0,
+                mismatches: Vec::new(),
+                skip_until: None,
+            })),
+        }
+    }
+
+    /// Builds a scoreboard expecting the transactions of `expected`, in order or not
+    pub fn from_expected<I: IntoIterator<Item = T>>(
+        name: &str,
+        mode: ScoreboardMode,
+        expected: I,
+    ) -> Self
+    where
+        I::IntoIter: Send + 'static,
+    {
+        let mut expected = expected.into_iter();
+        Self::new(name, mode, move || expected.next())
+    }
+
+    pub fn get_name(&self) -> String {
+        self.state.lock().unwrap().name.clone()
+    }
+
+    pub(crate) fn set_port(&self, port: &str) {
+        self.state.lock().unwrap().port = port.to_string();
+    }
+
+    pub(crate) fn record(&self, cycle: Cycle, actual: &T) {
+        self.state.lock().unwrap().record(cycle, actual);
+    }
+
+    /// Number of transactions that matched the reference model
+    pub fn get_matched(&self) -> u64 {
+        self.state.lock().unwrap().matched
+    }
+
+    pub fn get_mismatches(&self) -> Vec<Mismatch> {
+        self.state.lock().unwrap().mismatches.clone()
+    }
+}
+
+/// The type-erased view of a scoreboard kept by the simulation manager
+pub(crate) trait ScoreboardCheck: Send + Debug {
+    fn get_name(&self) -> String;
+    /// Reports every transaction the reference model still expects as missing,
+    /// then returns all the mismatches
+    fn finish(&self, cycle: Cycle) -> Vec<Mismatch>;
+    /// Ignores the transactions sent up to `cycle`, see `ScoreboardState::skip_until`
+    fn skip_until(&self, cycle: Cycle);
+}
+
+impl<T: PartialEq + Debug + Send + 'static> ScoreboardCheck for Scoreboard<T> {
+    fn get_name(&self) -> String {
+        Scoreboard::get_name(self)
+    }
+
+    fn finish(&self, cycle: Cycle) -> Vec<Mismatch> {
+        let mut state = self.state.lock().unwrap();
+        state.finish(cycle);
+        state.mismatches.clone()
+    }
+
+    fn skip_until(&self, cycle: Cycle) {
+        let mut state = self.state.lock().unwrap();
+        state.skip_until = state.skip_until.max(Some(cycle));
+    }
+}
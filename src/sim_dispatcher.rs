@@ -1,10 +1,15 @@
 use crate::component::Component;
+use crate::profile::{self, ComponentProfile};
 use crate::sim_manager::SimManager;
+use crate::types::Output;
 use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
 
 pub struct SimDispatcher {
     sim_manager: Weak<SimManager>,
     components: Vec<Arc<Mutex<dyn Component>>>,
+    /// The clock tick handler each component registered in `init`, if any, for profiling
+    clock_ticks: Mutex<Vec<Option<Output>>>,
 }
 
 impl SimDispatcher {
@@ -14,6 +19,7 @@ impl SimDispatcher {
     ) -> Arc<Self> {
         Arc::new(SimDispatcher {
             sim_manager,
+            clock_ticks: Mutex::new(vec![None; components.len()]),
             components,
         })
     }
@@ -22,12 +28,32 @@ impl SimDispatcher {
     /// This function in turns calls the `init` of all its child components.
     /// see `crate::component::Component::init`
     pub fn init(self: &Arc<Self>) {
-        for component in self.components.iter() {
-            component.lock().unwrap().init()
+        let sim_manager = self.sim_manager.upgrade();
+        for (index, component) in self.components.iter().enumerate() {
+            let num_clock_ticks = sim_manager
+                .as_ref()
+                .map(|sim_manager| sim_manager.get_num_clock_ticks());
+            let component_id = {
+                let mut locked_component = component.lock().unwrap();
+                locked_component.init();
+                locked_component.get_component_id()
+            };
+            if let Some(sim_manager) = sim_manager.as_ref() {
+                if num_clock_ticks != Some(sim_manager.get_num_clock_ticks()) {
+                    self.clock_ticks.lock().unwrap()[index] =
+                        sim_manager.get_clock_tick(sim_manager.get_num_clock_ticks() - 1);
+                }
+                sim_manager
+                    .get_registry()
+                    .attach_component_handle(component_id, Arc::downgrade(component));
+            }
         }
     }
 
     pub fn run(self: &Arc<Self>) {
+        if self.sim_manager.upgrade().unwrap().is_profiling() {
+            return self.run_profiled();
+        }
         loop {
             for component in self.components.iter() {
                 component.lock().unwrap().poll_recv()
@@ -37,4 +63,38 @@ impl SimDispatcher {
             }
         }
     }
+
+    /// Same as `SimDispatcher::run`, measuring every `poll_recv`, see `ComponentProfile`
+    fn run_profiled(self: &Arc<Self>) {
+        let clock_ticks = self.clock_ticks.lock().unwrap().clone();
+        let mut profiles: Vec<ComponentProfile> = self
+            .components
+            .iter()
+            .map(|component| ComponentProfile::new(component.lock().unwrap().get_component_id()))
+            .collect();
+        loop {
+            let sim_manager = self.sim_manager.upgrade().unwrap();
+            for (index, component) in self.components.iter().enumerate() {
+                // a single clock tick is in flight at a time, the poll emptying its channel took it
+                let clock_pending = clock_ticks[index]
+                    .as_ref()
+                    .is_some_and(|clock_tick| !clock_tick.is_empty());
+                let mut locked_component = component.lock().unwrap();
+                profile::take_event_counters();
+                let start = Instant::now();
+                locked_component.poll_recv();
+                let elapsed = start.elapsed();
+                let (received, sent) = profile::take_event_counters();
+                let took_clock = clock_pending
+                    && clock_ticks[index]
+                        .as_ref()
+                        .is_some_and(|clock_tick| clock_tick.is_empty());
+                profiles[index].record(elapsed, took_clock, received, sent);
+            }
+            if sim_manager.sim_can_end() {
+                break;
+            }
+        }
+        let _ = self.sim_manager.upgrade().unwrap().add_profiles(profiles);
+    }
 }
@@ -1,13 +1,34 @@
+use crate::activity::ActivityReport;
+use crate::assertion::{AssertionChecker, AssertionFailure, AssertionId, Property};
+use crate::checkpoint::{Checkpoint, SnapshotStore, VerificationState};
 use crate::clock_event::ClockEvent;
+use crate::coverage::{CoverageCollector, CoverageReport, Covergroup};
 use crate::error::SimError;
 use crate::event::Event;
+use crate::fault::{get_fault_stream, ActiveFaults, Fault, FaultInjection, FaultInjector};
+use crate::probe::{ProbeCommand, ProbeEvent};
+use crate::profile::{ComponentProfile, ProfileReport};
+use crate::protocol::ProtocolViolation;
+use crate::random::{get_time_seed, Rng};
+use crate::registry::{Port, PortDirection, Registry};
+use crate::scoreboard::{Scoreboard, ScoreboardCheck};
+use crate::stats::{Counter, SimStats, StatsCollector};
 use crate::task::Task;
+use crate::trace::{EventRecorder, SerializedEvent};
 use crate::types::Output;
 use crate::types::{ComponentId, Cycle, EventId};
+use crate::util::parse_integer;
+use crate::watchpoint::{
+    StopReason, WatchCondition, Watchpoint, WatchpointHit, WatchpointId, WatchpointList,
+};
 use crossbeam_channel::{Receiver, Sender};
+use std::any::TypeId;
 use std::collections::binary_heap::BinaryHeap;
-use std::collections::HashSet;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 #[derive(Debug)]
 pub struct SimManager {
@@ -19,8 +40,30 @@ pub struct SimManager {
     ack_recv: Receiver<EventId>,
     component_do_not_end_set: Mutex<HashSet<ComponentId>>,
     event_processed: Mutex<u128>,
+    registry: Mutex<Registry>,
+    recorder: Mutex<Option<EventRecorder>>,
+    replay_end: Mutex<Cycle>,
+    snapshots: Mutex<SnapshotStore>,
+    watchpoints: Mutex<WatchpointList>,
+    assertions: Mutex<AssertionChecker>,
+    coverage: Mutex<CoverageCollector>,
+    activity_start: Mutex<Option<Cycle>>,
+    profiling: Mutex<bool>,
+    profiles: Mutex<Vec<ComponentProfile>>,
+    stats: Mutex<StatsCollector>,
+    scoreboards: Mutex<Vec<Box<dyn ScoreboardCheck>>>,
+    /// The master seed, chosen the first time it is needed if not set
+    seed: Mutex<Option<u64>>,
+    component_seeds: Mutex<BTreeMap<String, u64>>,
+    faults: Mutex<FaultInjector>,
+    /// Whether any fault is injected, so that `Tx::send` skips the lookup otherwise
+    has_faults: AtomicBool,
+    protocol_violations: Mutex<Vec<ProtocolViolation>>,
 }
 
+/// The environment variable setting the master seed when `SimManager::set_seed` is not called
+pub const SEED_ENV_VAR: &str = "RSIM_SEED";
+
 impl SimManager {
     pub fn new(ack_recv: Receiver<EventId>) -> Arc<Self> {
         Arc::new(SimManager {
@@ -32,11 +75,34 @@ impl SimManager {
             ack_recv,
             component_do_not_end_set: Mutex::new(HashSet::new()),
             event_processed: Mutex::new(0),
+            registry: Mutex::new(Registry::new()),
+            recorder: Mutex::new(None),
+            replay_end: Mutex::new(0),
+            snapshots: Mutex::new(SnapshotStore::default()),
+            watchpoints: Mutex::new(WatchpointList::default()),
+            assertions: Mutex::new(AssertionChecker::default()),
+            coverage: Mutex::new(CoverageCollector::default()),
+            activity_start: Mutex::new(None),
+            profiling: Mutex::new(false),
+            profiles: Mutex::new(Vec::new()),
+            stats: Mutex::new(StatsCollector::default()),
+            scoreboards: Mutex::new(Vec::new()),
+            seed: Mutex::new(None),
+            component_seeds: Mutex::new(BTreeMap::new()),
+            faults: Mutex::new(FaultInjector::default()),
+            has_faults: AtomicBool::new(false),
+            protocol_violations: Mutex::new(Vec::new()),
         })
     }
 
     pub fn enq_event(&self, event: Task) {
-        let _ = self.event_q.lock().map(|mut event_q| event_q.push(event));
+        let event_q_depth = self.event_q.lock().map(|mut event_q| {
+            event_q.push(event);
+            event_q.len()
+        });
+        if let (Ok(event_q_depth), Ok(mut stats)) = (event_q_depth, self.stats.lock()) {
+            stats.record_scheduled(event_q_depth);
+        }
     }
 
     pub fn get_curr_cycle(&self) -> Cycle {
@@ -62,6 +128,14 @@ impl SimManager {
         self.clock_tick_q.lock().unwrap().push(sender)
     }
 
+    pub(crate) fn get_num_clock_ticks(&self) -> usize {
+        self.clock_tick_q.lock().unwrap().len()
+    }
+
+    pub(crate) fn get_clock_tick(&self, index: usize) -> Option<Output> {
+        self.clock_tick_q.lock().unwrap().get(index).cloned()
+    }
+
     pub fn register_do_not_end(&self, component_id: ComponentId) {
         let _ = self
             .component_do_not_end_set
@@ -76,12 +150,132 @@ impl SimManager {
             .map(|mut set| set.remove(&component_id));
     }
 
-    /// The sim can end if every component says we can
+    /// Registers a component under a hierarchical name such as `cpu.alu`,
+    /// meant to be called from `Component::init`
+    pub fn register_component(
+        &self,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        self.registry.lock()?.register_component(component_id, name)
+    }
+
+    /// Registers a port of an already registered component as `<component name>.<port_name>`
+    pub fn register_port(
+        &self,
+        component_id: ComponentId,
+        port_name: &str,
+        port: &dyn Port,
+    ) -> Result<(), SimError> {
+        self.registry
+            .lock()?
+            .register_port(component_id, port_name, port)
+    }
+
+    /// Gives access to the registry for introspection, e.g. listing components and reading port values
+    ///
+    /// The registry stays locked as long as the guard is held
+    pub fn get_registry(&self) -> MutexGuard<'_, Registry> {
+        self.registry.lock().unwrap()
+    }
+
+    /// Reads the current value of the port called `name`
+    pub fn peek<T: Copy + 'static>(&self, name: &str) -> Result<T, SimError> {
+        let value =
+            self.registry
+                .lock()?
+                .get_port_value_as_any(name)
+                .ok_or(SimError::RegistryError(format!(
+                    "unknown port \"{}\"",
+                    name
+                )))?;
+        value
+            .downcast::<T>()
+            .map(|value| *value)
+            .map_err(|_| SimError::ProbeError(format!("type mismatch on port \"{}\"", name)))
+    }
+
+    /// Reads the current value of the port called `name`, formatted with `Debug`
+    pub fn peek_as_string(&self, name: &str) -> Result<String, SimError> {
+        self.registry
+            .lock()?
+            .get_port_value_as_string(name)
+            .ok_or(SimError::RegistryError(format!(
+                "unknown port \"{}\"",
+                name
+            )))
+    }
+
+    /// Overrides the input port called `name` with `value` starting at `cycle`.
+    ///
+    /// Events from the driver are still acknowledged but do not change the value of the port
+    /// until `SimManager::release` is called.
+    pub fn force<T: Copy + Send + Sync + 'static>(
+        &self,
+        name: &str,
+        value: T,
+        cycle: Cycle,
+    ) -> Result<(), SimError> {
+        let type_id =
+            self.registry
+                .lock()?
+                .get_port_type_id(name)
+                .ok_or(SimError::RegistryError(format!(
+                    "unknown port \"{}\"",
+                    name
+                )))?;
+        if type_id != TypeId::of::<T>() {
+            return Err(SimError::ProbeError(format!(
+                "type mismatch on port \"{}\"",
+                name
+            )));
+        }
+        self.enq_probe_command(name, ProbeCommand::Force(Arc::new(value)), cycle)
+    }
+
+    /// Hands the input port called `name` back to its driver starting at `cycle`,
+    /// the port takes the last value the driver sent
+    pub fn release(&self, name: &str, cycle: Cycle) -> Result<(), SimError> {
+        self.enq_probe_command(name, ProbeCommand::Release, cycle)
+    }
+
+    fn enq_probe_command(
+        &self,
+        name: &str,
+        command: ProbeCommand,
+        cycle: Cycle,
+    ) -> Result<(), SimError> {
+        if cycle < self.get_curr_cycle() {
+            return Err(SimError::ProbeError(format!(
+                "cycle {} is in the past",
+                cycle
+            )));
+        }
+        let sender =
+            self.registry
+                .lock()?
+                .get_port_input_sender(name)
+                .ok_or(SimError::ProbeError(format!(
+                    "\"{}\" is not a registered input port",
+                    name
+                )))?;
+        let event = ProbeEvent::new(cycle, command, self.request_new_event_id());
+        self.enq_event(Task::new(Box::new(event), sender).with_port(Some(name.into())));
+        Ok(())
+    }
+
+    /// The sim can end if every component says we can,
+    /// and every replayed event has been delivered
     pub fn sim_can_end(&self) -> bool {
         self.component_do_not_end_set
             .lock()
             .map(|set| set.is_empty())
             .unwrap_or(false)
+            && self
+                .replay_end
+                .lock()
+                .map(|replay_end| *replay_end <= self.get_curr_cycle())
+                .unwrap_or(false)
     }
 
     fn recv_ack(&self) {
@@ -91,6 +285,7 @@ impl SimManager {
                     panic!("ack'd non-existing task");
                 }
                 *self.event_processed.lock().unwrap() += 1;
+                self.stats.lock().unwrap().record_processed();
             }
         }
     }
@@ -102,22 +297,35 @@ impl SimManager {
     /// Pops the first sendable event from the event q, sends it through the channel and add the event id to the rob
     fn send_events(&self) {
         let mut locked_event_q = self.event_q.lock().unwrap();
+        let mut locked_recorder = self.recorder.lock().unwrap();
+        let mut delivered = 0;
         while let Some(task) = locked_event_q.peek() {
             if task.event.get_scheduled_time() <= self.get_curr_cycle() {
                 if task.event.get_scheduled_time() < self.get_curr_cycle() {
                     panic!("Time fault detected!");
                 }
                 if let Some(task) = locked_event_q.pop() {
+                    if let Some(recorder) = locked_recorder.as_mut() {
+                        recorder.record(&task);
+                    }
                     let _ = self
                         .rob
                         .lock()
                         .map(|mut rob| rob.insert(task.event.get_event_id()));
                     let _ = task.event_callback.try_send(task.event);
+                    delivered += 1;
                 };
             } else {
                 break;
             }
         }
+        if delivered > 0 {
+            let rob_size = self.rob.lock().unwrap().len();
+            self.stats
+                .lock()
+                .unwrap()
+                .record_delivered(delivered, rob_size);
+        }
     }
 
     /// Sends out all clock tasks
@@ -126,10 +334,7 @@ impl SimManager {
             for clock_tick_task in clock_tick_q.iter() {
                 let clock_event =
                     ClockEvent::new(self.get_curr_cycle(), self.request_new_event_id());
-                self.event_q
-                    .lock()
-                    .unwrap()
-                    .push(Task::new(Box::new(clock_event), clock_tick_task.clone()));
+                self.enq_event(Task::new(Box::new(clock_event), clock_tick_task.clone()));
             }
         }
     }
@@ -175,12 +380,17 @@ impl SimManager {
     /// This should be used in combination with `SimManager::run_cycle_end`
     /// for the combination logic to propagate through
     pub fn run_cycle(&self) -> Result<(), SimError> {
+        self.stats.lock()?.record_start();
         loop {
             self.recv_ack();
             self.send_events();
 
             // Time to move on to the next cycle
             if self.can_increase_cycle()? {
+                self.take_snapshot_if_due()?;
+                self.check_assertions()?;
+                self.sample_coverage()?;
+                self.stats.lock()?.record_cycle_end();
                 self.increment_cycle();
                 self.schedule_clock_tasks();
                 self.send_events();
@@ -193,15 +403,815 @@ impl SimManager {
         }
     }
 
-    /// Continues the simulation until `SimManager::sim_can_end`
-    pub fn run(&self) -> Result<(), SimError> {
+    /// Continues the simulation until `SimManager::sim_can_end`, writing the coverage report if any,
+    /// until the end of a cycle in which a watchpoint triggered, see `SimManager::add_watchpoint`,
+    /// or until an assertion stopping on failure fails, the last cycle included, see `SimManager::add_assertion`
+    pub fn run(&self) -> Result<StopReason, SimError> {
+        self.take_watchpoint_hits()?;
+        self.assertions.lock()?.take_stop();
         loop {
             self.run_cycle()?;
 
-            if self.sim_can_end() {
+            let sim_can_end = self.sim_can_end();
+            if sim_can_end {
+                // no clock tick follows the last cycle to check and sample it
+                self.check_assertions()?;
+                self.sample_coverage()?;
+                self.coverage.lock()?.write_report()?;
+            }
+
+            let failures = self.assertions.lock()?.take_stop();
+            if !failures.is_empty() {
+                return Ok(StopReason::AssertionFailed(failures));
+            }
+            if sim_can_end {
                 break;
             }
+
+            if self.watchpoints.lock()?.has_enabled() {
+                self.run_cycle_end()?;
+                let hits = self.take_watchpoint_hits()?;
+                if !hits.is_empty() {
+                    return Ok(StopReason::Watchpoint(hits));
+                }
+            }
+        }
+        Ok(StopReason::SimEnd)
+    }
+
+    /// Watches the registered output port called `port`, checked on every `Tx::send`
+    pub fn add_watchpoint(
+        &self,
+        port: &str,
+        condition: WatchCondition,
+    ) -> Result<WatchpointId, SimError> {
+        let is_output = self
+            .registry
+            .lock()?
+            .get_port(port)
+            .is_some_and(|port| port.direction == PortDirection::Output);
+        if !is_output {
+            return Err(SimError::RegistryError(format!(
+                "\"{}\" is not a registered output port",
+                port
+            )));
+        }
+        Ok(self.watchpoints.lock()?.add(port, condition))
+    }
+
+    pub fn remove_watchpoint(&self, id: WatchpointId) -> Result<(), SimError> {
+        if self.watchpoints.lock()?.remove(id) {
+            Ok(())
+        } else {
+            Err(SimError::RegistryError(format!("no watchpoint {}", id)))
+        }
+    }
+
+    pub fn set_watchpoint_enabled(&self, id: WatchpointId, enabled: bool) -> Result<(), SimError> {
+        if self.watchpoints.lock()?.set_enabled(id, enabled) {
+            Ok(())
+        } else {
+            Err(SimError::RegistryError(format!("no watchpoint {}", id)))
+        }
+    }
+
+    pub fn get_watchpoints(&self) -> Result<Vec<Watchpoint>, SimError> {
+        Ok(self.watchpoints.lock()?.get_watchpoints().to_vec())
+    }
+
+    /// Returns the watchpoint hits since the last call
+    pub fn take_watchpoint_hits(&self) -> Result<Vec<WatchpointHit>, SimError> {
+        Ok(self.watchpoints.lock()?.take_hits())
+    }
+
+    /// Adds a temporal assertion on registered ports, checked at the end of every cycle.
+    ///
+    /// Failures are kept, see `SimManager::get_assertion_failures`,
+    /// and stop `SimManager::run` if `stop_on_failure`.
+    /// Obligations still pending when the simulation ends are not reported.
+    pub fn add_assertion(
+        &self,
+        name: &str,
+        property: Property,
+        stop_on_failure: bool,
+    ) -> Result<AssertionId, SimError> {
+        if let Property::Implies {
+            min_delay,
+            max_delay,
+            ..
+        } = property
+        {
+            if min_delay > max_delay {
+                return Err(SimError::AssertionError(format!(
+                    "{}: min delay {} is greater than max delay {}",
+                    name, min_delay, max_delay
+                )));
+            }
+        }
+        {
+            let registry = self.registry.lock()?;
+            if let Some(port) = property
+                .get_ports()
+                .into_iter()
+                .find(|port| registry.get_port(port).is_none())
+            {
+                return Err(SimError::RegistryError(format!(
+                    "unknown port \"{}\"",
+                    port
+                )));
+            }
+        }
+        Ok(self.assertions.lock()?.add(name, property, stop_on_failure))
+    }
+
+    pub fn get_assertion_failures(&self) -> Result<Vec<AssertionFailure>, SimError> {
+        Ok(self.assertions.lock()?.get_failures().to_vec())
+    }
+
+    /// Checks the assertions against the port values at the end of the current cycle
+    fn check_assertions(&self) -> Result<(), SimError> {
+        let mut assertions = self.assertions.lock()?;
+        if assertions.is_empty() {
+            return Ok(());
+        }
+        let values = self.get_port_values(assertions.get_ports())?;
+        assertions.check(self.get_curr_cycle(), &values);
+        Ok(())
+    }
+
+    fn get_port_values(&self, ports: Vec<String>) -> Result<BTreeMap<String, String>, SimError> {
+        let registry = self.registry.lock()?;
+        Ok(ports
+            .into_iter()
+            .filter_map(|port| {
+                registry
+                    .get_port_value_as_string(&port)
+                    .map(|value| (port, value))
+            })
+            .collect())
+    }
+
+    /// Adds a covergroup over registered ports,
+    /// sampled at every clock tick with the values at the end of the cycle before
+    pub fn add_covergroup(&self, group: Covergroup) -> Result<(), SimError> {
+        group.validate()?;
+        {
+            let registry = self.registry.lock()?;
+            if let Some(coverpoint) = group
+                .coverpoints
+                .iter()
+                .find(|coverpoint| registry.get_port(&coverpoint.port).is_none())
+            {
+                return Err(SimError::RegistryError(format!(
+                    "unknown port \"{}\"",
+                    coverpoint.port
+                )));
+            }
+        }
+        let mut coverage = self.coverage.lock()?;
+        if coverage.get_report().groups.contains_key(&group.name) {
+            return Err(SimError::CoverageError(format!(
+                "covergroup \"{}\" already exists",
+                group.name
+            )));
+        }
+        coverage.add(group);
+        Ok(())
+    }
+
+    /// Has `SimManager::run` write the coverage report to `path` once the simulation ends,
+    /// as text with the `txt` extension and as JSON with the `json` extension,
+    /// see `CoverageReport::merge_files` to merge the reports of several runs
+    pub fn set_coverage_report<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        self.coverage
+            .lock()?
+            .set_report_path(Some(path.as_ref().to_path_buf()));
+        Ok(())
+    }
+
+    pub fn get_coverage(&self) -> Result<CoverageReport, SimError> {
+        Ok(self.coverage.lock()?.get_report().clone())
+    }
+
+    fn sample_coverage(&self) -> Result<(), SimError> {
+        let mut coverage = self.coverage.lock()?;
+        if coverage.is_empty() {
+            return Ok(());
+        }
+        let values = self.get_port_values(coverage.get_ports())?;
+        coverage.sample(self.get_curr_cycle(), &values);
+        Ok(())
+    }
+
+    /// Starts counting, for every registered port, the events, the value changes and the bit toggles.
+    ///
+    /// This should be called after the components are initialized, ports registered later are not counted.
+    pub fn enable_activity_stats(&self) -> Result<(), SimError> {
+        for handle in self.registry.lock()?.get_port_handles() {
+            handle.probe.enable_activity();
+        }
+        *self.activity_start.lock()? = Some(self.get_curr_cycle());
+        Ok(())
+    }
+
+    pub fn get_activity_report(&self) -> Result<ActivityReport, SimError> {
+        let start = self.activity_start.lock()?.ok_or(SimError::ProbeError(
+            "activity stats are not enabled".to_string(),
+        ))?;
+        let ports = self
+            .registry
+            .lock()?
+            .get_port_handles()
+            .into_iter()
+            .filter_map(|handle| {
+                handle
+                    .probe
+                    .get_activity()
+                    .map(|activity| (handle.name, activity))
+            })
+            .collect();
+        Ok(ActivityReport {
+            cycles: self.get_curr_cycle() - start,
+            ports,
+        })
+    }
+
+    /// Has the dispatchers measure the time and events of every component, see `SimManager::get_profile_report`.
+    ///
+    /// This must be called before the dispatchers are spawned.
+    pub fn enable_profiling(&self) -> Result<(), SimError> {
+        *self.profiling.lock()? = true;
+        Ok(())
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        *self.profiling.lock().unwrap()
+    }
+
+    /// Called by the dispatchers once they are done
+    pub(crate) fn add_profiles(&self, profiles: Vec<ComponentProfile>) -> Result<(), SimError> {
+        self.profiles.lock()?.extend(profiles);
+        Ok(())
+    }
+
+    /// The profiles of the components, available once their dispatchers returned from `SimDispatcher::run`
+    pub fn get_profile_report(&self) -> Result<ProfileReport, SimError> {
+        let mut profiles = self.profiles.lock()?.clone();
+        let registry = self.registry.lock()?;
+        for profile in profiles.iter_mut() {
+            profile.name = registry.get_component_name(profile.component_id);
+        }
+        Ok(ProfileReport::new(profiles))
+    }
+
+    /// Registers a statistic reported along with the simulation statistics, see `SimManager::get_stats`.
+    ///
+    /// Components usually register their counters in `Component::init` and keep the returned handle.
+    pub fn register_counter(&self, name: &str) -> Result<Counter, SimError> {
+        self.stats
+            .lock()?
+            .register_counter(name)
+            .ok_or(SimError::RegistryError(format!(
+                "counter \"{}\" is already registered",
+                name
+            )))
+    }
+
+    pub fn get_stats(&self) -> Result<SimStats, SimError> {
+        let mut stats = self.stats.lock()?.get_stats();
+        stats.seed = *self.seed.lock()?;
+        stats.component_seeds = self.component_seeds.lock()?.clone();
+        Ok(stats)
+    }
+
+    /// Sets the master seed every random stream derives from, see `SimManager::get_rng`.
+    ///
+    /// This should be called before the components are built, as they take their stream when built or in `init`,
+    /// e.g. `RandomDriver::build`.
+    pub fn set_seed(&self, seed: u64) -> Result<(), SimError> {
+        *self.seed.lock()? = Some(seed);
+        self.coverage.lock()?.set_seed(seed);
+        Ok(())
+    }
+
+    /// Returns the master seed.
+    ///
+    /// If it was not set, it is read from the `RSIM_SEED` environment variable, in decimal or hexadecimal,
+    /// or taken from the wall-clock time. It is part of the stats, see `SimManager::get_stats`,
+    /// so that the run can be reproduced.
+    pub fn get_seed(&self) -> Result<u64, SimError> {
+        if let Some(seed) = *self.seed.lock()? {
+            return Ok(seed);
+        }
+        let seed = match std::env::var(SEED_ENV_VAR) {
+            Ok(value) => parse_integer(&value)
+                .and_then(|seed| u64::try_from(seed).ok())
+                .ok_or(SimError::StimulusError(format!(
+                    "invalid {} \"{}\"",
+                    SEED_ENV_VAR, value
+                )))?,
+            Err(_) => get_time_seed(),
+        };
+        self.set_seed(seed)?;
+        Ok(seed)
+    }
+
+    /// Records the seed of a component drawing from a stream of its own instead of one of `SimManager::get_rng`,
+    /// e.g. `RandomDriver::with_seed`, so that it is reported along with the master seed
+    /// in the stats and the coverage report.
+    ///
+    /// Components usually register their seed in `Component::init`.
+    pub fn register_seed(&self, name: &str, seed: u64) -> Result<(), SimError> {
+        if self
+            .component_seeds
+            .lock()?
+            .insert(name.to_string(), seed)
+            .is_none()
+        {
+            self.coverage.lock()?.add_component_seed(seed);
+        }
+        Ok(())
+    }
+
+    /// Returns the random stream of a component, derived from the master seed and `component_id`,
+    /// so that a run is reproducible from its seed alone whatever the other components draw.
+    ///
+    /// Every call starts the stream over, it is meant to be called once per component,
+    /// when building it or in `Component::init`.
+    pub fn get_rng(&self, component_id: ComponentId) -> Result<Rng, SimError> {
+        Ok(Rng::from_stream(self.get_seed()?, component_id))
+    }
+
+    /// Compares every value sent on the registered output port called `port` against the reference model
+    /// of `scoreboard`, see `SimManager::check_scoreboards`
+    pub fn add_scoreboard<T: PartialEq + Debug + Send + 'static>(
+        &self,
+        port: &str,
+        scoreboard: Scoreboard<T>,
+    ) -> Result<(), SimError> {
+        let probe = {
+            let registry = self.registry.lock()?;
+            match registry.get_port(port) {
+                Some(info) if info.direction == PortDirection::Output => {}
+                _ => {
+                    return Err(SimError::RegistryError(format!(
+                        "\"{}\" is not a registered output port",
+                        port
+                    )))
+                }
+            }
+            if registry.get_port_type_id(port) != Some(TypeId::of::<T>()) {
+                return Err(SimError::ScoreboardError(format!(
+                    "port \"{}\" does not carry values of type {}",
+                    port,
+                    std::any::type_name::<T>()
+                )));
+            }
+            registry.get_port_probe(port).unwrap()
+        };
+        let mut scoreboards = self.scoreboards.lock()?;
+        if scoreboards
+            .iter()
+            .any(|other| other.get_name() == scoreboard.get_name())
+        {
+            return Err(SimError::ScoreboardError(format!(
+                "scoreboard \"{}\" already exists",
+                scoreboard.get_name()
+            )));
+        }
+        scoreboard.set_port(port);
+        let monitor = scoreboard.clone();
+        probe.add_monitor(Box::new(move |cycle, value| {
+            if let Some(value) = value.downcast_ref::<T>() {
+                monitor.record(cycle, value);
+            }
+        }))?;
+        scoreboards.push(Box::new(scoreboard));
+        Ok(())
+    }
+
+    /// Checks the scoreboards once the simulation is over,
+    /// returning every mismatch, missing transactions included, as an error so that a test fails
+    pub fn check_scoreboards(&self) -> Result<(), SimError> {
+        let curr_cycle = self.get_curr_cycle();
+        let mismatches: Vec<String> = self
+            .scoreboards
+            .lock()?
+            .iter()
+            .flat_map(|scoreboard| scoreboard.finish(curr_cycle))
+            .map(|mismatch| mismatch.to_string())
+            .collect();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(SimError::ScoreboardError(format!(
+                "{} mismatches\n{}",
+                mismatches.len(),
+                mismatches.join("\n")
+            )))
+        }
+    }
+
+    /// Injects a fault into the events sent on a registered output port, see `FaultCampaign`.
+    ///
+    /// Faults with a probability draw from their own random stream of the master seed, derived from their name,
+    /// see `SimManager::get_seed`.
+    pub fn inject_fault(&self, fault: Fault) -> Result<(), SimError> {
+        let probe = {
+            let registry = self.registry.lock()?;
+            match registry.get_port(&fault.port) {
+                Some(info) if info.direction == PortDirection::Output => {}
+                _ => {
+                    return Err(SimError::RegistryError(format!(
+                        "\"{}\" is not a registered output port",
+                        fault.port
+                    )))
+                }
+            }
+            registry.get_port_probe(&fault.port).unwrap()
+        };
+        fault.validate(&probe.get_bit_names())?;
+        let rng = if fault.probability < 1.0 {
+            Some(Rng::from_stream(
+                self.get_seed()?,
+                get_fault_stream(&fault.name),
+            ))
+        } else {
+            None
+        };
+        let mut faults = self.faults.lock()?;
+        faults.add(fault, rng)?;
+        self.has_faults.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn remove_fault(&self, name: &str) -> Result<(), SimError> {
+        let mut faults = self.faults.lock()?;
+        if faults.remove(name) {
+            self.has_faults.store(!faults.is_empty(), Ordering::Release);
+            Ok(())
+        } else {
+            Err(SimError::FaultError(format!("no fault \"{}\"", name)))
+        }
+    }
+
+    pub fn get_faults(&self) -> Result<Vec<Fault>, SimError> {
+        Ok(self.faults.lock()?.get_faults())
+    }
+
+    /// Returns every event a fault affected so far, to tell faults that were masked from faults never activated
+    pub fn get_fault_injections(&self) -> Result<Vec<FaultInjection>, SimError> {
+        Ok(self.faults.lock()?.get_injections().to_vec())
+    }
+
+    /// Called by the checkers of channels and buses when a rule of their protocol is broken
+    pub fn report_violation(&self, checker: &str, message: String) -> Result<(), SimError> {
+        let cycle = self.get_curr_cycle();
+        self.protocol_violations.lock()?.push(ProtocolViolation {
+            checker: checker.to_string(),
+            cycle,
+            message,
+        });
+        Ok(())
+    }
+
+    pub fn get_protocol_violations(&self) -> Result<Vec<ProtocolViolation>, SimError> {
+        Ok(self.protocol_violations.lock()?.clone())
+    }
+
+    /// Returns every protocol violation so far as an error so that a test fails
+    pub fn check_protocols(&self) -> Result<(), SimError> {
+        let violations = self.protocol_violations.lock()?;
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(SimError::ProtocolError(format!(
+                "{} violations\n{}",
+                violations.len(),
+                violations
+                    .iter()
+                    .map(|violation| violation.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )))
+        }
+    }
+
+    /// Called by `Tx::send` for registered ports, records the injections
+    pub(crate) fn get_active_faults(&self, port: &str) -> Option<ActiveFaults> {
+        if !self.has_faults.load(Ordering::Acquire) {
+            return None;
+        }
+        let curr_cycle = self.get_curr_cycle();
+        self.faults.lock().ok()?.get_active(port, curr_cycle)
+    }
+
+    /// Called by `Tx::send` for registered ports
+    pub(crate) fn notify_port_write<F: FnOnce() -> String>(&self, port: &str, value: F) {
+        let curr_cycle = self.get_curr_cycle();
+        let _ = self
+            .watchpoints
+            .lock()
+            .map(|mut watchpoints| watchpoints.check_write(port, curr_cycle, value));
+    }
+
+    /// Captures the state of the simulation, that is the cycle, the pending events, the stats,
+    /// every registered port and every registered component, see `Component::save_state`.
+    ///
+    /// This can only be done at a cycle boundary, i.e. after `SimManager::run_cycle_end`
+    pub fn checkpoint(&self) -> Result<Checkpoint, SimError> {
+        if !self.can_increase_cycle()? {
+            return Err(SimError::CheckpointError(
+                "not at a cycle boundary".to_string(),
+            ));
+        }
+
+        let (port_handles, component_handles) = {
+            let registry = self.registry.lock()?;
+            (
+                registry.get_port_handles(),
+                registry.get_component_handles(),
+            )
+        };
+
+        let port_handles: HashMap<_, _> = port_handles
+            .into_iter()
+            .map(|port| (port.name.clone(), port))
+            .collect();
+        let mut pending_events = Vec::new();
+        for task in self.event_q.lock()?.iter() {
+            pending_events.push(SerializedEvent::from_task(task, &port_handles)?.ok_or(
+                SimError::CheckpointError(format!(
+                    "event {} is addressed to an unregistered port, see `Component::save_state`",
+                    task.event.get_event_id()
+                )),
+            )?);
+        }
+        pending_events.sort_by_key(|event| (event.scheduled_time, event.event_id));
+
+        let mut ports = BTreeMap::new();
+        for (name, port) in port_handles.iter() {
+            ports.insert(name.clone(), port.probe.save_state()?);
+        }
+
+        let mut components = BTreeMap::new();
+        for (name, handle) in component_handles {
+            if let Some(handle) = handle {
+                components.insert(name, handle.lock()?.save_state()?);
+            }
+        }
+
+        let mut do_not_end: Vec<ComponentId> = self
+            .component_do_not_end_set
+            .lock()?
+            .iter()
+            .copied()
+            .collect();
+        do_not_end.sort();
+
+        Ok(Checkpoint {
+            cycle: self.get_curr_cycle(),
+            next_event_id: *self.next_event_id.lock()?,
+            event_processed: *self.event_processed.lock()?,
+            do_not_end,
+            replay_end: *self.replay_end.lock()?,
+            stats: self.stats.lock()?.get_stats(),
+            pending_events,
+            ports,
+            components,
+        })
+    }
+
+    /// Brings the simulation back to the state captured by `SimManager::checkpoint`.
+    ///
+    /// The netlist must register the same components and ports as the one the checkpoint was taken from,
+    /// and the simulation must be at a cycle boundary, e.g. right after `SimDispatcher::init`
+    pub fn restore(&self, checkpoint: &Checkpoint) -> Result<(), SimError> {
+        if !self.rob.lock()?.is_empty() {
+            return Err(SimError::CheckpointError(
+                "not at a cycle boundary".to_string(),
+            ));
+        }
+
+        let (port_handles, component_handles) = {
+            let registry = self.registry.lock()?;
+            (
+                registry.get_port_handles(),
+                registry.get_component_handles(),
+            )
+        };
+        let ports: HashMap<_, _> = port_handles
+            .into_iter()
+            .map(|port| (port.name.clone(), port))
+            .collect();
+        let components: HashMap<_, _> = component_handles.into_iter().collect();
+        let unknown = |kind: &str, name: &str| {
+            SimError::CheckpointError(format!("unknown {} \"{}\"", kind, name))
+        };
+
+        let mut event_q = BinaryHeap::new();
+        for pending_event in checkpoint.pending_events.iter() {
+            let port = ports
+                .get(&pending_event.port)
+                .ok_or(unknown("port", &pending_event.port))?;
+            let sender = port
+                .input_sender
+                .clone()
+                .ok_or(unknown("input port", &pending_event.port))?;
+            let event = pending_event.build_event(port.probe.as_ref(), pending_event.event_id)?;
+            event_q
+                .push(Task::new(event, sender).with_port(Some(pending_event.port.as_str().into())));
+        }
+
+        for (name, state) in checkpoint.ports.iter() {
+            let port = ports.get(name).ok_or(unknown("port", name))?;
+            port.probe.restore_state(state.clone())?;
+        }
+
+        for (name, state) in checkpoint.components.iter() {
+            let handle = components
+                .get(name)
+                .cloned()
+                .flatten()
+                .ok_or(unknown("component", name))?;
+            handle.lock()?.restore_state(state.clone())?;
+        }
+
+        *self.event_q.lock()? = event_q;
+        *self.curr_cycle.lock()? = checkpoint.cycle;
+        *self.next_event_id.lock()? = checkpoint.next_event_id;
+        *self.event_processed.lock()? = checkpoint.event_processed;
+        *self.component_do_not_end_set.lock()? = checkpoint.do_not_end.iter().copied().collect();
+        *self.replay_end.lock()? = checkpoint.replay_end;
+        self.stats.lock()?.restore(&checkpoint.stats);
+        Ok(())
+    }
+
+    /// Starts writing every event delivered to a registered port to the trace file at `path`,
+    /// see `EventRecorder`. Ports are registered during `SimDispatcher::init`, so this should be called after.
+    pub fn start_recording<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let port_handles = self.registry.lock()?.get_port_handles();
+        let recorder = EventRecorder::new(path, port_handles)?;
+        if let Some(previous) = self.recorder.lock()?.replace(recorder) {
+            previous.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Stops the recording and flushes the trace file
+    pub fn stop_recording(&self) -> Result<(), SimError> {
+        match self.recorder.lock()?.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Schedules the events of `trace` addressed to the input ports of `components`, at their recorded cycle.
+    ///
+    /// This allows testing components in isolation against recorded stimulus,
+    /// the simulation does not end before the last replayed event is delivered.
+    /// Events between two replayed components should not be replayed, the live ones are still sent.
+    ///
+    /// Returns the number of scheduled events
+    pub fn replay(
+        &self,
+        trace: &[SerializedEvent],
+        components: &[&str],
+    ) -> Result<usize, SimError> {
+        let ports: HashMap<_, _> = self
+            .registry
+            .lock()?
+            .get_port_handles()
+            .into_iter()
+            .map(|port| (port.name.clone(), port))
+            .collect();
+
+        let mut replayed: Vec<&SerializedEvent> = trace
+            .iter()
+            .filter(|event| {
+                components.iter().any(|component| {
+                    event
+                        .port
+                        .strip_prefix(component)
+                        .and_then(|port_name| port_name.strip_prefix('.'))
+                        .is_some_and(|port_name| !port_name.contains('.'))
+                })
+            })
+            .collect();
+        replayed.sort_by_key(|event| (event.scheduled_time, event.event_id));
+
+        let mut tasks = Vec::new();
+        for event in replayed.iter() {
+            if event.scheduled_time < self.get_curr_cycle() {
+                return Err(SimError::ReplayError(format!(
+                    "event {} is scheduled in the past",
+                    event.event_id
+                )));
+            }
+            let sender = ports
+                .get(&event.port)
+                .and_then(|port| port.input_sender.clone())
+                .ok_or(SimError::ReplayError(format!(
+                    "\"{}\" is not a registered input port",
+                    event.port
+                )))?;
+            let probe = ports[&event.port].probe.as_ref();
+            tasks.push(
+                Task::new(
+                    event.build_event(probe, self.request_new_event_id())?,
+                    sender,
+                )
+                .with_port(Some(event.port.as_str().into())),
+            );
+        }
+
+        if let Some(last) = replayed.last() {
+            let mut replay_end = self.replay_end.lock()?;
+            *replay_end = (*replay_end).max(last.scheduled_time);
+        }
+        tasks.into_iter().for_each(|task| self.enq_event(task));
+        Ok(replayed.len())
+    }
+
+    /// Takes an in-memory checkpoint every `interval` cycles, keeping the latest `capacity` of them.
+    /// These are what `SimManager::step_back` and `SimManager::goto_cycle` start from.
+    pub fn enable_snapshots(&self, interval: Cycle, capacity: usize) -> Result<(), SimError> {
+        self.snapshots.lock()?.enable(interval, capacity);
+        Ok(())
+    }
+
+    pub fn disable_snapshots(&self) -> Result<(), SimError> {
+        self.snapshots.lock()?.disable();
+        Ok(())
+    }
+
+    /// Returns the cycles at which a snapshot is available
+    pub fn get_snapshot_cycles(&self) -> Result<Vec<Cycle>, SimError> {
+        Ok(self.snapshots.lock()?.get_cycles())
+    }
+
+    fn take_snapshot_if_due(&self) -> Result<(), SimError> {
+        if self.snapshots.lock()?.is_due(self.get_curr_cycle()) {
+            let checkpoint = self.checkpoint()?;
+            let verification = VerificationState {
+                assertions: self.assertions.lock()?.save(),
+                coverage: self.coverage.lock()?.save(),
+                faults: self.faults.lock()?.save(),
+            };
+            self.snapshots.lock()?.insert(checkpoint, verification);
+        }
+        Ok(())
+    }
+
+    /// Goes back `cycles` cycles, see `SimManager::goto_cycle`
+    pub fn step_back(&self, cycles: Cycle) -> Result<(), SimError> {
+        self.goto_cycle(self.get_curr_cycle().saturating_sub(cycles))
+    }
+
+    /// Brings the simulation to the end of `cycle`, i.e. as it is after `SimManager::run_cycle_end`.
+    ///
+    /// Earlier cycles are reached by restoring the latest snapshot before `cycle`, see `SimManager::enable_snapshots`,
+    /// then running forward. Components must implement `Component::save_state` for this to be exact.
+    ///
+    /// The assertions, the covergroups and the faults are brought back along with the snapshot.
+    /// The scoreboards and the trace being recorded cannot be, so they ignore the cycles already simulated
+    /// when these are re-executed. The watchpoint hits of the cycles jumped over are dropped.
+    ///
+    /// This needs the dispatchers to still be running, i.e. the simulation must not have ended.
+    pub fn goto_cycle(&self, cycle: Cycle) -> Result<(), SimError> {
+        if self.sim_can_end() {
+            return Err(SimError::CheckpointError(
+                "the simulation has ended".to_string(),
+            ));
+        }
+        self.run_cycle_end()?;
+        let curr_cycle = self.get_curr_cycle();
+        if cycle < curr_cycle {
+            let (checkpoint, verification) = {
+                let mut snapshots = self.snapshots.lock()?;
+                snapshots.truncate_after(cycle);
+                snapshots.get_latest_before(cycle).cloned()
+            }
+            .ok_or(SimError::CheckpointError(format!(
+                "no snapshot at or before cycle {}",
+                cycle
+            )))?;
+            self.restore(&checkpoint)?;
+            self.assertions.lock()?.restore(&verification.assertions);
+            self.coverage.lock()?.restore(&verification.coverage);
+            self.faults.lock()?.restore(&verification.faults);
+            for scoreboard in self.scoreboards.lock()?.iter() {
+                scoreboard.skip_until(curr_cycle);
+            }
+            if let Some(recorder) = self.recorder.lock()?.as_mut() {
+                recorder.skip_until(curr_cycle);
+            }
+        }
+        while self.get_curr_cycle() < cycle {
+            self.run_cycle()?;
+            self.run_cycle_end()?;
         }
+        self.watchpoints.lock()?.take_hits();
         Ok(())
     }
 
@@ -0,0 +1,235 @@
+use crate::error::SimError;
+use crate::types::Cycle;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A named statistic owned by a component, see `SimManager::register_counter`.
+///
+/// Clones share the same value.
+#[derive(Clone, Debug, Default)]
+pub struct Counter {
+    value: Arc<Mutex<f64>>,
+}
+
+impl Counter {
+    pub fn increment(&self) {
+        self.add(1.0);
+    }
+
+    pub fn add(&self, value: f64) {
+        *self.value.lock().unwrap() += value;
+    }
+
+    /// Overrides the value, e.g. for ratios such as IPC
+    pub fn set(&self, value: f64) {
+        *self.value.lock().unwrap() = value;
+    }
+
+    pub fn get(&self) -> f64 {
+        *self.value.lock().unwrap()
+    }
+}
+
+/// A snapshot of the statistics of a simulation, see `SimManager::get_stats`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimStats {
+    pub cycles: Cycle,
+    /// Events put in the event queue, clock ticks included
+    pub events_scheduled: u64,
+    /// Events sent to their port
+    pub events_delivered: u64,
+    /// Events acknowledged by their port
+    pub events_processed: u64,
+    /// Number of cycles per number of events delivered in the cycle
+    pub events_per_cycle: BTreeMap<u64, u64>,
+    /// Number of cycles per number of delta iterations in the cycle,
+    /// a delta iteration being a round of event delivery within a cycle
+    pub delta_iterations_per_cycle: BTreeMap<u64, u64>,
+    pub peak_event_q_depth: usize,
+    pub peak_rob_size: usize,
+    /// Wall-clock time in seconds from the first to the last cycle
+    pub wall_time: f64,
+    pub cycles_per_second: f64,
+    /// Counters registered by the components
+    pub counters: BTreeMap<String, f64>,
+    /// The master seed, if the simulation drew random numbers, see `SimManager::get_seed`
+    pub seed: Option<u64>,
+    /// The seeds of the components drawing from a stream of their own, by name, see `SimManager::register_seed`
+    #[serde(default)]
+    pub component_seeds: BTreeMap<String, u64>,
+}
+
+/// Mean of a histogram mapping values to their number of occurrences
+fn get_mean(histogram: &BTreeMap<u64, u64>) -> f64 {
+    let count: u64 = histogram.values().sum();
+    if count == 0 {
+        return 0.0;
+    }
+    let sum: u64 = histogram.iter().map(|(value, count)| value * count).sum();
+    sum as f64 / count as f64
+}
+
+impl SimStats {
+    pub fn get_mean_events_per_cycle(&self) -> f64 {
+        get_mean(&self.events_per_cycle)
+    }
+
+    pub fn get_mean_delta_iterations(&self) -> f64 {
+        get_mean(&self.delta_iterations_per_cycle)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Display for SimStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed: {}", seed)?;
+        }
+        for (name, seed) in self.component_seeds.iter() {
+            writeln!(f, "seed of {}: {}", name, seed)?;
+        }
+        writeln!(f, "cycles: {}", self.cycles)?;
+        writeln!(f, "events scheduled: {}", self.events_scheduled)?;
+        writeln!(f, "events delivered: {}", self.events_delivered)?;
+        writeln!(f, "events processed: {}", self.events_processed)?;
+        writeln!(
+            f,
+            "events per cycle: {:.2} mean, {} max",
+            self.get_mean_events_per_cycle(),
+            self.events_per_cycle.keys().last().unwrap_or(&0)
+        )?;
+        writeln!(
+            f,
+            "delta iterations per cycle: {:.2} mean, {} max",
+            self.get_mean_delta_iterations(),
+            self.delta_iterations_per_cycle.keys().last().unwrap_or(&0)
+        )?;
+        writeln!(f, "peak event queue depth: {}", self.peak_event_q_depth)?;
+        writeln!(f, "peak rob size: {}", self.peak_rob_size)?;
+        writeln!(
+            f,
+            "speed: {:.2} cycles/s over {:.3} s",
+            self.cycles_per_second, self.wall_time
+        )?;
+        for (name, value) in self.counters.iter() {
+            writeln!(f, "{}: {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects `SimStats` as the simulation manager schedules and delivers events
+#[derive(Debug, Default)]
+pub struct StatsCollector {
+    stats: SimStats,
+    /// Events delivered and delta iterations in the current cycle
+    curr_cycle_events: u64,
+    curr_cycle_deltas: u64,
+    /// Wall-clock time of the first and the last cycle
+    start: Option<Instant>,
+    end: Option<Instant>,
+    counters: BTreeMap<String, Counter>,
+}
+
+impl StatsCollector {
+    pub fn record_scheduled(&mut self, event_q_depth: usize) {
+        self.stats.events_scheduled += 1;
+        self.stats.peak_event_q_depth = self.stats.peak_event_q_depth.max(event_q_depth);
+    }
+
+    /// Records a delta iteration delivering `events` events
+    pub fn record_delivered(&mut self, events: u64, rob_size: usize) {
+        self.stats.events_delivered += events;
+        self.stats.peak_rob_size = self.stats.peak_rob_size.max(rob_size);
+        self.curr_cycle_events += events;
+        self.curr_cycle_deltas += 1;
+    }
+
+    pub fn record_processed(&mut self) {
+        self.stats.events_processed += 1;
+    }
+
+    /// Starts the wall-clock time on the first call
+    pub fn record_start(&mut self) {
+        self.start.get_or_insert_with(Instant::now);
+    }
+
+    /// Closes the histograms of the current cycle
+    pub fn record_cycle_end(&mut self) {
+        self.end = Some(Instant::now());
+        self.stats.cycles += 1;
+        *self
+            .stats
+            .events_per_cycle
+            .entry(self.curr_cycle_events)
+            .or_insert(0) += 1;
+        *self
+            .stats
+            .delta_iterations_per_cycle
+            .entry(self.curr_cycle_deltas)
+            .or_insert(0) += 1;
+        self.curr_cycle_events = 0;
+        self.curr_cycle_deltas = 0;
+    }
+
+    pub fn register_counter(&mut self, name: &str) -> Option<Counter> {
+        if self.counters.contains_key(name) {
+            return None;
+        }
+        let counter = Counter::default();
+        self.counters.insert(name.to_string(), counter.clone());
+        Some(counter)
+    }
+
+    /// Brings the statistics and the registered counters back to `stats`, as saved in a checkpoint.
+    /// The wall-clock time keeps running.
+    pub fn restore(&mut self, stats: &SimStats) {
+        self.stats = SimStats {
+            wall_time: 0.0,
+            cycles_per_second: 0.0,
+            counters: BTreeMap::new(),
+            ..stats.clone()
+        };
+        self.curr_cycle_events = 0;
+        self.curr_cycle_deltas = 0;
+        for (name, value) in stats.counters.iter() {
+            if let Some(counter) = self.counters.get(name) {
+                counter.set(*value);
+            }
+        }
+    }
+
+    pub fn get_stats(&self) -> SimStats {
+        let wall_time = match (self.start, self.end) {
+            (Some(start), Some(end)) => end.duration_since(start),
+            _ => Duration::ZERO,
+        }
+        .as_secs_f64();
+        SimStats {
+            wall_time,
+            cycles_per_second: if wall_time > 0.0 {
+                self.stats.cycles as f64 / wall_time
+            } else {
+                0.0
+            },
+            counters: self
+                .counters
+                .iter()
+                .map(|(name, counter)| (name.clone(), counter.get()))
+                .collect(),
+            ..self.stats.clone()
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::error::SimError;
+use crate::stats::SimStats;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// The parameters of a single simulation of a sweep, by name
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SweepPoint {
+    pub parameters: BTreeMap<String, Value>,
+}
+
+impl SweepPoint {
+    pub fn get(&self, name: &str) -> Result<&Value, SimError> {
+        self.parameters
+            .get(name)
+            .ok_or(SimError::SweepError(format!("no parameter \"{}\"", name)))
+    }
+
+    pub fn get_u64(&self, name: &str) -> Result<u64, SimError> {
+        self.get(name)?.as_u64().ok_or(SimError::SweepError(format!(
+            "parameter \"{}\" is not an unsigned integer",
+            name
+        )))
+    }
+
+    pub fn get_f64(&self, name: &str) -> Result<f64, SimError> {
+        self.get(name)?.as_f64().ok_or(SimError::SweepError(format!(
+            "parameter \"{}\" is not a number",
+            name
+        )))
+    }
+
+    pub fn get_bool(&self, name: &str) -> Result<bool, SimError> {
+        self.get(name)?
+            .as_bool()
+            .ok_or(SimError::SweepError(format!(
+                "parameter \"{}\" is not a boolean",
+                name
+            )))
+    }
+
+    pub fn get_str(&self, name: &str) -> Result<&str, SimError> {
+        self.get(name)?.as_str().ok_or(SimError::SweepError(format!(
+            "parameter \"{}\" is not a string",
+            name
+        )))
+    }
+}
+
+/// The values of every parameter, a sweep simulating every combination of them
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterGrid {
+    pub parameters: BTreeMap<String, Vec<Value>>,
+}
+
+impl ParameterGrid {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_parameter<V: Into<Value>>(mut self, name: &str, values: Vec<V>) -> Self {
+        self.parameters.insert(
+            name.to_string(),
+            values.into_iter().map(|value| value.into()).collect(),
+        );
+        self
+    }
+
+    /// Every combination of the values, the last parameter by name varying the fastest
+    pub fn get_points(&self) -> Vec<SweepPoint> {
+        let mut points = vec![SweepPoint::default()];
+        for (name, values) in self.parameters.iter() {
+            points = points
+                .into_iter()
+                .flat_map(|point| {
+                    values.iter().map(move |value| {
+                        let mut point = point.clone();
+                        point.parameters.insert(name.clone(), value.clone());
+                        point
+                    })
+                })
+                .collect();
+        }
+        points
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SweepResult {
+    pub point: SweepPoint,
+    pub stats: Option<SimStats>,
+    /// Why the simulation failed, if it did
+    pub error: Option<String>,
+}
+
+/// The results of a sweep, in the order of `ParameterGrid::get_points`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SweepReport {
+    pub results: Vec<SweepResult>,
+}
+
+/// Runs a simulation for every point of a `ParameterGrid`, in parallel
+#[derive(Clone, Debug)]
+pub struct Sweep {
+    grid: ParameterGrid,
+    num_threads: usize,
+}
+
+/// The message of a panic, as printed by the default panic hook
+fn get_panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+impl Sweep {
+    /// Runs as many simulations at once as there are cores, see `Sweep::with_threads`
+    pub fn new(grid: ParameterGrid) -> Self {
+        Sweep {
+            grid,
+            num_threads: thread::available_parallelism().map_or(1, |cores| cores.get()),
+        }
+    }
+
+    /// Runs up to `num_threads` simulations at once.
+    ///
+    /// Every simulation busy-waits in its own dispatcher threads besides the thread running it,
+    /// so `num_threads` times the dispatchers of a simulation should not exceed the cores,
+    /// e.g. the cores divided by the dispatchers for netlists with several of them.
+    pub fn with_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = num_threads.max(1);
+        self
+    }
+
+    /// `simulate` builds a fresh `SimManager` and netlist for a point, runs it and returns its stats,
+    /// see `SimManager::get_stats`. Counters registered by the components end up in the report.
+    ///
+    /// A failed simulation does not stop the sweep, its error is kept in the report, as is the message of a panic.
+    pub fn run<F: Fn(&SweepPoint) -> Result<SimStats, SimError> + Sync>(
+        &self,
+        simulate: F,
+    ) -> SweepReport {
+        let points = self.grid.get_points();
+        let results: Mutex<Vec<Option<SweepResult>>> = Mutex::new(vec![None; points.len()]);
+        let next_point = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..self.num_threads.min(points.len()) {
+                scope.spawn(|| loop {
+                    let index = next_point.fetch_add(1, Ordering::Relaxed);
+                    let Some(point) = points.get(index) else {
+                        break;
+                    };
+                    let (stats, error) = match catch_unwind(AssertUnwindSafe(|| simulate(point))) {
+                        Ok(Ok(stats)) => (Some(stats), None),
+                        Ok(Err(e)) => (None, Some(e.to_string())),
+                        Err(payload) => (
+                            None,
+                            Some(format!("panic: {}", get_panic_message(payload.as_ref()))),
+                        ),
+                    };
+                    results.lock().unwrap()[index] = Some(SweepResult {
+                        point: point.clone(),
+                        stats,
+                        error,
+                    });
+                });
+            }
+        });
+        SweepReport {
+            results: results
+                .into_inner()
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .collect(),
+        }
+    }
+}
+
+/// Quotes a CSV field if needed
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl SweepReport {
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes a row per simulation: the parameters, the stats, every counter, then the error if any
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), SimError> {
+        let parameters: BTreeSet<&String> = self
+            .results
+            .iter()
+            .flat_map(|result| result.point.parameters.keys())
+            .collect();
+        let counters: BTreeSet<&String> = self
+            .results
+            .iter()
+            .filter_map(|result| result.stats.as_ref())
+            .flat_map(|stats| stats.counters.keys())
+            .collect();
+        let stats_columns = [
+            "cycles",
+            "events_scheduled",
+            "events_delivered",
+            "events_processed",
+            "mean_events_per_cycle",
+            "mean_delta_iterations",
+            "peak_event_q_depth",
+            "peak_rob_size",
+            "wall_time",
+            "cycles_per_second",
+            "seed",
+        ];
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        let header: Vec<String> = parameters
+            .iter()
+            .map(|name| name.to_string())
+            .chain(stats_columns.iter().map(|name| name.to_string()))
+            .chain(counters.iter().map(|name| name.to_string()))
+            .chain(["error".to_string()])
+            .collect();
+        writeln!(
+            writer,
+            "{}",
+            header
+                .iter()
+                .map(|field| escape_csv(field))
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+
+        for result in self.results.iter() {
+            let mut row: Vec<String> = parameters
+                .iter()
+                .map(|name| match result.point.parameters.get(*name) {
+                    Some(Value::String(value)) => value.clone(),
+                    Some(value) => value.to_string(),
+                    None => String::new(),
+                })
+                .collect();
+            match result.stats.as_ref() {
+                Some(stats) => {
+                    row.extend([
+                        stats.cycles.to_string(),
+                        stats.events_scheduled.to_string(),
+                        stats.events_delivered.to_string(),
+                        stats.events_processed.to_string(),
+                        stats.get_mean_events_per_cycle().to_string(),
+                        stats.get_mean_delta_iterations().to_string(),
+                        stats.peak_event_q_depth.to_string(),
+                        stats.peak_rob_size.to_string(),
+                        stats.wall_time.to_string(),
+                        stats.cycles_per_second.to_string(),
+                        stats.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+                    ]);
+                    row.extend(counters.iter().map(|name| {
+                        stats
+                            .counters
+                            .get(*name)
+                            .map(|value| value.to_string())
+                            .unwrap_or_default()
+                    }));
+                }
+                None => row.extend(vec![String::new(); stats_columns.len() + counters.len()]),
+            }
+            row.push(result.error.clone().unwrap_or_default());
+            writeln!(
+                writer,
+                "{}",
+                row.iter()
+                    .map(|field| escape_csv(field))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use crate::event::Event;
 use crate::types::Output;
 use std::cmp::Ordering;
+use std::sync::Arc;
 
 /// A `Task` encapsulates an `Event` along with a callback channel
 ///
@@ -9,6 +10,8 @@ use std::cmp::Ordering;
 pub struct Task {
     pub event: Box<dyn Event>,
     pub event_callback: Output,
+    /// The name of the registered input port the event is addressed to, if any
+    pub port: Option<Arc<str>>,
 }
 
 impl Task {
@@ -16,8 +19,14 @@ impl Task {
         Task {
             event,
             event_callback,
+            port: None,
         }
     }
+
+    pub(crate) fn with_port(mut self, port: Option<Arc<str>>) -> Task {
+        self.port = port;
+        self
+    }
 }
 
 impl PartialEq for Task {
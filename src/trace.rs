@@ -0,0 +1,168 @@
+use crate::error::SimError;
+use crate::event::Event;
+use crate::probe::{ProbeCommand, ProbeEvent};
+use crate::registry::{PortHandle, PortProbe};
+use crate::task::Task;
+use crate::types::{Cycle, EventId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// The payload of a serialized event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EventPayload {
+    Value(Value),
+    Force(Value),
+    Release,
+}
+
+/// A `SerializedEvent` is an event addressed to a registered port, in a form that can be written to a file.
+/// It is what checkpoints keep of the event q and what traces are made of.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedEvent {
+    /// Name of the destination port
+    pub port: String,
+    pub event_id: EventId,
+    pub scheduled_time: Cycle,
+    pub payload: EventPayload,
+}
+
+impl SerializedEvent {
+    /// Serializes the event of `task`, `None` if it is not addressed to one of `ports`, e.g. clock events
+    pub(crate) fn from_task(
+        task: &Task,
+        ports: &HashMap<String, PortHandle>,
+    ) -> Result<Option<SerializedEvent>, SimError> {
+        let Some(port) = task.port.as_ref().and_then(|name| ports.get(name.as_ref())) else {
+            return Ok(None);
+        };
+        let data = task.event.get_data_as_any();
+        let payload = match data.downcast_ref::<ProbeCommand>() {
+            Some(ProbeCommand::Force(value)) => {
+                EventPayload::Force(port.probe.serialize_data(value.as_ref())?)
+            }
+            Some(ProbeCommand::Release) => EventPayload::Release,
+            None => EventPayload::Value(port.probe.serialize_data(data.as_ref())?),
+        };
+        Ok(Some(SerializedEvent {
+            port: port.name.clone(),
+            event_id: task.event.get_event_id(),
+            scheduled_time: task.event.get_scheduled_time(),
+            payload,
+        }))
+    }
+
+    /// Rebuilds the event for the port behind `probe`, numbered `event_id`
+    pub(crate) fn build_event(
+        &self,
+        probe: &dyn PortProbe,
+        event_id: EventId,
+    ) -> Result<Box<dyn Event>, SimError> {
+        Ok(match self.payload.clone() {
+            EventPayload::Value(value) => {
+                probe.build_event(value, event_id, self.scheduled_time)?
+            }
+            EventPayload::Force(value) => Box::new(ProbeEvent::new(
+                self.scheduled_time,
+                ProbeCommand::Force(probe.deserialize_data(value)?),
+                event_id,
+            )),
+            EventPayload::Release => Box::new(ProbeEvent::new(
+                self.scheduled_time,
+                ProbeCommand::Release,
+                event_id,
+            )),
+        })
+    }
+}
+
+/// An `EventRecorder` writes every event delivered to a registered port to a trace file,
+/// one JSON encoded `SerializedEvent` per line, see `SimManager::start_recording`
+pub struct EventRecorder {
+    writer: BufWriter<File>,
+    /// The registered ports by name
+    ports: HashMap<String, PortHandle>,
+    error: Option<SimError>,
+    /// The events up to this cycle were written before `SimManager::goto_cycle` went back
+    skip_until: Option<Cycle>,
+}
+
+impl Debug for EventRecorder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventRecorder")
+            .field("ports", &self.ports.len())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl EventRecorder {
+    pub(crate) fn new<P: AsRef<Path>>(
+        path: P,
+        ports: Vec<PortHandle>,
+    ) -> Result<EventRecorder, SimError> {
+        Ok(EventRecorder {
+            writer: BufWriter::new(File::create(path)?),
+            ports: ports
+                .into_iter()
+                .map(|port| (port.name.clone(), port))
+                .collect(),
+            error: None,
+            skip_until: None,
+        })
+    }
+
+    /// Records the event of `task`, the first error is kept for `EventRecorder::finish`
+    pub(crate) fn record(&mut self, task: &Task) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(e) = self.try_record(task) {
+            self.error = Some(e);
+        }
+    }
+
+    /// Stops writing the events up to `cycle` again, they are re-executed by `SimManager::goto_cycle`
+    pub(crate) fn skip_until(&mut self, cycle: Cycle) {
+        self.skip_until = self.skip_until.max(Some(cycle));
+    }
+
+    fn try_record(&mut self, task: &Task) -> Result<(), SimError> {
+        let scheduled_time = task.event.get_scheduled_time();
+        if self
+            .skip_until
+            .is_some_and(|skip_until| scheduled_time <= skip_until)
+        {
+            return Ok(());
+        }
+        if let Some(event) = SerializedEvent::from_task(task, &self.ports)? {
+            serde_json::to_writer(&mut self.writer, &event)?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> Result<(), SimError> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads a trace written by an `EventRecorder`
+pub fn load_trace<P: AsRef<Path>>(path: P) -> Result<Vec<SerializedEvent>, SimError> {
+    let mut trace = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.is_empty() {
+            trace.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(trace)
+}
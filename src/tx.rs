@@ -1,17 +1,46 @@
-use crate::event::Event;
+use crate::activity::{get_bits, ActivityCounter, PortActivity};
+use crate::error::SimError;
 use crate::event::EventValue;
+use crate::event::{Event, ValueEvent};
+use crate::fault::{apply_bit_faults, BitFault};
+use crate::profile;
+use crate::registry::{
+    deserialize_data, serialize_data, Port, PortDirection, PortMonitor, PortName, PortProbe,
+};
 use crate::rx::Rx;
 use crate::sim_manager::SimManager;
 use crate::task::Task;
-use crate::types::{Cycle, EventId};
+use crate::types::{Cycle, EventId, Output};
 use crossbeam_channel::{unbounded, Sender};
-use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// What a `Tx` needs once registered, the functions only exist for the types the registry accepts
+struct RegisteredTx<T> {
+    name: String,
+    /// Formats values for watchpoints
+    format_value: fn(&T) -> String,
+    apply_bit_faults: fn(&T, &[BitFault]) -> Option<T>,
+}
+
+/// The part of a `Tx` shared with the registry
+pub(crate) struct TxState<T> {
+    value: T,
+    registered: Option<RegisteredTx<T>>,
+    activity: Option<ActivityCounter<T>>,
+    monitors: Vec<PortMonitor>,
+}
 
 pub struct Tx<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static + EventValue> {
     sim_manager: Arc<SimManager>,
-    senders: Vec<Sender<Box<dyn Event>>>,
+    /// The channel of every `Rx`, with the name it is registered under
+    senders: Vec<(Sender<Box<dyn Event>>, PortName)>,
     ack_sender: Sender<EventId>,
-    value: T,
+    state: Arc<Mutex<TxState<T>>>,
 }
 
 impl<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static + EventValue> Tx<T> {
@@ -20,29 +49,173 @@ impl<T: Default + Clone + Copy + Sync + Send + PartialEq + 'static + EventValue>
             sim_manager,
             senders: Vec::new(),
             ack_sender,
-            value: T::default(),
+            state: Arc::new(Mutex::new(TxState {
+                value: T::default(),
+                registered: None,
+                activity: None,
+                monitors: Vec::new(),
+            })),
         }
     }
 
     pub fn send(&mut self, value: T, delay: Cycle) {
-        self.value = value;
-
         let curr_cycle = self.sim_manager.get_curr_cycle();
-        for sender in self.senders.iter() {
+        let mut value = value;
+        let mut delay = delay;
+        {
+            let mut state = self.state.lock().unwrap();
+            if let Some(registered) = state.registered.as_ref() {
+                if let Some(faults) = self.sim_manager.get_active_faults(&registered.name) {
+                    if faults.drop {
+                        return;
+                    }
+                    if !faults.bits.is_empty() {
+                        if let Some(faulty) = (registered.apply_bit_faults)(&value, &faults.bits) {
+                            value = faulty;
+                        }
+                    }
+                    delay += faults.delay;
+                }
+            }
+            let previous = state.value;
+            state.value = value;
+            if let Some(activity) = state.activity.as_mut() {
+                activity.record(&previous, &value, value != previous);
+            }
+            if let Some(registered) = state.registered.as_ref() {
+                self.sim_manager
+                    .notify_port_write(&registered.name, || (registered.format_value)(&value));
+            }
+            for monitor in state.monitors.iter_mut() {
+                monitor(curr_cycle, &value);
+            }
+        }
+
+        profile::count_sent(self.senders.len());
+        for (sender, name) in self.senders.iter() {
             let event_id = self.sim_manager.request_new_event_id();
             let event = value.build_event(event_id, curr_cycle + delay);
-            self.sim_manager.enq_event(Task::new(event, sender.clone()))
+            self.sim_manager
+                .enq_event(Task::new(event, sender.clone()).with_port(name.get().cloned()))
         }
     }
 
     pub fn add_rx(&mut self) -> Rx<T> {
         let (sender, receiver) = unbounded();
-        let rx = Rx::<T>::new(receiver, self.ack_sender.clone());
-        self.senders.push(sender);
+        let mut rx = Rx::<T>::new(receiver, self.ack_sender.clone());
+        rx.set_sender(sender.clone());
+        self.senders.push((sender, rx.get_name()));
         rx
     }
 
     pub fn get_value(&self) -> T {
-        self.value
+        self.state.lock().unwrap().value
+    }
+}
+
+impl<
+        T: Default
+            + Clone
+            + Copy
+            + Sync
+            + Send
+            + PartialEq
+            + Debug
+            + Serialize
+            + DeserializeOwned
+            + 'static
+            + EventValue,
+    > Port for Tx<T>
+{
+    fn get_direction(&self) -> PortDirection {
+        PortDirection::Output
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn get_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn get_probe(&self) -> Arc<dyn PortProbe> {
+        self.state.clone()
+    }
+
+    fn get_input_sender(&self) -> Option<Output> {
+        None
+    }
+
+    fn set_registered_name(&self, name: &str) {
+        self.state.lock().unwrap().registered = Some(RegisteredTx {
+            name: name.to_string(),
+            format_value: |value| format!("{:?}", value),
+            apply_bit_faults: apply_bit_faults::<T>,
+        });
+    }
+}
+
+impl<T: Copy + Sync + Send + Debug + Serialize + DeserializeOwned + 'static> PortProbe
+    for Mutex<TxState<T>>
+{
+    fn get_value_as_any(&self) -> Box<dyn Any> {
+        Box::new(self.lock().unwrap().value)
+    }
+
+    fn get_value_as_string(&self) -> String {
+        format!("{:?}", self.lock().unwrap().value)
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(serde_json::to_value(self.lock()?.value)?)
+    }
+
+    fn restore_state(&self, state: Value) -> Result<(), SimError> {
+        self.lock()?.value = serde_json::from_value(state)?;
+        Ok(())
+    }
+
+    fn serialize_data(&self, data: &dyn Any) -> Result<Value, SimError> {
+        serialize_data::<T>(data)
+    }
+
+    fn deserialize_data(&self, data: Value) -> Result<Arc<dyn Any + Send + Sync>, SimError> {
+        deserialize_data::<T>(data)
+    }
+
+    fn build_event(
+        &self,
+        data: Value,
+        event_id: EventId,
+        scheduled_time: Cycle,
+    ) -> Result<Box<dyn Event>, SimError> {
+        let value: T = serde_json::from_value(data)?;
+        Ok(Box::new(ValueEvent::new(scheduled_time, value, event_id)))
+    }
+
+    fn enable_activity(&self) {
+        let mut state = self.lock().unwrap();
+        state.activity = Some(ActivityCounter::new(get_bits::<T>, &state.value));
+    }
+
+    fn get_activity(&self) -> Option<PortActivity> {
+        self.lock()
+            .unwrap()
+            .activity
+            .as_ref()
+            .map(|activity| activity.get_activity())
+    }
+
+    fn get_bit_names(&self) -> Vec<String> {
+        get_bits(&self.lock().unwrap().value)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    fn add_monitor(&self, monitor: PortMonitor) -> Result<(), SimError> {
+        self.lock()?.monitors.push(monitor);
+        Ok(())
     }
 }
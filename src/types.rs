@@ -1,8 +1,11 @@
+use crate::component::Component;
 use crate::event::Event;
 use crossbeam_channel::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
 
 pub type ComponentId = u64;
 pub type EventId = u128;
 pub type Cycle = u128;
 pub type Input = Receiver<Box<dyn Event>>;
 pub type Output = Sender<Box<dyn Event>>;
+pub type ComponentHandle = Arc<Mutex<dyn Component>>;
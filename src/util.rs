@@ -0,0 +1,20 @@
+/// Parses decimal, `0x` hexadecimal and `0b` binary integers, `true` and `false` count as 1 and 0
+pub(crate) fn parse_integer(value: &str) -> Option<i128> {
+    let value = value.trim().replace('_', "");
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value.to_string()),
+        None => (false, value),
+    };
+    let parsed = if let Some(hex) = value.strip_prefix("0x") {
+        i128::from_str_radix(hex, 16).ok()
+    } else if let Some(bin) = value.strip_prefix("0b") {
+        i128::from_str_radix(bin, 2).ok()
+    } else {
+        match value.as_str() {
+            "true" => Some(1),
+            "false" => Some(0),
+            _ => value.parse::<i128>().ok(),
+        }
+    }?;
+    Some(if negative { -parsed } else { parsed })
+}
@@ -0,0 +1,195 @@
+use crate::assertion::AssertionFailure;
+use crate::types::Cycle;
+use crate::util::parse_integer;
+use std::fmt::{Display, Formatter};
+
+pub type WatchpointId = usize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    pub fn parse(op: &str) -> Option<CompareOp> {
+        match op {
+            "==" => Some(CompareOp::Eq),
+            "!=" => Some(CompareOp::Ne),
+            "<" => Some(CompareOp::Lt),
+            "<=" => Some(CompareOp::Le),
+            ">" => Some(CompareOp::Gt),
+            ">=" => Some(CompareOp::Ge),
+            _ => None,
+        }
+    }
+
+    /// Compares numerically when both sides are integers, otherwise only `==` and `!=` hold
+    pub fn holds(&self, lhs: &str, rhs: &str) -> bool {
+        match (parse_integer(lhs), parse_integer(rhs)) {
+            (Some(lhs), Some(rhs)) => match self {
+                CompareOp::Eq => lhs == rhs,
+                CompareOp::Ne => lhs != rhs,
+                CompareOp::Lt => lhs < rhs,
+                CompareOp::Le => lhs <= rhs,
+                CompareOp::Gt => lhs > rhs,
+                CompareOp::Ge => lhs >= rhs,
+            },
+            _ => match self {
+                CompareOp::Eq => lhs == rhs,
+                CompareOp::Ne => lhs != rhs,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Display for CompareOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WatchCondition {
+    /// Triggers on every `Tx::send` to the port
+    AnyWrite,
+    /// Triggers on every `Tx::send` of a value comparing to the given one,
+    /// values are compared through their `Debug` representation, numerically when both are integers
+    Compare(CompareOp, String),
+}
+
+impl Display for WatchCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchCondition::AnyWrite => write!(f, "any write"),
+            WatchCondition::Compare(op, value) => write!(f, "{} {}", op, value),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Watchpoint {
+    pub id: WatchpointId,
+    /// Name of the watched output port
+    pub port: String,
+    pub condition: WatchCondition,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchpointHit {
+    pub id: WatchpointId,
+    pub port: String,
+    pub cycle: Cycle,
+    /// The written value, formatted with `Debug`
+    pub value: String,
+}
+
+/// Why `SimManager::run` returned
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// Every component agreed the simulation can end, see `SimManager::sim_can_end`
+    SimEnd,
+    /// Watchpoints triggered during the last cycle
+    Watchpoint(Vec<WatchpointHit>),
+    /// Assertions with `stop_on_failure` failed at the end of the previous cycle
+    AssertionFailed(Vec<AssertionFailure>),
+}
+
+/// The watchpoints of a simulation along with the hits not yet reported
+#[derive(Debug, Default)]
+pub struct WatchpointList {
+    watchpoints: Vec<Watchpoint>,
+    next_id: WatchpointId,
+    hits: Vec<WatchpointHit>,
+}
+
+impl WatchpointList {
+    pub fn add(&mut self, port: &str, condition: WatchCondition) -> WatchpointId {
+        self.next_id += 1;
+        self.watchpoints.push(Watchpoint {
+            id: self.next_id,
+            port: port.to_string(),
+            condition,
+            enabled: true,
+        });
+        self.next_id
+    }
+
+    /// Returns whether the watchpoint existed
+    pub fn remove(&mut self, id: WatchpointId) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|watchpoint| watchpoint.id != id);
+        len != self.watchpoints.len()
+    }
+
+    /// Returns whether the watchpoint exists
+    pub fn set_enabled(&mut self, id: WatchpointId, enabled: bool) -> bool {
+        self.watchpoints
+            .iter_mut()
+            .find(|watchpoint| watchpoint.id == id)
+            .map(|watchpoint| watchpoint.enabled = enabled)
+            .is_some()
+    }
+
+    pub fn get_watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    pub fn has_enabled(&self) -> bool {
+        self.watchpoints.iter().any(|watchpoint| watchpoint.enabled)
+    }
+
+    /// Checks a write of the port against the watchpoints, `value` is only formatted if needed
+    pub fn check_write<F: FnOnce() -> String>(&mut self, port: &str, cycle: Cycle, value: F) {
+        let mut watchpoints = self
+            .watchpoints
+            .iter()
+            .filter(|watchpoint| watchpoint.enabled && watchpoint.port == port)
+            .peekable();
+        if watchpoints.peek().is_none() {
+            return;
+        }
+        let value = value();
+        let hits: Vec<WatchpointHit> = watchpoints
+            .filter(|watchpoint| match &watchpoint.condition {
+                WatchCondition::AnyWrite => true,
+                WatchCondition::Compare(op, expected) => op.holds(&value, expected),
+            })
+            .map(|watchpoint| WatchpointHit {
+                id: watchpoint.id,
+                port: port.to_string(),
+                cycle,
+                value: value.clone(),
+            })
+            .collect();
+        for hit in hits {
+            // a port may be written several times in a cycle, only its last value is reported
+            match self
+                .hits
+                .iter_mut()
+                .find(|other| other.id == hit.id && other.cycle == hit.cycle)
+            {
+                Some(other) => other.value = hit.value,
+                None => self.hits.push(hit),
+            }
+        }
+    }
+
+    pub fn take_hits(&mut self) -> Vec<WatchpointHit> {
+        std::mem::take(&mut self.hits)
+    }
+}
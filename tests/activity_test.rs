@@ -0,0 +1,96 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn activity_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        20,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    assert!(sim_manager.get_activity_report().is_err());
+    sim_manager.enable_activity_stats().unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+
+    let report = sim_manager.get_activity_report().unwrap();
+    assert_eq!(report.cycles, sim_manager.get_curr_cycle());
+
+    // the first packet has the default value, it is sent but changes nothing
+    let sent = &report.ports["counter.output"];
+    assert_eq!(sent.events, 20);
+    assert_eq!(sent.changes, 19);
+    let received = &report.ports["receiver.input"];
+    assert_eq!(received.events, 20);
+    assert_eq!(received.changes, 19);
+
+    // `is_last` and the 64 bits of `packet_id`
+    assert_eq!(sent.bits.len(), 65);
+    let bit = |name: &str| sent.bits.iter().find(|bit| bit.name == name).unwrap();
+    assert_eq!((bit("is_last").rises, bit("is_last").falls), (1, 0));
+    assert_eq!(
+        (bit("packet_id[0]").rises, bit("packet_id[0]").falls),
+        (10, 9)
+    );
+    assert_eq!(
+        (bit("packet_id[4]").rises, bit("packet_id[4]").falls),
+        (1, 0)
+    );
+    assert_eq!(
+        (bit("packet_id[5]").rises, bit("packet_id[5]").falls),
+        (0, 0)
+    );
+    // bits 0 to 3 both rise and fall
+    assert_eq!(sent.get_toggled_bits(), 4);
+    assert_eq!(report.ports.len(), 4);
+    assert_eq!(report.get_toggles(), 4 * sent.get_toggles());
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}
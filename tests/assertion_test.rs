@@ -0,0 +1,204 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::assertion::{Predicate, Property};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::watchpoint::{CompareOp, StopReason};
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+fn packet(port: &str, packet_id: u128, is_last: bool) -> Predicate {
+    Predicate::compare(
+        port,
+        CompareOp::Eq,
+        &format!(
+            "SimpleData {{ packet_id: {}, is_last: {} }}",
+            packet_id, is_last
+        ),
+    )
+}
+
+/// Starts the counter, link and receiver, the counter sending 20 packets
+fn start_netlist() -> (Arc<SimManager>, Vec<JoinHandle<()>>) {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        20,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+    (sim_manager, thread_handlers)
+}
+
+#[test]
+fn assertion_test() {
+    let (sim_manager, thread_handlers) = start_netlist();
+
+    // counter.output takes packet n in cycle n + 1, link.output 10 cycles later
+    assert!(sim_manager
+        .add_assertion(
+            "unknown",
+            Property::Always(Predicate::high("nothing.output")),
+            false
+        )
+        .is_err());
+    assert!(sim_manager
+        .add_assertion(
+            "empty window",
+            Property::Implies {
+                antecedent: packet("counter.output", 5, false),
+                consequent: packet("link.output", 5, false),
+                min_delay: 10,
+                max_delay: 1,
+            },
+            false
+        )
+        .is_err());
+    sim_manager
+        .add_assertion(
+            "latency",
+            Property::Implies {
+                antecedent: packet("counter.output", 5, false),
+                consequent: packet("link.output", 5, false),
+                min_delay: 1,
+                max_delay: 10,
+            },
+            true,
+        )
+        .unwrap();
+    let too_slow = sim_manager
+        .add_assertion(
+            "too slow",
+            Property::Implies {
+                antecedent: packet("counter.output", 5, false),
+                consequent: packet("link.output", 5, false),
+                min_delay: 0,
+                max_delay: 4,
+            },
+            true,
+        )
+        .unwrap();
+    sim_manager
+        .add_assertion(
+            "last held",
+            Property::Until {
+                hold: packet("counter.output", 19, true),
+                release: packet("link.output", 19, true),
+            },
+            true,
+        )
+        .unwrap();
+    let not_held = sim_manager
+        .add_assertion(
+            "not held",
+            Property::Until {
+                hold: packet("link.output", 3, false),
+                release: packet("counter.output", 19, true),
+            },
+            false,
+        )
+        .unwrap();
+    sim_manager
+        .add_assertion(
+            "in range",
+            Property::Never(packet("counter.output", 20, false)),
+            true,
+        )
+        .unwrap();
+
+    let StopReason::AssertionFailed(failures) = sim_manager.run().unwrap() else {
+        panic!("expected \"too slow\" to stop the simulation");
+    };
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].id, too_slow);
+    assert_eq!(failures[0].cycle, 10);
+    assert_eq!(failures[0].start_cycle, 6);
+    assert_eq!(
+        failures[0].values.get("link.output").unwrap(),
+        "SimpleData { packet_id: 0, is_last: false }"
+    );
+    assert!(failures[0]
+        .to_string()
+        .starts_with("assertion \"too slow\" failed at cycle 10 (started at cycle 6)"));
+
+    assert_eq!(sim_manager.run().unwrap(), StopReason::SimEnd);
+    let failures = sim_manager.get_assertion_failures().unwrap();
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[1].id, not_held);
+    assert_eq!(failures[1].cycle, 15);
+    assert_eq!(failures[1].start_cycle, 14);
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}
+
+#[test]
+fn last_cycle_assertion_test() {
+    let (sim_manager, thread_handlers) = start_netlist();
+
+    // the last packet reaches the receiver in the last cycle
+    let last_received = sim_manager
+        .add_assertion(
+            "last received",
+            Property::Never(packet("receiver.input", 19, true)),
+            true,
+        )
+        .unwrap();
+
+    let StopReason::AssertionFailed(failures) = sim_manager.run().unwrap() else {
+        panic!("expected the last cycle to be checked");
+    };
+    // the failures of the cycle before are returned too, not dropped as the simulation ends
+    let curr_cycle = sim_manager.get_curr_cycle();
+    assert!(failures.iter().all(|failure| failure.id == last_received));
+    assert_eq!(
+        failures
+            .iter()
+            .map(|failure| failure.cycle)
+            .collect::<Vec<_>>(),
+        vec![curr_cycle - 1, curr_cycle]
+    );
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}
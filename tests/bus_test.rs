@@ -0,0 +1,870 @@
+mod simple_component;
+
+use crossbeam_channel::{unbounded, Sender};
+use rsim_core::component::Component;
+use rsim_core::components::bus::apb::{ApbChecker, ApbInterconnect, ApbRequest, ApbResponse};
+use rsim_core::components::bus::axi_lite::{
+    axi_lite, AxiLiteAddress, AxiLiteInterconnect, AxiLiteManagerPort, AxiLiteReadData,
+    AxiLiteSubordinatePort, AxiLiteWriteData, AxiLiteWriteResponse, AxiResponse,
+};
+use rsim_core::components::bus::wishbone::{
+    WishboneChecker, WishboneInterconnect, WishboneRequest, WishboneResponse,
+};
+use rsim_core::components::bus::{AddressRange, Arbiter, Arbitration};
+use rsim_core::rx::Rx;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::{ComponentId, EventId, Input, Output};
+use simple_component::testbench::new_tx;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const NUM_WORDS: usize = 16;
+
+/// The data read, 0 for writes, and whether the access failed
+type Access = (u64, bool);
+type Results = Arc<Mutex<Vec<Access>>>;
+
+#[derive(Clone, Copy)]
+struct Operation {
+    write: bool,
+    addr: u64,
+    data: u64,
+}
+
+fn write(addr: u64, data: u64) -> Operation {
+    Operation {
+        write: true,
+        addr,
+        data,
+    }
+}
+
+fn read(addr: u64) -> Operation {
+    Operation {
+        write: false,
+        addr,
+        data: 0,
+    }
+}
+
+fn get_ranges() -> [AddressRange; 2] {
+    [
+        AddressRange::new(0x0, 0x80),
+        AddressRange::new(0x1000, 0x80),
+    ]
+}
+
+/// The operations of each of the two managers and their expected results,
+/// the managers access both subordinates at the same time and 0x2000 is not decoded
+fn get_script() -> [(Vec<Operation>, Vec<Access>); 2] {
+    [
+        (
+            vec![
+                write(0x08, 0x11),
+                write(0x1000, 0x22),
+                read(0x08),
+                read(0x2000),
+                read(0x1000),
+            ],
+            vec![
+                (0, false),
+                (0, false),
+                (0x11, false),
+                (0, true),
+                (0x22, false),
+            ],
+        ),
+        (
+            vec![
+                write(0x1008, 0x33),
+                read(0x1008),
+                write(0x10, 0x44),
+                read(0x10),
+            ],
+            vec![(0, false), (0x33, false), (0, false), (0x44, false)],
+        ),
+    ]
+}
+
+/// The fields every test component has
+struct Common {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl Common {
+    fn new(
+        component_id: ComponentId,
+        sim_manager: &Arc<SimManager>,
+        ack_sender: &Sender<EventId>,
+    ) -> Self {
+        let clock_tick_channel = unbounded();
+        Common {
+            component_id,
+            sim_manager: sim_manager.clone(),
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender: ack_sender.clone(),
+        }
+    }
+
+    /// Managers hold the end of the simulation until their operations are done
+    fn init(&self, manager: bool) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        let name = if manager { "manager" } else { "subordinate" };
+        self.sim_manager
+            .register_component(self.component_id, &format!("{}{}", name, self.component_id))
+            .unwrap();
+        if manager {
+            self.sim_manager.register_do_not_end(self.component_id);
+        }
+    }
+}
+
+/// `NUM_WORDS` words of 8 bytes from `base`
+struct Memory {
+    base: u64,
+    words: Vec<u64>,
+}
+
+impl Memory {
+    fn new(range: AddressRange) -> Self {
+        Memory {
+            base: range.base,
+            words: vec![0; NUM_WORDS],
+        }
+    }
+
+    /// Returns the word read, 0 for writes
+    fn access(&mut self, write: bool, addr: u64, data: u64) -> u64 {
+        let word = &mut self.words[((addr - self.base) / 8) as usize];
+        if write {
+            *word = data;
+            0
+        } else {
+            *word
+        }
+    }
+}
+
+/// Does its operations one after the other
+struct ApbManager {
+    common: Common,
+    request: Tx<ApbRequest>,
+    response: Rx<ApbResponse>,
+    operations: VecDeque<Operation>,
+    results: Results,
+}
+
+impl ApbManager {
+    fn on_clock(&mut self) {
+        let request = self.request.get_value();
+        let response = self.response.get_value();
+        if request.sel && !request.enable {
+            self.request.send(request.access(), 0);
+            return;
+        }
+        if request.sel && !response.ready {
+            return;
+        }
+        if request.sel {
+            self.results
+                .lock()
+                .unwrap()
+                .push((response.rdata, response.slverr));
+        }
+        let next_request = match self.operations.pop_front() {
+            Some(operation) if operation.write => {
+                ApbRequest::write(operation.addr, operation.data, 0xff)
+            }
+            Some(operation) => ApbRequest::read(operation.addr),
+            None => {
+                self.common
+                    .sim_manager
+                    .register_can_end(self.common.component_id);
+                ApbRequest::default()
+            }
+        };
+        if next_request != request {
+            self.request.send(next_request, 0);
+        }
+    }
+}
+
+impl Component for ApbManager {
+    fn init(&mut self) {
+        self.common.init(true);
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        self.response.drain();
+        if let Ok(event) = self.common.clock_receiver.try_recv() {
+            self.response.drain();
+            self.on_clock();
+            self.common.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.common.component_id
+    }
+}
+
+/// Adds no wait state, setting `ready` for the access phase of every transfer
+struct ApbSubordinate {
+    common: Common,
+    request: Rx<ApbRequest>,
+    response: Tx<ApbResponse>,
+    memory: Memory,
+}
+
+impl Component for ApbSubordinate {
+    fn init(&mut self) {
+        self.common.init(false);
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        self.request.drain();
+        if let Ok(event) = self.common.clock_receiver.try_recv() {
+            self.request.drain();
+            let request = self.request.get_value();
+            if request.sel && !request.enable {
+                let rdata = self
+                    .memory
+                    .access(request.write, request.addr, request.wdata);
+                let response = ApbResponse {
+                    ready: true,
+                    rdata,
+                    slverr: false,
+                };
+                self.response.send(response, 0);
+            } else if self.response.get_value().ready {
+                self.response.send(ApbResponse::default(), 0);
+            }
+            self.common.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.common.component_id
+    }
+}
+
+/// Does its operations one after the other
+struct WishboneManager {
+    common: Common,
+    request: Tx<WishboneRequest>,
+    response: Rx<WishboneResponse>,
+    operations: VecDeque<Operation>,
+    results: Results,
+}
+
+impl WishboneManager {
+    fn on_clock(&mut self) {
+        let request = self.request.get_value();
+        let response = self.response.get_value();
+        if request.stb && !response.ack && !response.err {
+            return;
+        }
+        if request.stb {
+            self.results
+                .lock()
+                .unwrap()
+                .push((response.dat, response.err));
+        }
+        let next_request = match self.operations.pop_front() {
+            Some(operation) if operation.write => {
+                WishboneRequest::write(operation.addr, operation.data, 0xff)
+            }
+            Some(operation) => WishboneRequest::read(operation.addr),
+            None => {
+                self.common
+                    .sim_manager
+                    .register_can_end(self.common.component_id);
+                WishboneRequest::default()
+            }
+        };
+        if next_request != request {
+            self.request.send(next_request, 0);
+        }
+    }
+}
+
+impl Component for WishboneManager {
+    fn init(&mut self) {
+        self.common.init(true);
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        self.response.drain();
+        if let Ok(event) = self.common.clock_receiver.try_recv() {
+            self.response.drain();
+            self.on_clock();
+            self.common.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.common.component_id
+    }
+}
+
+/// Acks every request in the cycle after it is driven
+struct WishboneSubordinate {
+    common: Common,
+    request: Rx<WishboneRequest>,
+    response: Tx<WishboneResponse>,
+    memory: Memory,
+}
+
+impl Component for WishboneSubordinate {
+    fn init(&mut self) {
+        self.common.init(false);
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        self.request.drain();
+        if let Ok(event) = self.common.clock_receiver.try_recv() {
+            self.request.drain();
+            let request = self.request.get_value();
+            if self.response.get_value().ack {
+                self.response.send(WishboneResponse::default(), 0);
+            } else if request.cyc && request.stb {
+                let dat = self.memory.access(request.we, request.adr, request.dat);
+                let response = WishboneResponse {
+                    ack: true,
+                    err: false,
+                    dat,
+                };
+                self.response.send(response, 0);
+            }
+            self.common.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.common.component_id
+    }
+}
+
+/// Does its operations one after the other, sending the address and data of a write together
+struct AxiLiteManager {
+    common: Common,
+    port: AxiLiteManagerPort,
+    operations: VecDeque<Operation>,
+    /// Whether the operation waiting for its response is a write
+    waiting: Option<bool>,
+    results: Results,
+}
+
+impl AxiLiteManager {
+    fn on_clock(&mut self) {
+        self.port.tick();
+        let result = match self.waiting {
+            Some(true) => self
+                .port
+                .b
+                .take()
+                .map(|response| (0, response.resp != AxiResponse::Okay)),
+            Some(false) => self
+                .port
+                .r
+                .take()
+                .map(|response| (response.data, response.resp != AxiResponse::Okay)),
+            None => None,
+        };
+        if let Some(result) = result {
+            self.results.lock().unwrap().push(result);
+            self.waiting = None;
+        }
+        if self.waiting.is_some() {
+            return;
+        }
+        match self.operations.pop_front() {
+            Some(operation) => {
+                let address = AxiLiteAddress {
+                    addr: operation.addr,
+                    prot: 0,
+                };
+                if operation.write {
+                    self.port.aw.send(address).unwrap();
+                    let data = AxiLiteWriteData {
+                        data: operation.data,
+                        strb: 0xff,
+                    };
+                    self.port.w.send(data).unwrap();
+                } else {
+                    self.port.ar.send(address).unwrap();
+                }
+                self.waiting = Some(operation.write);
+            }
+            None => self
+                .common
+                .sim_manager
+                .register_can_end(self.common.component_id),
+        }
+    }
+}
+
+impl Component for AxiLiteManager {
+    fn init(&mut self) {
+        self.common.init(true);
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        self.port.poll();
+        if let Ok(event) = self.common.clock_receiver.try_recv() {
+            self.port.poll();
+            self.on_clock();
+            self.common.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.common.component_id
+    }
+}
+
+/// Answers a request as soon as its response can be sent
+struct AxiLiteSubordinate {
+    common: Common,
+    port: AxiLiteSubordinatePort,
+    memory: Memory,
+}
+
+impl AxiLiteSubordinate {
+    fn on_clock(&mut self) {
+        self.port.tick();
+        if self.port.aw.peek().is_some() && self.port.w.peek().is_some() && self.port.b.can_send() {
+            let address = self.port.aw.take().unwrap();
+            let data = self.port.w.take().unwrap();
+            self.memory.access(true, address.addr, data.data);
+            let response = AxiLiteWriteResponse {
+                resp: AxiResponse::Okay,
+            };
+            self.port.b.send(response).unwrap();
+        }
+        if self.port.ar.peek().is_some() && self.port.r.can_send() {
+            let address = self.port.ar.take().unwrap();
+            let response = AxiLiteReadData {
+                data: self.memory.access(false, address.addr, 0),
+                resp: AxiResponse::Okay,
+            };
+            self.port.r.send(response).unwrap();
+        }
+    }
+}
+
+impl Component for AxiLiteSubordinate {
+    fn init(&mut self) {
+        self.common.init(false);
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        self.port.poll();
+        if let Ok(event) = self.common.clock_receiver.try_recv() {
+            self.port.poll();
+            self.on_clock();
+            self.common.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.common.component_id
+    }
+}
+
+/// Answers writes with `B` as soon as it can, never accepting their address and data
+struct EagerAxiLiteSubordinate {
+    common: Common,
+    port: AxiLiteSubordinatePort,
+}
+
+impl Component for EagerAxiLiteSubordinate {
+    fn init(&mut self) {
+        self.common.init(false);
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        self.port.poll();
+        if let Ok(event) = self.common.clock_receiver.try_recv() {
+            self.port.poll();
+            self.port.tick();
+            if self.port.b.can_send() {
+                let response = AxiLiteWriteResponse {
+                    resp: AxiResponse::Okay,
+                };
+                self.port.b.send(response).unwrap();
+            }
+            self.common.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.common.component_id
+    }
+}
+
+/// Runs `components` until the managers are done, then checks their results
+fn run_script(
+    sim_manager: &Arc<SimManager>,
+    components: Vec<Arc<Mutex<dyn Component>>>,
+    results: &[Results],
+) {
+    let sim_dispatcher = SimDispatcher::new(Arc::downgrade(sim_manager), components);
+    sim_dispatcher.init();
+    let thread_handler = thread::spawn(move || sim_dispatcher.run());
+    sim_manager.run().unwrap();
+    thread_handler.join().unwrap();
+
+    for ((_, expected), results) in get_script().iter().zip(results) {
+        assert_eq!(*results.lock().unwrap(), *expected);
+    }
+    sim_manager.check_protocols().unwrap();
+}
+
+#[test]
+fn apb_interconnect_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut interconnect =
+        ApbInterconnect::new(0, sim_manager.clone(), "apb", ack_channel.0.clone());
+    let mut components: Vec<Arc<Mutex<dyn Component>>> = Vec::new();
+    let mut results = Vec::new();
+    for (index, (operations, _)) in get_script().into_iter().enumerate() {
+        let mut request = new_tx(&sim_manager, &ack_channel.0);
+        let mut response = new_tx(&sim_manager, &ack_channel.0);
+        let response_rx = response.add_rx();
+        interconnect = interconnect.with_manager(request.add_rx(), response);
+        let manager_results = Results::default();
+        components.push(Arc::new(Mutex::new(ApbManager {
+            common: Common::new(index as ComponentId + 1, &sim_manager, &ack_channel.0),
+            request,
+            response: response_rx,
+            operations: operations.into(),
+            results: manager_results.clone(),
+        })));
+        results.push(manager_results);
+    }
+    for (index, range) in get_ranges().into_iter().enumerate() {
+        let mut request = new_tx(&sim_manager, &ack_channel.0);
+        let mut response = new_tx(&sim_manager, &ack_channel.0);
+        let response_rx = response.add_rx();
+        components.push(Arc::new(Mutex::new(ApbSubordinate {
+            common: Common::new(index as ComponentId + 3, &sim_manager, &ack_channel.0),
+            request: request.add_rx(),
+            response,
+            memory: Memory::new(range),
+        })));
+        interconnect = interconnect.with_subordinate(range, request, response_rx);
+    }
+    components.push(interconnect.build().unwrap());
+
+    run_script(&sim_manager, components, &results);
+}
+
+#[test]
+fn wishbone_interconnect_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut interconnect =
+        WishboneInterconnect::new(0, sim_manager.clone(), "wishbone", ack_channel.0.clone());
+    let mut components: Vec<Arc<Mutex<dyn Component>>> = Vec::new();
+    let mut results = Vec::new();
+    for (index, (operations, _)) in get_script().into_iter().enumerate() {
+        let mut request = new_tx(&sim_manager, &ack_channel.0);
+        let mut response = new_tx(&sim_manager, &ack_channel.0);
+        let response_rx = response.add_rx();
+        interconnect = interconnect.with_manager(request.add_rx(), response);
+        let manager_results = Results::default();
+        components.push(Arc::new(Mutex::new(WishboneManager {
+            common: Common::new(index as ComponentId + 1, &sim_manager, &ack_channel.0),
+            request,
+            response: response_rx,
+            operations: operations.into(),
+            results: manager_results.clone(),
+        })));
+        results.push(manager_results);
+    }
+    for (index, range) in get_ranges().into_iter().enumerate() {
+        let mut request = new_tx(&sim_manager, &ack_channel.0);
+        let mut response = new_tx(&sim_manager, &ack_channel.0);
+        let response_rx = response.add_rx();
+        components.push(Arc::new(Mutex::new(WishboneSubordinate {
+            common: Common::new(index as ComponentId + 3, &sim_manager, &ack_channel.0),
+            request: request.add_rx(),
+            response,
+            memory: Memory::new(range),
+        })));
+        interconnect = interconnect.with_subordinate(range, request, response_rx);
+    }
+    components.push(interconnect.build().unwrap());
+
+    run_script(&sim_manager, components, &results);
+}
+
+#[test]
+fn axi_lite_interconnect_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut interconnect =
+        AxiLiteInterconnect::new(0, sim_manager.clone(), "axi", ack_channel.0.clone());
+    let mut components: Vec<Arc<Mutex<dyn Component>>> = Vec::new();
+    let mut results = Vec::new();
+    for (index, (operations, _)) in get_script().into_iter().enumerate() {
+        let name = format!("axi.manager{}", index);
+        let (manager_port, subordinate_port) =
+            axi_lite(sim_manager.clone(), ack_channel.0.clone(), &name);
+        interconnect = interconnect.with_manager(subordinate_port);
+        let manager_results = Results::default();
+        components.push(Arc::new(Mutex::new(AxiLiteManager {
+            common: Common::new(index as ComponentId + 1, &sim_manager, &ack_channel.0),
+            port: manager_port,
+            operations: operations.into(),
+            waiting: None,
+            results: manager_results.clone(),
+        })));
+        results.push(manager_results);
+    }
+    for (index, range) in get_ranges().into_iter().enumerate() {
+        let name = format!("axi.subordinate{}", index);
+        let (manager_port, subordinate_port) =
+            axi_lite(sim_manager.clone(), ack_channel.0.clone(), &name);
+        components.push(Arc::new(Mutex::new(AxiLiteSubordinate {
+            common: Common::new(index as ComponentId + 3, &sim_manager, &ack_channel.0),
+            port: subordinate_port,
+            memory: Memory::new(range),
+        })));
+        interconnect = interconnect.with_subordinate(range, manager_port);
+    }
+    components.push(interconnect.build().unwrap());
+
+    run_script(&sim_manager, components, &results);
+}
+
+#[test]
+fn axi_lite_early_response_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let (manager_port, subordinate_port) =
+        axi_lite(sim_manager.clone(), ack_channel.0.clone(), "axi.manager0");
+    let manager = AxiLiteManager {
+        common: Common::new(1, &sim_manager, &ack_channel.0),
+        port: manager_port,
+        operations: vec![write(0x0, 1), write(0x8, 2), write(0x10, 3)].into(),
+        waiting: None,
+        results: Results::default(),
+    };
+    let (downstream_port, eager_port) = axi_lite(
+        sim_manager.clone(),
+        ack_channel.0.clone(),
+        "axi.subordinate0",
+    );
+    let subordinate = EagerAxiLiteSubordinate {
+        common: Common::new(3, &sim_manager, &ack_channel.0),
+        port: eager_port,
+    };
+    let interconnect =
+        AxiLiteInterconnect::new(0, sim_manager.clone(), "axi", ack_channel.0.clone())
+            .with_manager(subordinate_port)
+            .with_subordinate(get_ranges()[0], downstream_port)
+            .build()
+            .unwrap();
+    let components: Vec<Arc<Mutex<dyn Component>>> = vec![
+        interconnect,
+        Arc::new(Mutex::new(manager)),
+        Arc::new(Mutex::new(subordinate)),
+    ];
+
+    let sim_dispatcher = SimDispatcher::new(Arc::downgrade(&sim_manager), components);
+    sim_dispatcher.init();
+    let thread_handler = thread::spawn(move || sim_dispatcher.run());
+    for _ in 0..20 {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+    }
+    // the writes never complete, the manager would hold the end of the simulation
+    sim_manager.register_can_end(1);
+    thread_handler.join().unwrap();
+
+    // the interconnect reports the response and waits for the subordinate to accept the write
+    let violations = sim_manager.get_protocol_violations().unwrap();
+    assert!(violations.iter().any(|violation| {
+        violation.checker == "axi.subordinate0"
+            && violation.message.contains("before the address and data")
+    }));
+}
+
+#[test]
+fn apb_checker_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let mut checker = ApbChecker::new(sim_manager.clone(), "apb");
+
+    let ready = ApbResponse {
+        ready: true,
+        ..Default::default()
+    };
+    let idle = ApbResponse::default();
+    let enable_only = ApbRequest {
+        enable: true,
+        ..Default::default()
+    };
+    // (request, response, transfer, violations so far)
+    let steps = [
+        (ApbRequest::read(4), idle, false, 0),
+        (ApbRequest::read(4).access(), ready, true, 0),
+        (enable_only, idle, false, 1),
+        (ApbRequest::default(), idle, false, 1),
+        (ApbRequest::write(8, 1, 0xff), idle, false, 1),
+        // the setup phase is not followed by an access phase
+        (ApbRequest::default(), idle, false, 2),
+        // an access phase without a setup phase
+        (ApbRequest::write(8, 1, 0xff).access(), idle, false, 3),
+        // wdata changes before ready
+        (ApbRequest::write(8, 2, 0xff).access(), ready, true, 4),
+    ];
+    for (index, (request, response, transfer, violations)) in steps.into_iter().enumerate() {
+        assert_eq!(checker.check(request, response), transfer, "step {}", index);
+        assert_eq!(
+            sim_manager.get_protocol_violations().unwrap().len(),
+            violations,
+            "step {}",
+            index
+        );
+    }
+    assert_eq!(checker.get_transfer_count(), 2);
+
+    let state = checker.save_state().unwrap();
+    checker.reset();
+    assert_eq!(checker.get_transfer_count(), 0);
+    checker.restore_state(state).unwrap();
+    assert_eq!(checker.get_transfer_count(), 2);
+}
+
+#[test]
+fn wishbone_checker_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let mut checker = WishboneChecker::new(sim_manager.clone(), "wishbone");
+
+    let ack = WishboneResponse {
+        ack: true,
+        err: false,
+        dat: 5,
+    };
+    let ack_and_err = WishboneResponse {
+        ack: true,
+        err: true,
+        dat: 0,
+    };
+    let idle = WishboneResponse::default();
+    let stb_only = WishboneRequest {
+        stb: true,
+        ..Default::default()
+    };
+    // (request, response, transfer, violations so far)
+    let steps = [
+        (WishboneRequest::read(4), idle, false, 0),
+        (WishboneRequest::read(4), ack, true, 0),
+        // an ack without a request
+        (WishboneRequest::default(), ack, false, 1),
+        (WishboneRequest::write(8, 1, 0xff), idle, false, 1),
+        // the request is withdrawn before ack
+        (WishboneRequest::default(), idle, false, 2),
+        (stb_only, idle, false, 3),
+        (WishboneRequest::read(4), ack_and_err, true, 4),
+        (WishboneRequest::read(4), idle, false, 4),
+        // adr changes before ack
+        (WishboneRequest::read(12), idle, false, 5),
+    ];
+    for (index, (request, response, transfer, violations)) in steps.into_iter().enumerate() {
+        assert_eq!(checker.check(request, response), transfer, "step {}", index);
+        assert_eq!(
+            sim_manager.get_protocol_violations().unwrap().len(),
+            violations,
+            "step {}",
+            index
+        );
+    }
+    assert_eq!(checker.get_transfer_count(), 2);
+}
+
+#[test]
+fn arbiter_test() {
+    let mut round_robin = Arbiter::new(Arbitration::RoundRobin);
+    let grants: Vec<_> = (0..3)
+        .map(|_| round_robin.grant(&[true, true, false]))
+        .collect();
+    assert_eq!(grants, vec![Some(0), Some(1), Some(0)]);
+    assert_eq!(round_robin.grant(&[false, false, true]), Some(2));
+    assert_eq!(round_robin.grant(&[false, false, false]), None);
+    round_robin.reset();
+    assert_eq!(round_robin.grant(&[true, true, true]), Some(0));
+
+    let mut fixed_priority = Arbiter::new(Arbitration::FixedPriority);
+    for _ in 0..3 {
+        assert_eq!(fixed_priority.grant(&[false, true, true]), Some(1));
+    }
+}
+
+#[test]
+fn bus_config_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let new_interconnect = |ranges: &[AddressRange]| {
+        let mut interconnect =
+            ApbInterconnect::new(0, sim_manager.clone(), "apb", ack_channel.0.clone());
+        for range in ranges {
+            let mut response: Tx<ApbResponse> = new_tx(&sim_manager, &ack_channel.0);
+            interconnect = interconnect.with_subordinate(
+                *range,
+                new_tx(&sim_manager, &ack_channel.0),
+                response.add_rx(),
+            );
+        }
+        interconnect
+    };
+
+    assert!(new_interconnect(&get_ranges()).build().is_ok());
+    assert!(new_interconnect(&[AddressRange::new(0x0, 0)])
+        .build()
+        .is_err());
+    assert!(new_interconnect(&[
+        AddressRange::new(0x0, 0x100),
+        AddressRange::new(0x80, 0x100)
+    ])
+    .build()
+    .is_err());
+    assert!(new_interconnect(&[
+        AddressRange::new(0x100, 0x100),
+        AddressRange::new(0x0, 0x101)
+    ])
+    .build()
+    .is_err());
+}
@@ -0,0 +1,145 @@
+mod simple_component;
+
+use crossbeam_channel::{unbounded, Sender};
+use rsim_core::channel::{ready_valid, Beat, ReadyValidRx};
+use rsim_core::component::Component;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::{ComponentId, EventId, Input, Output};
+use simple_component::flow_control::{new_consumer, run_transfers, NUM_VALUES};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Sends a value every other cycle on a plain `Tx`, dropping `valid` whether it was transferred or not
+struct DroppingProducer {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    output: Tx<Beat<u32>>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl Component for DroppingProducer {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, "producer")
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        if let Ok(event) = self.clock_receiver.try_recv() {
+            let cycle = self.sim_manager.get_curr_cycle();
+            if cycle % 2 == 1 {
+                self.output.send(Beat::new(cycle as u32), 0);
+            } else {
+                self.output.send(Beat::idle(), 0);
+            }
+            self.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
+
+/// `run_transfers` over a ready/valid channel
+fn run_channel_transfers(period: u64) -> (Vec<u32>, u64) {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let (output, input) = ready_valid(sim_manager.clone(), ack_channel.0.clone(), "channel");
+    run_transfers(&sim_manager, &ack_channel.0, output, input, period)
+}
+
+#[test]
+fn channel_full_throughput_test() {
+    let (received, cycles) = run_channel_transfers(1);
+    assert_eq!(received, (0..NUM_VALUES).collect::<Vec<_>>());
+    // a value per cycle once the channel is running
+    assert!(cycles <= NUM_VALUES as u64 + 4);
+}
+
+#[test]
+fn channel_backpressure_test() {
+    let (received, cycles) = run_channel_transfers(3);
+    assert_eq!(received, (0..NUM_VALUES).collect::<Vec<_>>());
+    assert!(cycles >= 3 * NUM_VALUES as u64);
+}
+
+#[test]
+fn channel_pending_value_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let (mut output, _input) =
+        ready_valid::<u32>(sim_manager.clone(), ack_channel.0.clone(), "channel");
+
+    // a value is held until it is transferred
+    output.send(1).unwrap();
+    assert!(!output.can_send());
+    assert!(output.send(2).is_err());
+    assert_eq!(output.get_transfer_count(), 0);
+}
+
+#[test]
+fn channel_dropped_valid_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let mut output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let input = ReadyValidRx::new(
+        sim_manager.clone(),
+        "channel",
+        output.add_rx(),
+        Tx::new(sim_manager.clone(), ack_channel.0.clone()),
+    );
+
+    let clock_tick_channel = unbounded();
+    let producer = Arc::new(Mutex::new(DroppingProducer {
+        component_id: 0,
+        sim_manager: sim_manager.clone(),
+        output,
+        clock_sender: clock_tick_channel.0,
+        clock_receiver: clock_tick_channel.1,
+        ack_sender: ack_channel.0.clone(),
+    }));
+    // the first value fills the buffer, the next ones are never transferred
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let consumer = new_consumer(&sim_manager, &ack_channel.0, input, u64::MAX, &received);
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![producer]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![consumer]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+    for _ in 0..10 {
+        sim_manager.run_cycle().unwrap();
+    }
+    sim_manager.run_cycle_end().unwrap();
+
+    let violations = sim_manager.get_protocol_violations().unwrap();
+    assert!(!violations.is_empty());
+    assert_eq!(violations[0].checker, "channel");
+    assert_eq!(violations[0].cycle, 5);
+    assert_eq!(
+        violations[0].message,
+        "valid dropped without a transfer of 3"
+    );
+    assert!(sim_manager.check_protocols().is_err());
+
+    sim_manager.register_can_end(1);
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}
@@ -0,0 +1,134 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::checkpoint::Checkpoint;
+use rsim_core::error::SimError;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::Cycle;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_event::SimpleData;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+
+fn build_netlist() -> (Arc<SimManager>, Vec<Arc<SimDispatcher>>) {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        40,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    (sim_manager, sim_dispatchers)
+}
+
+fn spawn(sim_dispatchers: Vec<Arc<SimDispatcher>>) -> Vec<JoinHandle<()>> {
+    sim_dispatchers
+        .into_iter()
+        .map(|sim_dispatcher| thread::spawn(move || sim_dispatcher.run()))
+        .collect()
+}
+
+/// Steps until the end of the simulation, recording what the receiver sees every cycle
+fn trace(sim_manager: &SimManager) -> Vec<(Cycle, SimpleData)> {
+    let mut trace = Vec::new();
+    while !sim_manager.sim_can_end() {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+        trace.push((
+            sim_manager.get_curr_cycle(),
+            sim_manager.peek("receiver.input").unwrap(),
+        ));
+    }
+    trace
+}
+
+#[test]
+fn checkpoint_test() {
+    let path = std::env::temp_dir().join("rsim_checkpoint_test.json");
+
+    let (sim_manager, sim_dispatchers) = build_netlist();
+    let thread_handlers = spawn(sim_dispatchers);
+    for _ in 0..21 {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+    }
+    let checkpoint = sim_manager.checkpoint().unwrap();
+    assert_eq!(checkpoint.cycle, 21);
+    assert!(!checkpoint.pending_events.is_empty());
+    checkpoint.save_to_file(&path).unwrap();
+    let expected = trace(&sim_manager);
+    thread_handlers.into_iter().for_each(|h| h.join().unwrap());
+    assert_eq!(expected.last().unwrap().1, SimpleData::new(39, true));
+
+    let (sim_manager_restored, sim_dispatchers) = build_netlist();
+    sim_manager_restored
+        .restore(&Checkpoint::load_from_file(&path).unwrap())
+        .unwrap();
+    let thread_handlers = spawn(sim_dispatchers);
+    let restored = trace(&sim_manager_restored);
+    thread_handlers.into_iter().for_each(|h| h.join().unwrap());
+
+    assert_eq!(expected, restored);
+    assert_eq!(
+        sim_manager.get_event_processed().unwrap(),
+        sim_manager_restored.get_event_processed().unwrap()
+    );
+    let (stats, stats_restored) = (
+        sim_manager.get_stats().unwrap(),
+        sim_manager_restored.get_stats().unwrap(),
+    );
+    assert_eq!(stats.cycles, stats_restored.cycles);
+    assert_eq!(stats.events_delivered, stats_restored.events_delivered);
+    assert_eq!(stats.counters, stats_restored.counters);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn unregistered_port_checkpoint_test() {
+    let (sim_manager, _sim_dispatchers) = build_netlist();
+    assert!(sim_manager.checkpoint().is_ok());
+
+    // an event pending on a port nobody registered cannot be saved
+    let ack_channel = unbounded();
+    let mut unregistered = Tx::new(sim_manager.clone(), ack_channel.0);
+    let _rx = unregistered.add_rx();
+    unregistered.send(SimpleData::new(0, false), 2);
+    assert!(matches!(
+        sim_manager.checkpoint(),
+        Err(SimError::CheckpointError(message)) if message.contains("unregistered port")
+    ));
+}
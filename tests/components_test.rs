@@ -0,0 +1,300 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::components::adder::Adder;
+use rsim_core::components::comparator::Comparator;
+use rsim_core::components::decoder::Decoder;
+use rsim_core::components::mux::Mux;
+use rsim_core::components::register::Register;
+use rsim_core::components::shifter::{ShiftKind, Shifter};
+use rsim_core::components::sign_extender::SignExtender;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::Cycle;
+use simple_component::testbench::{new_tx, run_cycles};
+
+#[test]
+fn counter_datapath_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    // acc <= acc + 1, sampled by a comparator against 3
+    let mut acc_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut sum = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut one = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut three = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let adder_a = acc_output.add_rx();
+    let comparator_a = acc_output.add_rx();
+    let adder_b = one.add_rx();
+    let comparator_b = three.add_rx();
+    let acc_data = sum.add_rx();
+
+    let acc = Register::new(
+        0,
+        sim_manager.clone(),
+        "acc",
+        acc_data,
+        acc_output,
+        ack_channel.0.clone(),
+    )
+    .build()
+    .unwrap();
+    let adder = Adder::new(1, sim_manager.clone(), "adder", adder_a, adder_b, sum)
+        .with_width(8)
+        .build()
+        .unwrap();
+    let comparator = Comparator::new(
+        2,
+        sim_manager.clone(),
+        "comparator",
+        comparator_a,
+        comparator_b,
+    )
+    .with_equal(Tx::new(sim_manager.clone(), ack_channel.0.clone()))
+    .with_greater(Tx::new(sim_manager.clone(), ack_channel.0.clone()))
+    .build()
+    .unwrap();
+
+    one.send(1, 0);
+    three.send(3, 0);
+    run_cycles(&sim_manager, vec![acc, adder, comparator], 300, |cycle| {
+        let expected = (cycle % 256) as u64;
+        assert_eq!(sim_manager.peek::<u64>("acc.output").unwrap(), expected);
+        assert_eq!(
+            sim_manager.peek::<u64>("adder.output").unwrap(),
+            (expected + 1) % 256
+        );
+        assert_eq!(
+            sim_manager.peek::<bool>("comparator.equal").unwrap(),
+            expected == 3
+        );
+        assert_eq!(
+            sim_manager.peek::<bool>("comparator.greater").unwrap(),
+            expected > 3
+        );
+    });
+}
+
+#[test]
+fn register_enable_reset_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut data = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut enable = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut reset = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let register = Register::new(
+        0,
+        sim_manager.clone(),
+        "register",
+        data.add_rx(),
+        Tx::<u32>::new(sim_manager.clone(), ack_channel.0.clone()),
+        ack_channel.0.clone(),
+    )
+    .with_enable(enable.add_rx())
+    .with_reset(reset.add_rx(), 7)
+    .build()
+    .unwrap();
+
+    // (data, enable, reset) due at each clock tick from the first one, sampled by it
+    let inputs = [
+        (1, false, false),
+        (2, true, false),
+        (3, false, false),
+        (4, true, true),
+        (5, true, false),
+    ];
+    for (cycle, (data_value, enable_value, reset_value)) in inputs.into_iter().enumerate() {
+        data.send(data_value, cycle as Cycle + 1);
+        enable.send(enable_value, cycle as Cycle + 1);
+        reset.send(reset_value, cycle as Cycle + 1);
+    }
+    // the value in the register after each clock tick
+    let expected = [7, 7, 2, 2, 7, 5, 5];
+    assert_eq!(sim_manager.get_curr_cycle(), 0);
+    run_cycles(&sim_manager, vec![register], 6, |cycle| {
+        assert_eq!(
+            sim_manager.peek::<u32>("register.output").unwrap(),
+            expected[cycle as usize],
+            "cycle {}",
+            cycle
+        );
+    });
+}
+
+/// mux: select, inputs; decoder: select, enable; adder and comparator: a, b, subtract; shifter and sext: value, amount
+type Inputs = (u64, [u32; 3], u64, bool, u64, u64, bool, u64, u64);
+
+/// mux, decoder, sum, carry, overflow, less, shifted, extended
+type Outputs = (u32, u64, u64, bool, bool, bool, u64, u64);
+
+#[test]
+fn combinational_components_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut inputs: Vec<Tx<u32>> = (0..3)
+        .map(|_| new_tx(&sim_manager, &ack_channel.0))
+        .collect();
+    let mut select = new_tx(&sim_manager, &ack_channel.0);
+    let mux = Mux::new(
+        0,
+        sim_manager.clone(),
+        "mux",
+        inputs.iter_mut().map(|input| input.add_rx()).collect(),
+        select.add_rx(),
+        new_tx(&sim_manager, &ack_channel.0),
+    )
+    .build()
+    .unwrap();
+
+    let mut decoder_select = new_tx(&sim_manager, &ack_channel.0);
+    let mut decoder_enable = new_tx(&sim_manager, &ack_channel.0);
+    let decoder = Decoder::new(
+        1,
+        sim_manager.clone(),
+        "decoder",
+        decoder_select.add_rx(),
+        new_tx(&sim_manager, &ack_channel.0),
+    )
+    .with_width(2)
+    .with_enable(decoder_enable.add_rx())
+    .build()
+    .unwrap();
+
+    let mut a = new_tx(&sim_manager, &ack_channel.0);
+    let mut b = new_tx(&sim_manager, &ack_channel.0);
+    let mut subtract = new_tx(&sim_manager, &ack_channel.0);
+    let adder = Adder::new(
+        2,
+        sim_manager.clone(),
+        "adder",
+        a.add_rx(),
+        b.add_rx(),
+        new_tx(&sim_manager, &ack_channel.0),
+    )
+    .with_width(8)
+    .with_subtract(subtract.add_rx())
+    .with_carry(new_tx(&sim_manager, &ack_channel.0))
+    .with_overflow(new_tx(&sim_manager, &ack_channel.0))
+    .build()
+    .unwrap();
+    let comparator = Comparator::new(3, sim_manager.clone(), "comparator", a.add_rx(), b.add_rx())
+        .with_width(8)
+        .with_signed()
+        .with_less(new_tx(&sim_manager, &ack_channel.0))
+        .build()
+        .unwrap();
+
+    let mut value = new_tx(&sim_manager, &ack_channel.0);
+    let mut amount = new_tx(&sim_manager, &ack_channel.0);
+    let shifter = Shifter::new(
+        4,
+        sim_manager.clone(),
+        "shifter",
+        value.add_rx(),
+        amount.add_rx(),
+        new_tx(&sim_manager, &ack_channel.0),
+    )
+    .with_width(8)
+    .with_kind(ShiftKind::ArithmeticRight)
+    .build()
+    .unwrap();
+    let sign_extender = SignExtender::new(
+        5,
+        sim_manager.clone(),
+        "sext",
+        12,
+        value.add_rx(),
+        new_tx(&sim_manager, &ack_channel.0),
+    )
+    .with_width(32)
+    .build()
+    .unwrap();
+
+    let vectors: [Inputs; 4] = [
+        (1, [10, 11, 12], 2, true, 0x7f, 0x01, false, 0x80, 1),
+        (2, [10, 11, 12], 3, false, 0x10, 0x20, true, 0x800, 3),
+        (5, [10, 11, 12], 0, true, 0x80, 0x80, false, 0x40, 8),
+        (0, [20, 21, 22], 1, true, 0xff, 0x01, true, 0xfff, 2),
+    ];
+    for (index, vector) in vectors.iter().enumerate() {
+        let cycle = index as Cycle + 1;
+        select.send(vector.0, cycle);
+        for (input, value) in inputs.iter_mut().zip(vector.1) {
+            input.send(value, cycle);
+        }
+        decoder_select.send(vector.2, cycle);
+        decoder_enable.send(vector.3, cycle);
+        a.send(vector.4, cycle);
+        b.send(vector.5, cycle);
+        subtract.send(vector.6, cycle);
+        value.send(vector.7, cycle);
+        amount.send(vector.8, cycle);
+    }
+
+    let expected: [Outputs; 4] = [
+        (11, 0b0100, 0x80, false, true, false, 0xc0, 0x80),
+        (12, 0, 0xf0, false, false, true, 0x00, 0xffff_f800),
+        (0, 0b0001, 0x00, true, true, false, 0x00, 0x40),
+        (20, 0b0010, 0xfe, true, false, true, 0xff, 0xffff_ffff),
+    ];
+    run_cycles(
+        &sim_manager,
+        vec![mux, decoder, adder, comparator, shifter, sign_extender],
+        expected.len() as Cycle,
+        |cycle| {
+            let expected = expected[cycle as usize - 1];
+            let actual = (
+                sim_manager.peek::<u32>("mux.output").unwrap(),
+                sim_manager.peek::<u64>("decoder.output").unwrap(),
+                sim_manager.peek::<u64>("adder.output").unwrap(),
+                sim_manager.peek::<bool>("adder.carry").unwrap(),
+                sim_manager.peek::<bool>("adder.overflow").unwrap(),
+                sim_manager.peek::<bool>("comparator.less").unwrap(),
+                sim_manager.peek::<u64>("shifter.output").unwrap(),
+                sim_manager.peek::<u64>("sext.output").unwrap(),
+            );
+            assert_eq!(actual, expected, "cycle {}", cycle);
+        },
+    );
+}
+
+#[test]
+fn component_width_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    assert!(Adder::new(
+        0,
+        sim_manager.clone(),
+        "adder",
+        new_tx(&sim_manager, &ack_channel.0).add_rx(),
+        new_tx(&sim_manager, &ack_channel.0).add_rx(),
+        new_tx(&sim_manager, &ack_channel.0)
+    )
+    .with_width(0)
+    .build()
+    .is_err());
+    assert!(Decoder::new(
+        0,
+        sim_manager.clone(),
+        "decoder",
+        new_tx(&sim_manager, &ack_channel.0).add_rx(),
+        new_tx(&sim_manager, &ack_channel.0)
+    )
+    .with_width(7)
+    .build()
+    .is_err());
+    assert!(SignExtender::new(
+        0,
+        sim_manager.clone(),
+        "sext",
+        32,
+        new_tx(&sim_manager, &ack_channel.0).add_rx(),
+        new_tx(&sim_manager, &ack_channel.0)
+    )
+    .with_width(16)
+    .build()
+    .is_err());
+}
@@ -0,0 +1,149 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::coverage::{Bin, CoverageReport, Covergroup, Coverpoint, Cross};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::watchpoint::StopReason;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+fn packet(packet_id: u128, is_last: bool) -> String {
+    format!(
+        "SimpleData {{ packet_id: {}, is_last: {} }}",
+        packet_id, is_last
+    )
+}
+
+fn run_with_coverage(num_packets: u128, report_path: &Path) -> CoverageReport {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        num_packets,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    let bins = vec![
+        Bin::value("first", &packet(1, false)),
+        Bin::value("last of 20", &packet(19, true)),
+        Bin::value("last of 26", &packet(25, true)),
+    ];
+    let group = Covergroup::new(
+        "packets",
+        vec![
+            Coverpoint::new("sent", "counter.output", bins.clone()),
+            Coverpoint::new("received", "receiver.input", bins),
+            Coverpoint::new("forwarded", "link.output", Vec::new()),
+        ],
+        vec![
+            Cross::new("sent x received", &["sent", "received"]),
+            Cross::new("sent x forwarded", &["sent", "forwarded"]),
+        ],
+    );
+    assert!(sim_manager
+        .add_covergroup(Covergroup::new(
+            "bad cross",
+            Vec::new(),
+            vec![Cross::new("cross", &["nothing"])]
+        ))
+        .is_err());
+    sim_manager.add_covergroup(group.clone()).unwrap();
+    assert!(sim_manager.add_covergroup(group).is_err());
+    sim_manager.set_coverage_report(report_path).unwrap();
+
+    assert_eq!(sim_manager.run().unwrap(), StopReason::SimEnd);
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+    sim_manager.get_coverage().unwrap()
+}
+
+#[test]
+fn coverage_test() {
+    let short_path = std::env::temp_dir().join("rsim_coverage_test_short");
+    let long_path = std::env::temp_dir().join("rsim_coverage_test_long");
+
+    let short = run_with_coverage(20, &short_path);
+    let group = &short.groups["packets"];
+    let sent = &group.coverpoints["sent"];
+    assert_eq!(sent.bins["first"], 1);
+    assert!(sent.bins["last of 20"] > 0);
+    assert_eq!(sent.bins["last of 26"], 0);
+    assert_eq!(sent.get_coverage(), Some(2.0 / 3.0));
+    assert!(group.coverpoints["forwarded"].automatic);
+    assert_eq!(group.coverpoints["forwarded"].bins[&packet(5, false)], 1);
+    assert_eq!(group.crosses["sent x received"].bins.len(), 9);
+    assert!(group.crosses["sent x received"].bins["last of 20 x last of 20"] > 0);
+    assert_eq!(group.crosses["sent x received"].bins["first x first"], 0);
+    // received in the cycle before the last one, which is sampled too
+    assert_eq!(group.coverpoints["received"].bins["last of 20"], 2);
+    assert!(group.crosses["sent x forwarded"].automatic);
+    assert_eq!(
+        group.get_coverage(),
+        Some((2.0 / 3.0 + 2.0 / 3.0 + 1.0 / 9.0) / 3.0)
+    );
+
+    let text = std::fs::read_to_string(short_path.with_extension("txt")).unwrap();
+    assert!(text.contains("covergroup packets"));
+    assert!(text.contains("    last of 26: 0 (missed)"));
+    assert_eq!(
+        CoverageReport::load_json(short_path.with_extension("json")).unwrap(),
+        short
+    );
+
+    run_with_coverage(26, &long_path);
+    let merged = CoverageReport::merge_files(&[
+        short_path.with_extension("json"),
+        long_path.with_extension("json"),
+    ])
+    .unwrap();
+    assert_eq!(merged.runs, 2);
+    let sent = &merged.groups["packets"].coverpoints["sent"];
+    assert_eq!(sent.bins["first"], 2);
+    assert_eq!(sent.get_coverage(), Some(1.0));
+
+    for path in [short_path, long_path] {
+        let _ = std::fs::remove_file(path.with_extension("txt"));
+        let _ = std::fs::remove_file(path.with_extension("json"));
+    }
+}
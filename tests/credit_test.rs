@@ -0,0 +1,165 @@
+mod simple_component;
+
+use crossbeam_channel::{unbounded, Sender};
+use rsim_core::component::Component;
+use rsim_core::credit::{credit_link, CreditRx};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::{ComponentId, EventId, Input, Output};
+use simple_component::flow_control::{new_consumer, run_transfers, NUM_VALUES};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Sends a value every cycle on a plain `Tx`, ignoring the credits
+struct GreedyProducer {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    output: Tx<u32>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl Component for GreedyProducer {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, "producer")
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+    }
+
+    fn reset(&mut self) {}
+
+    fn poll_recv(&mut self) {
+        if let Ok(event) = self.clock_receiver.try_recv() {
+            let cycle = self.sim_manager.get_curr_cycle();
+            self.output.send(cycle as u32, 1);
+            self.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
+
+/// `run_transfers` over a credit-based link
+fn run_credit_transfers(latency: u128, depth: u32, period: u64) -> (Vec<u32>, u64) {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let (output, input) = credit_link(
+        sim_manager.clone(),
+        ack_channel.0.clone(),
+        "link",
+        latency,
+        depth,
+    )
+    .unwrap();
+    run_transfers(&sim_manager, &ack_channel.0, output, input, period)
+}
+
+#[test]
+fn credit_link_test() {
+    // enough credits to cover the round trip, a value per cycle
+    let (received, cycles) = run_credit_transfers(2, 8, 1);
+    assert_eq!(received, (0..NUM_VALUES).collect::<Vec<_>>());
+    assert!(cycles <= NUM_VALUES as u64 + 4);
+
+    // a single credit, a value per round trip
+    let (received, cycles) = run_credit_transfers(2, 1, 1);
+    assert_eq!(received, (0..NUM_VALUES).collect::<Vec<_>>());
+    assert!(cycles >= 4 * (NUM_VALUES as u64 - 1));
+}
+
+#[test]
+fn credit_link_config_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    assert!(credit_link::<u32>(sim_manager.clone(), ack_channel.0.clone(), "link", 0, 4).is_err());
+    assert!(credit_link::<u32>(sim_manager.clone(), ack_channel.0.clone(), "link", 1, 0).is_err());
+}
+
+#[test]
+fn credit_underflow_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let (mut output, _input) =
+        credit_link::<u32>(sim_manager.clone(), ack_channel.0.clone(), "link", 1, 2).unwrap();
+
+    output.send(1).unwrap();
+    output.send(2).unwrap();
+    assert!(!output.can_send());
+    assert!(output.send(3).is_err());
+    assert_eq!(output.get_sent_count(), 2);
+
+    let violations = sim_manager.get_protocol_violations().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].checker, "link");
+    assert_eq!(
+        violations[0].message,
+        "credit underflow, cannot send 3 with no credit"
+    );
+    assert!(sim_manager.check_protocols().is_err());
+}
+
+#[test]
+fn credit_overflow_test() {
+    const DEPTH: u32 = 2;
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let mut output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let input = CreditRx::new(
+        sim_manager.clone(),
+        "link",
+        output.add_rx(),
+        Tx::new(sim_manager.clone(), ack_channel.0.clone()),
+        1,
+        DEPTH,
+    );
+
+    let clock_tick_channel = unbounded();
+    let producer = Arc::new(Mutex::new(GreedyProducer {
+        component_id: 0,
+        sim_manager: sim_manager.clone(),
+        output,
+        clock_sender: clock_tick_channel.0,
+        clock_receiver: clock_tick_channel.1,
+        ack_sender: ack_channel.0.clone(),
+    }));
+    // never takes a value
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let consumer = new_consumer(&sim_manager, &ack_channel.0, input, u64::MAX, &received);
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![producer]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![consumer]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+    for _ in 0..5 {
+        sim_manager.run_cycle().unwrap();
+    }
+    sim_manager.run_cycle_end().unwrap();
+
+    // values sent at cycles 1 and 2 fill the buffer, the one sent at cycle 3 overflows it at cycle 4
+    let violations = sim_manager.get_protocol_violations().unwrap();
+    assert!(!violations.is_empty());
+    assert_eq!(violations[0].cycle, 4);
+    assert_eq!(
+        violations[0].message,
+        "buffer overflow, 3 received with 2 values buffered"
+    );
+
+    sim_manager.register_can_end(1);
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}
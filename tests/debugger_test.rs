@@ -0,0 +1,111 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::debugger::Debugger;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::io::Cursor;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn debugger_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        20,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    sim_manager.enable_snapshots(4, 16).unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    let script = "\
+list link
+break cycle 15
+continue
+print receiver.input
+delete 1
+break link.output changed
+watch receiver.input
+continue
+disable 2
+breakpoints
+step 3
+back 2
+bogus
+info 2
+continue
+quit
+";
+    let mut output = Vec::new();
+    Debugger::new(sim_manager.clone())
+        .run(Cursor::new(script), &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    let expected = [
+        "link (id 1)",
+        "  link.input Input",
+        "breakpoint 1: cycle 15",
+        "breakpoint 1 hit at cycle 15",
+        "receiver.input = SimpleData { packet_id: 4, is_last: false }",
+        "breakpoint 2: link.output changed",
+        "[16] receiver.input = SimpleData { packet_id: 5, is_last: false }",
+        "breakpoint 2 hit at cycle 16",
+        "2: link.output changed (disabled)",
+        "cycle 19",
+        "cycle 17",
+        "error: DebuggerError: invalid command \"bogus\"",
+        "error: DebuggerError: invalid command \"info 2\"",
+        "[30] receiver.input = SimpleData { packet_id: 19, is_last: true }",
+        "simulation ended at cycle 30",
+    ];
+    let mut rest = output.as_str();
+    for line in expected {
+        let position = rest
+            .find(line)
+            .unwrap_or_else(|| panic!("missing \"{}\" in:\n{}", line, output));
+        rest = &rest[position + line.len()..];
+    }
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}
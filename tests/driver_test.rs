@@ -0,0 +1,150 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::driver::RandomDriver;
+use rsim_core::error::SimError;
+use rsim_core::random::{Distribution, Rng};
+use rsim_core::scoreboard::{Scoreboard, ScoreboardMode};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_event::SimpleData;
+use std::sync::Arc;
+use std::thread;
+
+const NUM_VALUES: u64 = 200;
+const SEED: u64 = 42;
+
+fn get_distribution() -> Distribution<u128> {
+    Distribution::weighted(vec![(0..=9, 3), (100..=100, 1), (1000..=u128::MAX, 1)]).unwrap()
+}
+
+fn is_odd_or_last(data: &SimpleData) -> bool {
+    data.packet_id % 2 == 1 || data.is_last
+}
+
+/// Draws the values the driver is expected to send with the same seed
+fn get_expected() -> Vec<SimpleData> {
+    let distribution = get_distribution();
+    let mut rng = Rng::new(SEED);
+    let mut expected = Vec::new();
+    while expected.len() < NUM_VALUES as usize {
+        let data = SimpleData::new(distribution.sample(&mut rng), rng.gen_bool(0.1));
+        if is_odd_or_last(&data) {
+            expected.push(data);
+        }
+    }
+    expected
+}
+
+#[test]
+fn driver_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let distribution = get_distribution();
+    let driver = RandomDriver::new(
+        0,
+        sim_manager.clone(),
+        "driver",
+        output,
+        ack_channel.0.clone(),
+        move |rng| SimpleData::new(distribution.sample(rng), rng.gen_bool(0.1)),
+    )
+    .with_seed(SEED)
+    .with_constraint(is_odd_or_last)
+    .with_num_values(NUM_VALUES)
+    .build()
+    .unwrap();
+
+    let sim_dispatchers = vec![SimDispatcher::new(
+        Arc::downgrade(&sim_manager),
+        vec![driver.clone()],
+    )];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    assert_eq!(driver.lock().unwrap().get_seed(), Some(SEED));
+
+    let scoreboard = Scoreboard::from_expected("driver", ScoreboardMode::InOrder, get_expected());
+    sim_manager
+        .add_scoreboard("driver.output", scoreboard.clone())
+        .unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+    sim_manager.check_scoreboards().unwrap();
+    assert_eq!(driver.lock().unwrap().get_sent_count(), NUM_VALUES);
+
+    let stats = sim_manager.get_stats().unwrap();
+    assert_eq!(stats.seed, None);
+    assert_eq!(stats.component_seeds["driver"], SEED);
+    assert!(stats.to_string().contains("seed of driver: 42"));
+    assert_eq!(sim_manager.get_coverage().unwrap().seeds, vec![SEED]);
+}
+
+#[test]
+fn unsatisfiable_constraint_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let driver = RandomDriver::from_distribution(
+        0,
+        sim_manager.clone(),
+        "driver",
+        Tx::new(sim_manager.clone(), ack_channel.0.clone()),
+        ack_channel.0.clone(),
+        Distribution::range(0u8..=7).unwrap(),
+    )
+    .with_seed(SEED)
+    .with_constraint(|value| *value > 7)
+    .with_num_values(NUM_VALUES)
+    .build()
+    .unwrap();
+
+    let sim_dispatcher = SimDispatcher::new(Arc::downgrade(&sim_manager), vec![driver.clone()]);
+    sim_dispatcher.init();
+    let thread_handler = thread::spawn(move || sim_dispatcher.run());
+
+    // the driver lets the simulation end instead of panicking
+    sim_manager.run().unwrap();
+    thread_handler.join().unwrap();
+
+    let driver = driver.lock().unwrap();
+    assert_eq!(driver.get_sent_count(), 0);
+    assert!(matches!(
+        driver.get_error(),
+        Some(SimError::StimulusError(message)) if message.contains("constraints")
+    ));
+}
+
+#[test]
+fn distribution_test() {
+    let (low, high) = (5u8, 4u8);
+    assert!(Distribution::range(low..=high).is_err());
+    assert!(Distribution::weighted(vec![(0u8..=4, 0)]).is_err());
+    assert!(Distribution::weighted(vec![(0u8..=4, u64::MAX), (5..=9, 1)]).is_err());
+
+    let distribution = Distribution::weighted(vec![(-8i8..=-1, 3), (0..=0, 1)]).unwrap();
+    let mut rng = Rng::new(SEED);
+    let samples: Vec<i8> = (0..4000).map(|_| distribution.sample(&mut rng)).collect();
+    assert!(samples.iter().all(|value| (-8..=0).contains(value)));
+    let zeros = samples.iter().filter(|value| **value == 0).count();
+    assert!((800..1200).contains(&zeros));
+    assert!((-8..0).all(|value| samples.contains(&value)));
+
+    assert_eq!(Rng::new(SEED).next_u64(), Rng::new(SEED).next_u64());
+    assert_ne!(Rng::new(SEED).next_u64(), Rng::new(SEED + 1).next_u64());
+    let mut rng = Rng::new(SEED);
+    assert!((0..100).all(|_| rng.gen_range(u64::MAX - 1..=u64::MAX) >= u64::MAX - 1));
+    assert!((0..100).all(|_| rng.gen_range(3i32..=3) == 3));
+}
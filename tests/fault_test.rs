@@ -0,0 +1,252 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::driver::RandomDriver;
+use rsim_core::error::SimError;
+use rsim_core::fault::{Fault, FaultCampaign, FaultKind};
+use rsim_core::random::Distribution;
+use rsim_core::scoreboard::{Scoreboard, ScoreboardMode};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_event::SimpleData;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+
+const NUM_PACKETS: u128 = 20;
+
+/// Runs the counter, link and receiver with `faults`,
+/// the outcome being the packets forwarded by the link as they should and the number of faults injected
+fn simulate(faults: &[Fault]) -> Result<(u64, usize), SimError> {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+    sim_manager.set_seed(1)?;
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        NUM_PACKETS,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let expected =
+        (0..NUM_PACKETS).map(|packet_id| SimpleData::new(packet_id, packet_id == NUM_PACKETS - 1));
+    let scoreboard = Scoreboard::from_expected("link", ScoreboardMode::InOrder, expected);
+    sim_manager.add_scoreboard("link.output", scoreboard.clone())?;
+    for fault in faults {
+        sim_manager.inject_fault(fault.clone())?;
+    }
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run()?;
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+    Ok((
+        scoreboard.get_matched(),
+        sim_manager.get_fault_injections()?.len(),
+    ))
+}
+
+#[test]
+fn fault_campaign_test() {
+    let campaign = FaultCampaign::new(vec![
+        Fault::stuck_at(
+            "packet_id[40] stuck at 0",
+            "counter.output",
+            "packet_id[40]",
+            false,
+        ),
+        Fault::stuck_at(
+            "packet_id[0] stuck at 1",
+            "counter.output",
+            "packet_id[0]",
+            true,
+        ),
+        Fault::bit_flip("is_last flip", "counter.output", "is_last", 3),
+        Fault::drop("drop", "link.output").with_cycles(15, Some(15)),
+        Fault::delay("delay", "counter.output", 5).with_cycles(6, Some(6)),
+        Fault::bit_flip("random flips", "counter.output", "packet_id[1]", 0)
+            .with_cycles(0, None)
+            .with_probability(0.5),
+    ]);
+    let path = std::env::temp_dir().join("rsim_fault_campaign.json");
+    campaign.save_json(&path).unwrap();
+    let campaign = FaultCampaign::load_json(&path).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let report = campaign
+        .run(|faults| simulate(faults).map(|(matched, _)| matched))
+        .unwrap();
+    assert_eq!(report.golden, NUM_PACKETS.to_string());
+    let changed: Vec<&str> = report
+        .get_changed()
+        .iter()
+        .map(|result| result.fault.name.as_str())
+        .collect();
+    assert_eq!(
+        changed,
+        vec![
+            "packet_id[0] stuck at 1",
+            "is_last flip",
+            "drop",
+            "delay",
+            "random flips"
+        ]
+    );
+    assert!(report
+        .to_string()
+        .contains("packet_id[40] stuck at 0 on counter.output: masked"));
+
+    // the random flips are reproducible from the seed
+    let random_flips = campaign.faults[5].clone();
+    let (matched, injections) = simulate(std::slice::from_ref(&random_flips)).unwrap();
+    assert!(injections > 0 && injections < NUM_PACKETS as usize);
+    assert_eq!(simulate(&[random_flips]).unwrap(), (matched, injections));
+    let (matched, injections) =
+        simulate(&[Fault::bit_flip("flip", "counter.output", "is_last", 3)]).unwrap();
+    assert!(matched < NUM_PACKETS as u64);
+    assert_eq!(injections, 1);
+}
+
+#[test]
+fn fault_injection_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let driver = RandomDriver::from_distribution(
+        0,
+        sim_manager.clone(),
+        "driver",
+        output,
+        ack_channel.0.clone(),
+        Distribution::range(1i8..=1).unwrap(),
+    )
+    .with_num_values(10)
+    .build()
+    .unwrap();
+
+    let sim_dispatchers = vec![SimDispatcher::new(
+        Arc::downgrade(&sim_manager),
+        vec![driver],
+    )];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    assert!(sim_manager
+        .inject_fault(Fault::stuck_at("bad bit", "driver.output", "[8]", true))
+        .is_err());
+    assert!(sim_manager
+        .inject_fault(Fault::drop("bad port", "driver.input"))
+        .is_err());
+    assert!(sim_manager
+        .inject_fault(Fault::drop("bad probability", "driver.output").with_probability(2.0))
+        .is_err());
+    sim_manager
+        .inject_fault(Fault::stuck_at("sign", "driver.output", "[7]", true))
+        .unwrap();
+    sim_manager
+        .inject_fault(Fault::bit_flip("flip", "driver.output", "[1]", 4))
+        .unwrap();
+    assert!(sim_manager
+        .inject_fault(Fault::drop("flip", "driver.output"))
+        .is_err());
+    assert_eq!(
+        sim_manager.get_faults().unwrap()[1].kind,
+        FaultKind::BitFlip {
+            bit: "[1]".to_string()
+        }
+    );
+
+    let mut expected = vec![-127i8; 10];
+    expected[3] = -125;
+    sim_manager
+        .add_scoreboard(
+            "driver.output",
+            Scoreboard::from_expected("driver", ScoreboardMode::InOrder, expected),
+        )
+        .unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+    sim_manager.check_scoreboards().unwrap();
+    assert_eq!(sim_manager.get_fault_injections().unwrap().len(), 11);
+    assert_eq!(sim_manager.peek::<i8>("driver.output").unwrap(), -127);
+    sim_manager.remove_fault("sign").unwrap();
+    assert!(sim_manager.remove_fault("sign").is_err());
+}
+
+#[test]
+fn wide_port_fault_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let driver = RandomDriver::from_distribution(
+        0,
+        sim_manager.clone(),
+        "driver",
+        output,
+        ack_channel.0.clone(),
+        Distribution::range(0u128..=1).unwrap(),
+    )
+    .build()
+    .unwrap();
+    let sim_dispatcher = SimDispatcher::new(Arc::downgrade(&sim_manager), vec![driver]);
+    sim_dispatcher.init();
+
+    // the bits of a u128 port above the 64th can't be injected
+    assert!(sim_manager
+        .inject_fault(Fault::stuck_at("high", "driver.output", "[100]", true))
+        .is_err());
+    assert!(sim_manager
+        .inject_fault(Fault::bit_flip("flip", "driver.output", "[64]", 1))
+        .is_err());
+    sim_manager
+        .inject_fault(Fault::stuck_at("low", "driver.output", "[63]", true))
+        .unwrap();
+}
@@ -0,0 +1,231 @@
+mod simple_component;
+
+use crossbeam_channel::{unbounded, Sender};
+use rsim_core::component::Component;
+use rsim_core::components::fifo::{ClockDomain, Fifo};
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::{Cycle, EventId};
+use simple_component::testbench::{new_tx, run_cycles};
+use std::sync::Arc;
+
+/// The push and pop inputs of a FIFO and the FIFO itself, with its flags
+struct Testbench {
+    push: Tx<bool>,
+    push_data: Tx<u64>,
+    pop: Tx<bool>,
+    fifo: Fifo,
+}
+
+impl Testbench {
+    fn new(sim_manager: &Arc<SimManager>, ack_sender: &Sender<EventId>, depth: u64) -> Self {
+        let mut push = new_tx(sim_manager, ack_sender);
+        let mut push_data = new_tx(sim_manager, ack_sender);
+        let mut pop = new_tx(sim_manager, ack_sender);
+        let fifo = Fifo::new(0, sim_manager.clone(), "fifo", ack_sender.clone(), 8, depth)
+            .with_write_port(push.add_rx(), push_data.add_rx())
+            .with_read_port(pop.add_rx(), new_tx(sim_manager, ack_sender))
+            .with_full(new_tx(sim_manager, ack_sender))
+            .with_empty(new_tx(sim_manager, ack_sender))
+            .with_almost_full(new_tx(sim_manager, ack_sender), depth - 1);
+        Testbench {
+            push,
+            push_data,
+            pop,
+            fifo,
+        }
+    }
+
+    /// (push data, pop) due at each clock tick from the first one
+    fn send(&mut self, inputs: &[(Option<u64>, bool)]) {
+        for (index, (push_data, pop)) in inputs.iter().enumerate() {
+            let cycle = index as Cycle + 1;
+            self.push.send(push_data.is_some(), cycle);
+            self.push_data.send(push_data.unwrap_or_default(), cycle);
+            self.pop.send(*pop, cycle);
+        }
+    }
+}
+
+#[test]
+fn fifo_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut testbench = Testbench::new(&sim_manager, &ack_channel.0, 2);
+    testbench.send(&[
+        (Some(0x1a), false),
+        (Some(0x12b), false),
+        (Some(0x3c), true),
+        (Some(0x4d), false),
+        (None, true),
+        (None, true),
+        (None, true),
+    ]);
+    let fifo = testbench.fifo.build().unwrap();
+
+    // (pop data, full, empty, almost full) after each clock tick
+    let expected = [
+        (0, false, false, true),
+        (0, true, false, true),
+        (0x1a, true, false, true),
+        (0x1a, true, false, true),
+        (0x2b, false, false, true),
+        (0x3c, false, true, false),
+        (0x3c, false, true, false),
+    ];
+    run_cycles(&sim_manager, vec![fifo.clone()], 7, |cycle| {
+        let actual = (
+            sim_manager.peek::<u64>("fifo.pop_data").unwrap(),
+            sim_manager.peek::<bool>("fifo.full").unwrap(),
+            sim_manager.peek::<bool>("fifo.empty").unwrap(),
+            sim_manager.peek::<bool>("fifo.almost_full").unwrap(),
+        );
+        assert_eq!(actual, expected[cycle as usize - 1], "cycle {}", cycle);
+    });
+    assert_eq!(fifo.lock().unwrap().get_len(), 0);
+
+    // pushing while full and popping while empty
+    let violations = sim_manager.get_protocol_violations().unwrap();
+    assert_eq!(
+        violations
+            .iter()
+            .map(|violation| (violation.checker.as_str(), violation.cycle))
+            .collect::<Vec<_>>(),
+        vec![("fifo", 4), ("fifo", 7)]
+    );
+}
+
+#[test]
+fn fifo_reset_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut testbench = Testbench::new(&sim_manager, &ack_channel.0, 2);
+    testbench.send(&[(Some(0x1a), false), (Some(0x2b), false)]);
+    let fifo = testbench.fifo.build().unwrap();
+
+    // the flags follow the FIFO emptied by a reset after the second clock tick, before the next one
+    let get_flags = || {
+        (
+            sim_manager.peek::<bool>("fifo.full").unwrap(),
+            sim_manager.peek::<bool>("fifo.empty").unwrap(),
+        )
+    };
+    run_cycles(&sim_manager, vec![fifo.clone()], 3, |cycle| match cycle {
+        2 => {
+            assert_eq!(get_flags(), (true, false));
+            fifo.lock().unwrap().reset();
+            assert_eq!(get_flags(), (false, true));
+        }
+        3 => assert_eq!(get_flags(), (false, true)),
+        _ => {}
+    });
+    assert_eq!(fifo.lock().unwrap().get_len(), 0);
+}
+
+#[test]
+fn first_word_fall_through_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut testbench = Testbench::new(&sim_manager, &ack_channel.0, 4);
+    testbench.send(&[
+        (Some(5), false),
+        (Some(6), true),
+        (None, true),
+        (Some(7), false),
+    ]);
+    let fifo = testbench
+        .fifo
+        .with_first_word_fall_through()
+        .build()
+        .unwrap();
+
+    // the head is on pop data as soon as it is pushed
+    let expected = [(5, false), (6, false), (6, true), (7, false)];
+    run_cycles(&sim_manager, vec![fifo], 4, |cycle| {
+        let actual = (
+            sim_manager.peek::<u64>("fifo.pop_data").unwrap(),
+            sim_manager.peek::<bool>("fifo.empty").unwrap(),
+        );
+        assert_eq!(actual, expected[cycle as usize - 1], "cycle {}", cycle);
+    });
+    sim_manager.check_protocols().unwrap();
+}
+
+#[test]
+fn dual_clock_fifo_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    // the read clock ticks every other cycle
+    let mut testbench = Testbench::new(&sim_manager, &ack_channel.0, 2);
+    testbench.send(&[
+        (Some(0xa), false),
+        (Some(0xb), false),
+        (Some(0xc), false),
+        (None, false),
+        (None, false),
+        (None, true),
+        (None, false),
+        (None, true),
+        (None, false),
+    ]);
+    let fifo = testbench
+        .fifo
+        .with_clock_domains(ClockDomain::new(1, 0), ClockDomain::new(2, 0), 2)
+        .build()
+        .unwrap();
+
+    // a push is seen as not empty two read clock ticks after the read side samples it,
+    // a pop as not full two write clock ticks after the write side samples it
+    let expected = [
+        (0, false, true),
+        (0, true, true),
+        (0, true, true),
+        (0, true, false),
+        (0, true, false),
+        (0xa, true, false),
+        (0xa, true, false),
+        (0xb, false, true),
+        (0xb, false, true),
+    ];
+    run_cycles(&sim_manager, vec![fifo], 9, |cycle| {
+        let actual = (
+            sim_manager.peek::<u64>("fifo.pop_data").unwrap(),
+            sim_manager.peek::<bool>("fifo.full").unwrap(),
+            sim_manager.peek::<bool>("fifo.empty").unwrap(),
+        );
+        assert_eq!(actual, expected[cycle as usize - 1], "cycle {}", cycle);
+    });
+
+    // 0xc is dropped
+    let violations = sim_manager.get_protocol_violations().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].cycle, 3);
+}
+
+#[test]
+fn fifo_config_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let new_fifo = |width, depth| {
+        Fifo::new(
+            0,
+            sim_manager.clone(),
+            "fifo",
+            ack_channel.0.clone(),
+            width,
+            depth,
+        )
+    };
+
+    assert!(new_fifo(0, 4).build().is_err());
+    assert!(new_fifo(8, 0).build().is_err());
+    assert!(new_fifo(8, 4)
+        .with_clock_domains(ClockDomain::new(0, 0), ClockDomain::default(), 2)
+        .build()
+        .is_err());
+    assert!(new_fifo(8, 4).build().is_ok());
+}
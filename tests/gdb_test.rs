@@ -0,0 +1,134 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::gdb::GdbServer;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use simple_component::simple_cpu::SimpleCpu;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+/// Sends a packet the way gdb does, waiting for its acknowledgement
+fn send(stream: &mut TcpStream, data: &[u8]) {
+    let checksum = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    stream.write_all(b"$").unwrap();
+    stream.write_all(data).unwrap();
+    write!(stream, "#{:02x}", checksum).unwrap();
+
+    let mut byte = [0u8];
+    stream.read_exact(&mut byte).unwrap();
+    assert_eq!(
+        byte[0],
+        b'+',
+        "packet \"{}\" was not acknowledged",
+        String::from_utf8_lossy(data)
+    );
+}
+
+/// Reads a reply without acknowledging it
+fn receive(stream: &mut TcpStream) -> String {
+    let mut reply = Vec::new();
+    let mut byte = [0u8];
+    stream.read_exact(&mut byte).unwrap();
+    assert_eq!(byte[0], b'$');
+    loop {
+        stream.read_exact(&mut byte).unwrap();
+        if byte[0] == b'#' {
+            break;
+        }
+        reply.push(byte[0]);
+    }
+    let mut checksum = [0u8; 2];
+    stream.read_exact(&mut checksum).unwrap();
+    String::from_utf8(reply).unwrap()
+}
+
+/// Sends a packet and returns the reply
+fn request(stream: &mut TcpStream, data: &str) -> String {
+    send(stream, data.as_bytes());
+    let reply = receive(stream);
+    stream.write_all(b"+").unwrap();
+    reply
+}
+
+#[test]
+fn gdb_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let cpu = SimpleCpu::new(0, sim_manager.clone(), 10, ack_channel.0.clone());
+
+    let sim_dispatchers = vec![SimDispatcher::new(
+        Arc::downgrade(&sim_manager),
+        vec![cpu.clone()],
+    )];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server_handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        GdbServer::new(sim_manager, cpu).serve(stream)
+    });
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    assert_eq!(
+        request(&mut stream, "qSupported:swbreak+"),
+        "PacketSize=4000"
+    );
+    assert_eq!(request(&mut stream, "?"), "S05");
+    assert_eq!(request(&mut stream, "g"), "00000000".repeat(5));
+
+    // registers and memory
+    assert_eq!(request(&mut stream, "P1=05000000"), "OK");
+    assert_eq!(request(&mut stream, "p1"), "05000000");
+    assert_eq!(request(&mut stream, "P2=10000000"), "OK");
+    assert_eq!(request(&mut stream, "M10,2:abcd"), "OK");
+    assert_eq!(request(&mut stream, "m10,2"), "abcd");
+    assert_eq!(request(&mut stream, "m100,4"), "E01");
+    assert_eq!(request(&mut stream, "vMustReplyEmpty"), "");
+
+    // a payload that is not ASCII is an error, not a panic of the stub
+    send(&mut stream, b"M10,2:\xc3\xa9ab");
+    assert_eq!(receive(&mut stream), "E01");
+    stream.write_all(b"+").unwrap();
+
+    // a reply is sent again until it is acknowledged
+    send(&mut stream, b"p1");
+    assert_eq!(receive(&mut stream), "05000000");
+    stream.write_all(b"-").unwrap();
+    assert_eq!(receive(&mut stream), "05000000");
+    stream.write_all(b"+").unwrap();
+
+    // a single step retires exactly one instruction
+    assert_eq!(request(&mut stream, "s"), "S05");
+    assert_eq!(request(&mut stream, "p4"), "04000000");
+    assert_eq!(request(&mut stream, "p1"), "06000000");
+    assert_eq!(request(&mut stream, "m10,1"), "06");
+
+    // continue until the breakpoint
+    assert_eq!(request(&mut stream, "Z0,14,4"), "OK");
+    assert_eq!(request(&mut stream, "c"), "S05");
+    assert_eq!(request(&mut stream, "p4"), "14000000");
+    assert_eq!(request(&mut stream, "p1"), "0a000000");
+
+    // continue until the end of the simulation
+    assert_eq!(request(&mut stream, "z0,14,4"), "OK");
+    assert_eq!(request(&mut stream, "c"), "W00");
+    assert_eq!(request(&mut stream, "?"), "W00");
+    assert_eq!(request(&mut stream, "m10,1"), "0f");
+    assert_eq!(request(&mut stream, "D"), "OK");
+
+    server_handle.join().unwrap().unwrap();
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}
@@ -0,0 +1,239 @@
+use crossbeam_channel::unbounded;
+use rsim_core::components::image::{ImageSegment, MemoryImage, PlacedSegment, SegmentData};
+use rsim_core::components::memory::Memory;
+use rsim_core::error::SimError;
+use rsim_core::sim_manager::SimManager;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}_{}", std::process::id(), name))
+}
+
+fn placed(name: &str, address: u64, start: u64, end: u64) -> PlacedSegment {
+    PlacedSegment {
+        name: name.to_string(),
+        address,
+        start,
+        end,
+    }
+}
+
+/// A little endian ELF32 file with a loadable segment holding `.text` and `.bss` at `0x8000_0000`
+fn build_elf() -> Vec<u8> {
+    let text = [0x13, 0x05, 0x10, 0x00, 0x6f, 0x00];
+    let string_table = b"\0.text\0.bss\0.shstrtab\0";
+    let text_offset = 52 + 32;
+    let string_table_offset = text_offset + text.len();
+    let section_offset = string_table_offset + string_table.len();
+
+    let mut elf = Vec::new();
+    let u16 = |elf: &mut Vec<u8>, value: u16| elf.extend_from_slice(&value.to_le_bytes());
+    let u32 = |elf: &mut Vec<u8>, value: u32| elf.extend_from_slice(&value.to_le_bytes());
+    elf.extend_from_slice(b"\x7fELF\x01\x01\x01\0\0\0\0\0\0\0\0\0");
+    u16(&mut elf, 2); // executable
+    u16(&mut elf, 0xf3); // risc-v
+    u32(&mut elf, 1);
+    u32(&mut elf, 0x8000_0004); // entry
+    u32(&mut elf, 52); // program headers
+    u32(&mut elf, section_offset as u32);
+    u32(&mut elf, 0);
+    u16(&mut elf, 52);
+    u16(&mut elf, 32);
+    u16(&mut elf, 1);
+    u16(&mut elf, 40);
+    u16(&mut elf, 4);
+    u16(&mut elf, 3); // .shstrtab
+
+    // PT_LOAD, 6 bytes in the file and 8 in memory
+    for value in [1, text_offset as u32, 0x8000_0000, 0x8000_0000, 6, 8, 5, 4] {
+        u32(&mut elf, value);
+    }
+    elf.extend_from_slice(&text);
+    elf.extend_from_slice(string_table);
+
+    // (name, type, flags, address, offset, size)
+    let sections = [
+        (0, 0, 0, 0, 0, 0),
+        (1, 1, 6, 0x8000_0000, text_offset as u32, 6),
+        (7, 8, 3, 0x8000_0006, 0, 2),
+        (
+            12,
+            3,
+            0,
+            0,
+            string_table_offset as u32,
+            string_table.len() as u32,
+        ),
+    ];
+    for (name, section_type, flags, address, offset, size) in sections {
+        for value in [name, section_type, flags, address, offset, size, 0, 0, 1, 0] {
+            u32(&mut elf, value);
+        }
+    }
+    elf
+}
+
+#[test]
+fn elf_image_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let path = temp_path("program.elf");
+    fs::write(&path, build_elf()).unwrap();
+    let image = MemoryImage::from_elf(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(image.get_entry(), Some(0x8000_0004));
+    assert_eq!(
+        image.get_segments(),
+        &[ImageSegment {
+            name: ".text .bss".to_string(),
+            address: 0x8000_0000,
+            data: SegmentData::ZeroFilled {
+                bytes: vec![0x13, 0x05, 0x10, 0x00, 0x6f, 0x00],
+                len: 8,
+            },
+        }]
+    );
+
+    let memory = Memory::new(0, sim_manager.clone(), "memory", ack_channel.0, 32, 4)
+        .with_image(image.clone(), 0x7fff_fffc)
+        .build()
+        .unwrap();
+    let memory = memory.lock().unwrap();
+    assert_eq!(
+        memory.get_placed_segments(),
+        &[placed(".text .bss", 0x8000_0000, 1, 3)]
+    );
+    let backdoor = memory.get_backdoor();
+    assert_eq!(backdoor.dump().unwrap(), vec![0, 0x0010_0513, 0x006f, 0]);
+
+    // the segment does not fit below its address nor past the end
+    assert!(matches!(
+        backdoor.load_image(&image, 0x8000_0004),
+        Err(SimError::ImageError(_))
+    ));
+    assert!(matches!(
+        backdoor.load_image(&image, 0x7fff_fff4),
+        Err(SimError::ImageError(_))
+    ));
+
+    // the zeros of a segment are not allocated before it is known to fit
+    let mut huge = build_elf();
+    huge[72..76].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&path, huge).unwrap();
+    let image = MemoryImage::from_elf(&path).unwrap();
+    assert_eq!(image.get_segments()[0].len(), u32::MAX as u64);
+    assert!(matches!(
+        backdoor.load_image(&image, 0x7fff_fffc),
+        Err(SimError::ImageError(_))
+    ));
+
+    let mut truncated = build_elf();
+    truncated.truncate(90);
+    fs::write(&path, truncated).unwrap();
+    assert!(MemoryImage::from_elf(&path).is_err());
+    fs::write(&path, b"not an elf").unwrap();
+    assert!(MemoryImage::from_elf(&path).is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn intel_hex_image_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let path = temp_path("program.hex");
+    fs::write(
+        &path,
+        ":020000040001F9\n\
+         :0300000011223397\n\
+         :02000300445562\n\
+         :010010007778\n\
+         :0400000500010004F2\n\
+         :00000001FF\n",
+    )
+    .unwrap();
+    let image = MemoryImage::from_intel_hex(&path).unwrap();
+    fs::write(&path, ":010010007779\n:00000001FF\n").unwrap();
+    let bad_checksum = MemoryImage::from_intel_hex(&path);
+    fs::write(&path, ":010010007778\n").unwrap();
+    let no_end = MemoryImage::from_intel_hex(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(matches!(bad_checksum, Err(SimError::ImageError(_))));
+    assert!(matches!(no_end, Err(SimError::ImageError(_))));
+
+    // contiguous records form a single segment
+    assert_eq!(image.get_entry(), Some(0x0001_0004));
+    assert_eq!(image.get_segments().len(), 2);
+
+    let memory = Memory::new(0, sim_manager.clone(), "memory", ack_channel.0, 16, 16)
+        .with_image(image, 0x0001_0000)
+        .build()
+        .unwrap();
+    let memory = memory.lock().unwrap();
+    assert_eq!(
+        memory.get_placed_segments(),
+        &[
+            placed("segment0", 0x0001_0000, 0, 3),
+            placed("segment1", 0x0001_0010, 8, 9),
+        ]
+    );
+    assert_eq!(
+        memory.get_backdoor().dump().unwrap(),
+        vec![0x2211, 0x4433, 0x55, 0, 0, 0, 0, 0, 0x77, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn readmemh_and_binary_image_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let backdoor = Memory::new(0, sim_manager.clone(), "memory", ack_channel.0, 12, 8)
+        .build()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .get_backdoor();
+
+    let path = temp_path("words.mem");
+    fs::write(&path, "@1 abc def\n@6 123\n").unwrap();
+    let words = MemoryImage::from_readmemh(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        backdoor.load_image(&words, 0).unwrap(),
+        vec![placed("segment0", 1, 1, 3), placed("segment1", 6, 6, 7)]
+    );
+
+    let path = temp_path("data.bin");
+    fs::write(&path, [0x34, 0xf2, 0x78]).unwrap();
+    let binary = MemoryImage::from_binary(&path, 0x108).unwrap();
+    fs::remove_file(&path).unwrap();
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+    assert_eq!(
+        backdoor.load_image(&binary, 0x100).unwrap(),
+        vec![placed(&name, 0x108, 4, 6)]
+    );
+    assert_eq!(
+        backdoor.dump().unwrap(),
+        vec![0, 0xabc, 0xdef, 0, 0x234, 0x078, 0x123, 0]
+    );
+
+    // nothing is written when segments overlap
+    let overlapping = MemoryImage::new()
+        .with_segment(ImageSegment {
+            name: "first".to_string(),
+            address: 0,
+            data: SegmentData::Bytes(vec![1, 2, 3]),
+        })
+        .with_segment(ImageSegment {
+            name: "second".to_string(),
+            address: 2,
+            data: SegmentData::Bytes(vec![4]),
+        });
+    assert!(matches!(
+        backdoor.load_image(&overlapping, 0),
+        Err(SimError::ImageError(_))
+    ));
+    assert_eq!(backdoor.read(0).unwrap(), 0);
+}
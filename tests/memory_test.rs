@@ -0,0 +1,247 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::component::Component;
+use rsim_core::components::memory::{Memory, ReadMode};
+use rsim_core::components::register::Register;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::{ComponentId, Cycle};
+use simple_component::testbench::{new_tx, run_cycles};
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn sram_read_mode_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut write_address = new_tx(&sim_manager, &ack_channel.0);
+    let mut write_data = new_tx(&sim_manager, &ack_channel.0);
+    let mut write_enable = new_tx(&sim_manager, &ack_channel.0);
+    let mut read_address = new_tx(&sim_manager, &ack_channel.0);
+
+    // the same accesses on a read-first and a write-first SRAM, their read data sampled by registers
+    let mut components: Vec<Arc<Mutex<dyn Component>>> = Vec::new();
+    for (index, (name, read_mode)) in [
+        ("read_first", ReadMode::ReadFirst),
+        ("write_first", ReadMode::WriteFirst),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut read_data = new_tx(&sim_manager, &ack_channel.0);
+        let register = Register::new(
+            2 * index as ComponentId + 1,
+            sim_manager.clone(),
+            &format!("{}_data", name),
+            read_data.add_rx(),
+            new_tx::<u64>(&sim_manager, &ack_channel.0),
+            ack_channel.0.clone(),
+        )
+        .build()
+        .unwrap();
+        let sram = Memory::sram(
+            2 * index as ComponentId,
+            sim_manager.clone(),
+            name,
+            ack_channel.0.clone(),
+            8,
+            16,
+        )
+        .with_write_port(
+            write_address.add_rx(),
+            write_data.add_rx(),
+            write_enable.add_rx(),
+        )
+        .with_read_port(read_address.add_rx(), read_data)
+        .with_read_mode(read_mode)
+        .build()
+        .unwrap();
+        components.push(sram);
+        components.push(register);
+    }
+
+    // (write address, write data, write enable, read address) due at each clock tick from the first one
+    let inputs = [
+        (3, 0xaa, true, 3),
+        (4, 0x1bb, true, 3),
+        (3, 0x11, false, 4),
+        (0, 0, false, 3),
+    ];
+    for (index, (address, data, enable, read)) in inputs.into_iter().enumerate() {
+        let cycle = index as Cycle + 1;
+        write_address.send(address, cycle);
+        write_data.send(data, cycle);
+        write_enable.send(enable, cycle);
+        read_address.send(read, cycle);
+    }
+
+    // the data read at each clock tick, sampled at the next one
+    let expected = [
+        (0, 0),
+        (0, 0),
+        (0, 0xaa),
+        (0xaa, 0xaa),
+        (0xbb, 0xbb),
+        (0xaa, 0xaa),
+    ];
+    run_cycles(&sim_manager, components, 5, |cycle| {
+        let actual = (
+            sim_manager.peek::<u64>("read_first_data.output").unwrap(),
+            sim_manager.peek::<u64>("write_first_data.output").unwrap(),
+        );
+        assert_eq!(actual, expected[cycle as usize], "cycle {}", cycle);
+    });
+}
+
+#[test]
+fn register_file_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut write_address = new_tx(&sim_manager, &ack_channel.0);
+    let mut write_data = new_tx(&sim_manager, &ack_channel.0);
+    let mut write_enable = new_tx(&sim_manager, &ack_channel.0);
+    let mut read_addresses: Vec<Tx<u64>> = (0..2)
+        .map(|_| new_tx(&sim_manager, &ack_channel.0))
+        .collect();
+    let mut register_file =
+        Memory::register_file(0, sim_manager.clone(), "regs", ack_channel.0.clone(), 32, 8)
+            .with_write_port(
+                write_address.add_rx(),
+                write_data.add_rx(),
+                write_enable.add_rx(),
+            );
+    for read_address in read_addresses.iter_mut() {
+        register_file = register_file
+            .with_read_port(read_address.add_rx(), new_tx(&sim_manager, &ack_channel.0));
+    }
+    let register_file = register_file.build().unwrap();
+    let backdoor = register_file.lock().unwrap().get_backdoor();
+    backdoor.write(6, 0x1_2345_6789).unwrap();
+
+    // (write address, write data, write enable, read addresses) due at each clock tick from the first one
+    let inputs = [
+        (1, 5, true, [1, 6]),
+        (2, 7, true, [1, 2]),
+        (1, 9, false, [2, 1]),
+        (6, 3, true, [6, 7]),
+    ];
+    for (index, (address, data, enable, reads)) in inputs.into_iter().enumerate() {
+        let cycle = index as Cycle + 1;
+        write_address.send(address, cycle);
+        write_data.send(data, cycle);
+        write_enable.send(enable, cycle);
+        for (read_address, read) in read_addresses.iter_mut().zip(reads) {
+            read_address.send(read, cycle);
+        }
+    }
+
+    // reads see the writes of the same clock tick
+    let expected = [(5, 0x2345_6789), (5, 7), (7, 5), (3, 0)];
+    run_cycles(&sim_manager, vec![register_file], 4, |cycle| {
+        let actual = (
+            sim_manager.peek::<u64>("regs.read0_data").unwrap(),
+            sim_manager.peek::<u64>("regs.read1_data").unwrap(),
+        );
+        assert_eq!(actual, expected[cycle as usize - 1], "cycle {}", cycle);
+    });
+    assert_eq!(backdoor.dump().unwrap(), vec![0, 5, 7, 0, 0, 0, 3, 0]);
+}
+
+#[test]
+fn rom_init_file_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let path = std::env::temp_dir().join(format!("rom_{}.hex", std::process::id()));
+    fs::write(&path, "// boot code\n@2\nde ad_be // two words\nef\n").unwrap();
+
+    let mut address = new_tx(&sim_manager, &ack_channel.0);
+    let mut data = new_tx(&sim_manager, &ack_channel.0);
+    let register = Register::new(
+        1,
+        sim_manager.clone(),
+        "data",
+        data.add_rx(),
+        new_tx::<u64>(&sim_manager, &ack_channel.0),
+        ack_channel.0.clone(),
+    )
+    .build()
+    .unwrap();
+    let rom = Memory::rom(0, sim_manager.clone(), "rom", ack_channel.0.clone(), 16, 8)
+        .with_read_port(address.add_rx(), data)
+        .with_read_latency(2)
+        .with_init_file(&path)
+        .build()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        rom.lock().unwrap().get_backdoor().dump().unwrap(),
+        vec![0, 0, 0xde, 0xadbe, 0xef, 0, 0, 0]
+    );
+
+    for (index, read) in [2, 3, 4, 0].into_iter().enumerate() {
+        address.send(read, index as Cycle + 1);
+    }
+
+    // the data read at each clock tick, sampled two ticks later
+    let expected = [0, 0, 0xde, 0xadbe, 0xef, 0];
+    run_cycles(&sim_manager, vec![rom, register], 6, |cycle| {
+        assert_eq!(
+            sim_manager.peek::<u64>("data.output").unwrap(),
+            expected[cycle as usize - 1],
+            "cycle {}",
+            cycle
+        );
+    });
+}
+
+#[test]
+fn memory_config_test() {
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let new_memory = |width, depth| {
+        Memory::new(
+            0,
+            sim_manager.clone(),
+            "memory",
+            ack_channel.0.clone(),
+            width,
+            depth,
+        )
+    };
+
+    assert!(new_memory(0, 4).build().is_err());
+    assert!(new_memory(8, 0).build().is_err());
+    assert!(
+        Memory::rom(0, sim_manager.clone(), "rom", ack_channel.0.clone(), 8, 4)
+            .with_write_port(
+                new_tx(&sim_manager, &ack_channel.0).add_rx(),
+                new_tx(&sim_manager, &ack_channel.0).add_rx(),
+                new_tx(&sim_manager, &ack_channel.0).add_rx()
+            )
+            .build()
+            .is_err()
+    );
+
+    let path = std::env::temp_dir().join(format!("memory_{}.hex", std::process::id()));
+    fs::write(&path, "01 02\n@3 04 05\n").unwrap();
+    assert!(new_memory(8, 4).with_init_file(&path).build().is_err());
+    fs::write(&path, "01 xy\n").unwrap();
+    assert!(new_memory(8, 4).with_init_file(&path).build().is_err());
+    fs::remove_file(&path).unwrap();
+
+    let backdoor = new_memory(8, 4)
+        .build()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .get_backdoor();
+    backdoor.load(1, &[0x101, 0x02]).unwrap();
+    assert_eq!(backdoor.read(1).unwrap(), 0x01);
+    assert!(backdoor.read(4).is_err());
+    assert!(backdoor.load(3, &[1, 2]).is_err());
+    assert_eq!(backdoor.dump().unwrap(), vec![0, 0x01, 0x02, 0]);
+}
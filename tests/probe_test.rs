@@ -0,0 +1,94 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_event::SimpleData;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use simple_component::simple_sender::SimpleSender;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn probe_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut sender_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = sender_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let link = SimpleLink::new(
+        0,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let sender = SimpleSender::new(
+        1,
+        sim_manager.clone(),
+        20,
+        sender_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![sender]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![receiver]),
+    ];
+
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let forced = SimpleData::new(1000, false);
+    sim_manager.force("link.input", forced, 15).unwrap();
+    sim_manager.release("link.input", 20).unwrap();
+    assert!(sim_manager.force("link.output", forced, 15).is_err());
+    assert!(sim_manager.force("link.input", 0u32, 15).is_err());
+    assert!(sim_manager.release("link.nothing", 15).is_err());
+    assert!(sim_manager.peek::<u32>("receiver.input").is_err());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    // packet n leaves the sender at cycle n + 1 and reaches the receiver at cycle n + 11
+    for _ in 0..25 {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+        let cycle = sim_manager.get_curr_cycle();
+        let value = sim_manager.peek::<SimpleData>("receiver.input").unwrap();
+        match cycle {
+            11..15 | 20.. => assert_eq!(value.packet_id, cycle - 11),
+            15..20 => assert_eq!(value, forced),
+            _ => {}
+        }
+    }
+    assert!(sim_manager.force("link.input", forced, 3).is_err());
+
+    sim_manager.run().unwrap();
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+
+    assert_eq!(
+        sim_manager.peek::<SimpleData>("receiver.input").unwrap(),
+        SimpleData::new(19, true)
+    );
+    assert_eq!(
+        sim_manager.peek_as_string("sender.output").unwrap(),
+        format!("{:?}", SimpleData::new(19, true))
+    );
+}
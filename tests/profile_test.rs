@@ -0,0 +1,87 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn profile_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        20,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    sim_manager.enable_profiling().unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+
+    let report = sim_manager.get_profile_report().unwrap();
+    assert_eq!(report.components.len(), 3);
+    for pair in report.components.windows(2) {
+        assert!(pair[0].get_total_time() >= pair[1].get_total_time());
+    }
+
+    let counter = report.get_component(0).unwrap();
+    assert_eq!(counter.name.as_deref(), Some("counter"));
+    assert_eq!((counter.events_received, counter.events_sent), (0, 20));
+    assert!(counter.clock_time > Duration::ZERO);
+    assert_eq!(counter.comb_time, Duration::ZERO);
+
+    let link = report.get_component(1).unwrap();
+    assert_eq!((link.events_received, link.events_sent), (20, 20));
+    assert!(link.comb_time > Duration::ZERO);
+    assert_eq!(link.clock_time, Duration::ZERO);
+
+    let receiver = report.get_component(2).unwrap();
+    assert_eq!((receiver.events_received, receiver.events_sent), (20, 0));
+
+    let text = report.to_string();
+    assert!(text.lines().count() == 4);
+    assert!(text.contains("counter"));
+}
@@ -0,0 +1,119 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::trace::{load_trace, EventPayload};
+use rsim_core::tx::Tx;
+use rsim_core::types::Cycle;
+use simple_component::simple_event::SimpleData;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use simple_component::simple_sender::SimpleSender;
+use std::sync::Arc;
+use std::thread;
+
+/// Steps until the end of the simulation, recording what the receiver sees every cycle
+fn run(
+    sim_manager: &Arc<SimManager>,
+    sim_dispatchers: Vec<Arc<SimDispatcher>>,
+) -> Vec<(Cycle, SimpleData)> {
+    let thread_handlers: Vec<_> = sim_dispatchers
+        .into_iter()
+        .map(|sim_dispatcher| thread::spawn(move || sim_dispatcher.run()))
+        .collect();
+    let mut trace = Vec::new();
+    while !sim_manager.sim_can_end() {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+        trace.push((
+            sim_manager.get_curr_cycle(),
+            sim_manager.peek("receiver.input").unwrap(),
+        ));
+    }
+    thread_handlers.into_iter().for_each(|h| h.join().unwrap());
+    trace
+}
+
+#[test]
+fn record_replay_test() {
+    let path = std::env::temp_dir().join("rsim_record_replay_test.jsonl");
+
+    // full netlist: sender -> link -> receiver
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let mut sender_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = sender_output.add_rx();
+    let receiver_input = link_output.add_rx();
+    let link = SimpleLink::new(
+        0,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let sender = SimpleSender::new(
+        1,
+        sim_manager.clone(),
+        30,
+        sender_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![sender]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    sim_manager.start_recording(&path).unwrap();
+    let expected = run(&sim_manager, sim_dispatchers);
+    sim_manager.stop_recording().unwrap();
+
+    let trace = load_trace(&path).unwrap();
+    assert_eq!(trace.len(), 60);
+    assert_eq!(trace[0].port, "link.input");
+    assert_eq!(trace[0].scheduled_time, 11);
+    assert_eq!(
+        trace[0].payload,
+        EventPayload::Value(serde_json::to_value(SimpleData::new(0, false)).unwrap())
+    );
+
+    // isolated netlist: the link is fed from the trace instead of the sender
+    let ack_channel = unbounded();
+    let sim_manager = SimManager::new(ack_channel.1);
+    let mut stub_output = Tx::<SimpleData>::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = stub_output.add_rx();
+    let receiver_input = link_output.add_rx();
+    let link = SimpleLink::new(
+        0,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+    let sim_dispatchers = vec![SimDispatcher::new(
+        Arc::downgrade(&sim_manager),
+        vec![link, receiver],
+    )];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    assert_eq!(sim_manager.replay(&trace, &["link"]).unwrap(), 30);
+    let replayed = run(&sim_manager, sim_dispatchers);
+
+    assert_eq!(expected, replayed);
+    let _ = std::fs::remove_file(path);
+}
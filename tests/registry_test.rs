@@ -0,0 +1,98 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::registry::PortDirection;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_event::SimpleData;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use simple_component::simple_sender::SimpleSender;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn registry_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut sender_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = sender_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let link = SimpleLink::new(
+        0,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let sender = SimpleSender::new(
+        1,
+        sim_manager.clone(),
+        10,
+        sender_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![sender]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![receiver]),
+    ];
+
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    {
+        let registry = sim_manager.get_registry();
+        let names: Vec<String> = registry
+            .get_components()
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["link", "receiver", "sender"]);
+
+        let link_info = registry.get_component("link").unwrap();
+        assert_eq!(link_info.component_id, 0);
+        assert_eq!(link_info.ports.len(), 2);
+        assert_eq!(link_info.ports[0].name, "link.input");
+        assert_eq!(link_info.ports[0].direction, PortDirection::Input);
+        assert_eq!(link_info.ports[1].name, "link.output");
+        assert_eq!(link_info.ports[1].direction, PortDirection::Output);
+        assert!(link_info.ports[0].type_name.ends_with("SimpleData"));
+
+        assert!(registry.get_component_handle(2).is_some());
+        assert!(registry.get_port("sender.input").is_none());
+    }
+    assert!(sim_manager.register_component(3, "link").is_err());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+    sim_manager.run().unwrap();
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+
+    let registry = sim_manager.get_registry();
+    let last = *registry
+        .get_port_value_as_any("receiver.input")
+        .unwrap()
+        .downcast::<SimpleData>()
+        .unwrap();
+    assert_eq!(last, SimpleData::new(9, true));
+    assert_eq!(
+        registry.get_port_value_as_string("sender.output").unwrap(),
+        format!("{:?}", SimpleData::new(9, true))
+    );
+}
@@ -0,0 +1,189 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::coverage::{Covergroup, Coverpoint};
+use rsim_core::scoreboard::{Scoreboard, ScoreboardMode};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::Cycle;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_event::SimpleData;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn reverse_step_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        40,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    sim_manager.enable_snapshots(8, 16).unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    let sample = |sim_manager: &SimManager| {
+        (
+            sim_manager.peek::<SimpleData>("counter.output").unwrap(),
+            sim_manager.peek::<SimpleData>("receiver.input").unwrap(),
+        )
+    };
+
+    let mut expected = vec![sample(&sim_manager)];
+    for _ in 0..30 {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+        expected.push(sample(&sim_manager));
+    }
+    assert_eq!(
+        sim_manager.get_snapshot_cycles().unwrap(),
+        vec![0, 8, 16, 24]
+    );
+
+    sim_manager.step_back(7).unwrap();
+    assert_eq!(sim_manager.get_curr_cycle(), 23);
+    assert_eq!(sample(&sim_manager), expected[23]);
+
+    sim_manager.goto_cycle(5).unwrap();
+    assert_eq!(sim_manager.get_curr_cycle(), 5);
+    assert_eq!(sample(&sim_manager), expected[5]);
+    assert_eq!(sim_manager.get_snapshot_cycles().unwrap(), vec![0]);
+
+    sim_manager.goto_cycle(30).unwrap();
+    assert_eq!(sample(&sim_manager), expected[30]);
+    sim_manager.step_back(0).unwrap();
+    assert_eq!(sample(&sim_manager), expected[30]);
+
+    sim_manager.run().unwrap();
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+
+    assert_eq!(
+        sim_manager.peek::<SimpleData>("receiver.input").unwrap(),
+        SimpleData::new(39, true)
+    );
+    assert!(sim_manager.step_back(1).is_err());
+}
+
+#[test]
+fn reverse_step_verification_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        40,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    sim_manager.enable_snapshots(8, 16).unwrap();
+
+    let mut packet_id: u128 = 0;
+    let scoreboard = Scoreboard::new("packets", ScoreboardMode::InOrder, move || {
+        packet_id += 1;
+        (packet_id <= 40).then(|| SimpleData::new(packet_id - 1, packet_id == 40))
+    });
+    sim_manager
+        .add_scoreboard("counter.output", scoreboard.clone())
+        .unwrap();
+    sim_manager
+        .add_covergroup(Covergroup::new(
+            "packets",
+            vec![Coverpoint::new("sent", "counter.output", Vec::new())],
+            Vec::new(),
+        ))
+        .unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    let mut expected = vec![sim_manager.get_coverage().unwrap()];
+    for _ in 0..30 {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+        expected.push(sim_manager.get_coverage().unwrap());
+    }
+
+    sim_manager.step_back(7).unwrap();
+    assert_eq!(sim_manager.get_coverage().unwrap(), expected[23]);
+    sim_manager.goto_cycle(30).unwrap();
+    assert_eq!(sim_manager.get_coverage().unwrap(), expected[30]);
+    sim_manager.step_back(20).unwrap();
+    assert_eq!(sim_manager.get_coverage().unwrap(), expected[10]);
+
+    sim_manager.run().unwrap();
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+
+    sim_manager.check_scoreboards().unwrap();
+    assert_eq!(scoreboard.get_matched(), 40);
+    let group = &sim_manager.get_coverage().unwrap().groups["packets"];
+    // every cycle is sampled once, the last one included
+    assert_eq!(group.samples as Cycle, sim_manager.get_curr_cycle() + 1);
+}
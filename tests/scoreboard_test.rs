@@ -0,0 +1,162 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::error::SimError;
+use rsim_core::scoreboard::{MismatchKind, Scoreboard, ScoreboardMode};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_event::SimpleData;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+
+const NUM_PACKETS: u128 = 20;
+
+/// The packets the counter is expected to send
+fn golden_model() -> impl FnMut() -> Option<SimpleData> + Send {
+    let mut packet_id = 0;
+    move || {
+        if packet_id == NUM_PACKETS {
+            return None;
+        }
+        packet_id += 1;
+        Some(SimpleData::new(packet_id - 1, packet_id == NUM_PACKETS))
+    }
+}
+
+/// Runs the counter, link and receiver with the scoreboards added by `add_scoreboards`
+fn run_with_scoreboards<F: FnOnce(&SimManager)>(add_scoreboards: F) -> Result<(), SimError> {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        NUM_PACKETS,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    add_scoreboards(&sim_manager);
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+    sim_manager.check_scoreboards()
+}
+
+#[test]
+fn scoreboard_test() {
+    let in_order = Scoreboard::new("in order", ScoreboardMode::InOrder, golden_model());
+    let mut reversed: Vec<SimpleData> = std::iter::from_fn(golden_model()).collect();
+    reversed.reverse();
+    let out_of_order =
+        Scoreboard::from_expected("out of order", ScoreboardMode::OutOfOrder, reversed);
+    run_with_scoreboards(|sim_manager| {
+        assert!(sim_manager
+            .add_scoreboard("receiver.input", in_order.clone())
+            .is_err());
+        assert!(sim_manager
+            .add_scoreboard(
+                "counter.output",
+                Scoreboard::from_expected("wrong type", ScoreboardMode::InOrder, vec![0u32]),
+            )
+            .is_err());
+        sim_manager
+            .add_scoreboard("counter.output", in_order.clone())
+            .unwrap();
+        assert!(sim_manager
+            .add_scoreboard("link.output", in_order.clone())
+            .is_err());
+        sim_manager
+            .add_scoreboard("link.output", out_of_order.clone())
+            .unwrap();
+    })
+    .unwrap();
+    assert_eq!(in_order.get_matched(), NUM_PACKETS as u64);
+    assert_eq!(out_of_order.get_matched(), NUM_PACKETS as u64);
+}
+
+#[test]
+fn scoreboard_mismatch_test() {
+    // the golden model differs from the counter on the 6th packet and expects one more packet
+    let mut expected: Vec<SimpleData> = std::iter::from_fn(golden_model()).collect();
+    expected[5].packet_id = 100;
+    expected.push(SimpleData::new(NUM_PACKETS, true));
+    let in_order = Scoreboard::from_expected("in order", ScoreboardMode::InOrder, expected.clone());
+    let out_of_order =
+        Scoreboard::from_expected("out of order", ScoreboardMode::OutOfOrder, expected);
+
+    let result = run_with_scoreboards(|sim_manager| {
+        sim_manager
+            .add_scoreboard("counter.output", in_order.clone())
+            .unwrap();
+        sim_manager
+            .add_scoreboard("link.output", out_of_order.clone())
+            .unwrap();
+    });
+    let Err(SimError::ScoreboardError(message)) = result else {
+        panic!("the scoreboards should fail");
+    };
+    assert!(message.starts_with("5 mismatches"));
+
+    let mismatches = in_order.get_mismatches();
+    assert_eq!(in_order.get_matched(), NUM_PACKETS as u64 - 1);
+    assert_eq!(mismatches.len(), 2);
+    assert_eq!(mismatches[0].port, "counter.output");
+    // the counter sends a packet at every cycle from cycle 1
+    assert_eq!(mismatches[0].cycle, 6);
+    assert_eq!(
+        mismatches[0].kind,
+        MismatchKind::Different {
+            expected: format!("{:?}", SimpleData::new(100, false)),
+            actual: format!("{:?}", SimpleData::new(5, false)),
+        }
+    );
+    assert!(matches!(mismatches[1].kind, MismatchKind::Missing { .. }));
+    assert!(message.contains(&mismatches[0].to_string()));
+
+    let mismatches = out_of_order.get_mismatches();
+    assert_eq!(out_of_order.get_matched(), NUM_PACKETS as u64 - 1);
+    assert_eq!(
+        mismatches[0].kind,
+        MismatchKind::Unexpected {
+            actual: format!("{:?}", SimpleData::new(5, false)),
+        }
+    );
+    assert_eq!(mismatches.len(), 3);
+}
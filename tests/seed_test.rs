@@ -0,0 +1,103 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::driver::RandomDriver;
+use rsim_core::random::{Distribution, Rng};
+use rsim_core::scoreboard::{Scoreboard, ScoreboardMode};
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::{SimManager, SEED_ENV_VAR};
+use rsim_core::tx::Tx;
+use std::sync::Arc;
+use std::thread;
+
+const NUM_VALUES: usize = 50;
+
+/// Runs two drivers without seeds of their own, checking they draw from the streams of the master seed
+fn run_drivers(seed: u64) {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+    sim_manager.set_seed(seed).unwrap();
+
+    let mut drivers = Vec::new();
+    for component_id in 0..2 {
+        let driver = RandomDriver::from_distribution(
+            component_id,
+            sim_manager.clone(),
+            &format!("driver{}", component_id),
+            Tx::new(sim_manager.clone(), ack_channel.0.clone()),
+            ack_channel.0.clone(),
+            Distribution::range(0u32..=u32::MAX).unwrap(),
+        )
+        .with_num_values(NUM_VALUES as u64)
+        .build()
+        .unwrap();
+        drivers.push(driver);
+    }
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![drivers[0].clone()]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![drivers[1].clone()]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    for component_id in 0..2 {
+        let distribution = Distribution::range(0u32..=u32::MAX).unwrap();
+        let mut rng = Rng::from_stream(seed, component_id);
+        let expected: Vec<u32> = (0..NUM_VALUES)
+            .map(|_| distribution.sample(&mut rng))
+            .collect();
+        let name = format!("driver{}", component_id);
+        sim_manager
+            .add_scoreboard(
+                &format!("{}.output", name),
+                Scoreboard::from_expected(&name, ScoreboardMode::InOrder, expected),
+            )
+            .unwrap();
+    }
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+    sim_manager.check_scoreboards().unwrap();
+
+    let stats = sim_manager.get_stats().unwrap();
+    assert_eq!(stats.seed, Some(seed));
+    assert!(stats.to_string().contains(&format!("seed: {}", seed)));
+    assert_eq!(sim_manager.get_coverage().unwrap().seeds, vec![seed]);
+}
+
+#[test]
+fn seed_test() {
+    run_drivers(7);
+    run_drivers(1234);
+
+    let sim_manager = SimManager::new(unbounded().1);
+    sim_manager.set_seed(7).unwrap();
+    assert_eq!(sim_manager.get_seed().unwrap(), 7);
+    assert_eq!(
+        sim_manager.get_rng(3).unwrap(),
+        sim_manager.get_rng(3).unwrap()
+    );
+    assert_ne!(
+        sim_manager.get_rng(3).unwrap(),
+        sim_manager.get_rng(4).unwrap()
+    );
+    assert_eq!(sim_manager.get_stats().unwrap().seed, Some(7));
+
+    std::env::set_var(SEED_ENV_VAR, "0x2a");
+    let sim_manager = SimManager::new(unbounded().1);
+    assert_eq!(sim_manager.get_stats().unwrap().seed, None);
+    assert_eq!(sim_manager.get_seed().unwrap(), 42);
+    assert_eq!(sim_manager.get_rng(3).unwrap(), Rng::from_stream(42, 3));
+
+    std::env::set_var(SEED_ENV_VAR, "not a seed");
+    assert!(SimManager::new(unbounded().1).get_seed().is_err());
+    std::env::remove_var(SEED_ENV_VAR);
+}
@@ -0,0 +1,363 @@
+use crossbeam_channel::{unbounded, Sender};
+use rsim_core::channel::{ReadyValidRx, ReadyValidTx};
+use rsim_core::component::Component;
+use rsim_core::credit::{CreditRx, CreditTx};
+use rsim_core::error::SimError;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::types::{ComponentId, EventId, Input, Output};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const NUM_VALUES: u32 = 20;
+
+/// The sending end of a link with flow control, a ready/valid channel or a credit-based link
+pub trait LinkTx: Send + Sync + 'static {
+    fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError>;
+
+    fn poll(&mut self);
+
+    /// Handles the clock tick, before `can_send` or `send`
+    fn tick(&mut self);
+
+    fn can_send(&self) -> bool;
+
+    fn send(&mut self, value: u32) -> Result<(), SimError>;
+
+    /// The values the other end has got or is sure to get
+    fn get_transfer_count(&self) -> u64;
+
+    fn reset(&mut self);
+}
+
+/// The receiving end of a link with flow control
+pub trait LinkRx: Send + Sync + 'static {
+    fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError>;
+
+    fn poll(&mut self);
+
+    /// Handles the clock tick, before `peek` or `take`
+    fn tick(&mut self);
+
+    fn peek(&self) -> Option<u32>;
+
+    fn take(&mut self) -> Option<u32>;
+
+    fn reset(&mut self);
+}
+
+impl LinkTx for ReadyValidTx<u32> {
+    fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        ReadyValidTx::register_ports(self, sim_manager, component_id, name)
+    }
+
+    fn poll(&mut self) {
+        ReadyValidTx::poll(self);
+    }
+
+    fn tick(&mut self) {
+        ReadyValidTx::tick(self);
+    }
+
+    fn can_send(&self) -> bool {
+        ReadyValidTx::can_send(self)
+    }
+
+    fn send(&mut self, value: u32) -> Result<(), SimError> {
+        ReadyValidTx::send(self, value)
+    }
+
+    fn get_transfer_count(&self) -> u64 {
+        ReadyValidTx::get_transfer_count(self)
+    }
+
+    fn reset(&mut self) {
+        ReadyValidTx::reset(self);
+    }
+}
+
+impl LinkRx for ReadyValidRx<u32> {
+    fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        ReadyValidRx::register_ports(self, sim_manager, component_id, name)
+    }
+
+    fn poll(&mut self) {
+        ReadyValidRx::poll(self);
+    }
+
+    fn tick(&mut self) {
+        ReadyValidRx::tick(self);
+    }
+
+    fn peek(&self) -> Option<u32> {
+        ReadyValidRx::peek(self)
+    }
+
+    fn take(&mut self) -> Option<u32> {
+        ReadyValidRx::take(self)
+    }
+
+    fn reset(&mut self) {
+        ReadyValidRx::reset(self);
+    }
+}
+
+impl LinkTx for CreditTx<u32> {
+    fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        CreditTx::register_ports(self, sim_manager, component_id, name)
+    }
+
+    fn poll(&mut self) {
+        CreditTx::poll(self);
+    }
+
+    fn tick(&mut self) {
+        // the credits due in the cycle are received by polling again
+        CreditTx::poll(self);
+    }
+
+    fn can_send(&self) -> bool {
+        CreditTx::can_send(self)
+    }
+
+    fn send(&mut self, value: u32) -> Result<(), SimError> {
+        CreditTx::send(self, value)
+    }
+
+    fn get_transfer_count(&self) -> u64 {
+        CreditTx::get_sent_count(self)
+    }
+
+    fn reset(&mut self) {
+        CreditTx::reset(self);
+    }
+}
+
+impl LinkRx for CreditRx<u32> {
+    fn register_ports(
+        &self,
+        sim_manager: &SimManager,
+        component_id: ComponentId,
+        name: &str,
+    ) -> Result<(), SimError> {
+        CreditRx::register_ports(self, sim_manager, component_id, name)
+    }
+
+    fn poll(&mut self) {
+        CreditRx::poll(self);
+    }
+
+    fn tick(&mut self) {
+        CreditRx::poll(self);
+    }
+
+    fn peek(&self) -> Option<u32> {
+        CreditRx::peek(self)
+    }
+
+    fn take(&mut self) -> Option<u32> {
+        CreditRx::take(self)
+    }
+
+    fn reset(&mut self) {
+        CreditRx::reset(self);
+    }
+}
+
+/// Sends `0..NUM_VALUES` as fast as the link allows
+pub struct Producer<L: LinkTx> {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    output: L,
+    next_value: u32,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl<L: LinkTx> Component for Producer<L> {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, "producer")
+            .unwrap();
+        self.output
+            .register_ports(&self.sim_manager, self.component_id, "output")
+            .unwrap();
+        self.sim_manager.register_do_not_end(self.component_id);
+    }
+
+    fn reset(&mut self) {
+        self.next_value = 0;
+        self.output.reset();
+    }
+
+    fn poll_recv(&mut self) {
+        self.output.poll();
+        if let Ok(event) = self.clock_receiver.try_recv() {
+            self.output.tick();
+            if self.next_value < NUM_VALUES && self.output.can_send() {
+                self.output.send(self.next_value).unwrap();
+                self.next_value += 1;
+            }
+            if self.output.get_transfer_count() == NUM_VALUES as u64 {
+                self.sim_manager.register_can_end(self.component_id);
+            }
+            self.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
+
+/// Takes a value every `period` cycles, until `NUM_VALUES` values are received
+pub struct Consumer<L: LinkRx> {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    input: L,
+    period: u64,
+    received: Arc<Mutex<Vec<u32>>>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl<L: LinkRx> Component for Consumer<L> {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, "consumer")
+            .unwrap();
+        self.input
+            .register_ports(&self.sim_manager, self.component_id, "input")
+            .unwrap();
+        self.sim_manager.register_do_not_end(self.component_id);
+    }
+
+    fn reset(&mut self) {
+        self.input.reset();
+        self.received.lock().unwrap().clear();
+    }
+
+    fn poll_recv(&mut self) {
+        self.input.poll();
+        if let Ok(event) = self.clock_receiver.try_recv() {
+            self.input.tick();
+            if self
+                .sim_manager
+                .get_curr_cycle()
+                .is_multiple_of(self.period as u128)
+            {
+                let peeked = self.input.peek();
+                let taken = self.input.take();
+                assert_eq!(peeked, taken);
+                if let Some(value) = taken {
+                    self.received.lock().unwrap().push(value);
+                }
+            }
+            if self.received.lock().unwrap().len() == NUM_VALUES as usize {
+                self.sim_manager.register_can_end(self.component_id);
+            }
+            self.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
+
+/// A consumer with the component id 1, pushing the values it takes to `received`
+pub fn new_consumer<L: LinkRx>(
+    sim_manager: &Arc<SimManager>,
+    ack_sender: &Sender<EventId>,
+    input: L,
+    period: u64,
+    received: &Arc<Mutex<Vec<u32>>>,
+) -> Arc<Mutex<Consumer<L>>> {
+    let clock_tick_channel = unbounded();
+    Arc::new(Mutex::new(Consumer {
+        component_id: 1,
+        sim_manager: sim_manager.clone(),
+        input,
+        period,
+        received: received.clone(),
+        clock_sender: clock_tick_channel.0,
+        clock_receiver: clock_tick_channel.1,
+        ack_sender: ack_sender.clone(),
+    }))
+}
+
+/// Runs a producer on `output` against a consumer on `input` taking a value every `period` cycles,
+/// returning the values received and the number of cycles
+pub fn run_transfers<T: LinkTx, R: LinkRx>(
+    sim_manager: &Arc<SimManager>,
+    ack_sender: &Sender<EventId>,
+    output: T,
+    input: R,
+    period: u64,
+) -> (Vec<u32>, u64) {
+    let clock_tick_channel = unbounded();
+    let producer = Arc::new(Mutex::new(Producer {
+        component_id: 0,
+        sim_manager: sim_manager.clone(),
+        output,
+        next_value: 0,
+        clock_sender: clock_tick_channel.0,
+        clock_receiver: clock_tick_channel.1,
+        ack_sender: ack_sender.clone(),
+    }));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let consumer = new_consumer(sim_manager, ack_sender, input, period, &received);
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(sim_manager), vec![producer]),
+        SimDispatcher::new(Arc::downgrade(sim_manager), vec![consumer]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+
+    assert!(sim_manager.get_protocol_violations().unwrap().is_empty());
+    sim_manager.check_protocols().unwrap();
+    let received = received.lock().unwrap().clone();
+    (received, sim_manager.get_curr_cycle() as u64)
+}
@@ -1,5 +1,11 @@
+#![allow(dead_code)]
+
+pub mod flow_control;
+pub mod simple_counter;
+pub mod simple_cpu;
 pub mod simple_event;
 pub mod simple_link;
 pub mod simple_loopback;
 pub mod simple_receiver;
 pub mod simple_sender;
+pub mod testbench;
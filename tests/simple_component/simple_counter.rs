@@ -0,0 +1,101 @@
+use crate::simple_component::simple_event::SimpleData;
+use crossbeam_channel::{unbounded, Sender};
+use rsim_core::component::Component;
+use rsim_core::error::SimError;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::stats::Counter;
+use rsim_core::tx::Tx;
+use rsim_core::types::{ComponentId, EventId, Input, Output};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+/// Same as `SimpleSender`, but written without `ComponentAttribute`
+/// so it can take part in checkpoints through `Component::save_state`
+pub struct SimpleCounter {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    num_packets: u128,
+    sent_count: u128,
+    sent_counter: Option<Counter>,
+    output: Tx<SimpleData>,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl SimpleCounter {
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        num_packets: u128,
+        output: Tx<SimpleData>,
+        ack_sender: Sender<EventId>,
+    ) -> Arc<Mutex<Self>> {
+        let clock_tick_channel = unbounded();
+        Arc::new(Mutex::new(SimpleCounter {
+            component_id,
+            sim_manager,
+            num_packets,
+            sent_count: 0,
+            sent_counter: None,
+            output,
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }))
+    }
+
+    fn on_clock(&mut self) {
+        if self.sent_count < self.num_packets {
+            let is_last = self.sent_count == self.num_packets - 1;
+            self.output
+                .send(SimpleData::new(self.sent_count, is_last), 10);
+            if let Some(sent_counter) = self.sent_counter.as_ref() {
+                sent_counter.increment();
+            }
+        } else {
+            self.sim_manager.register_can_end(self.component_id);
+        }
+
+        self.sent_count += 1;
+    }
+}
+
+impl Component for SimpleCounter {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, "counter")
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+        self.sim_manager.register_do_not_end(self.component_id);
+        self.sent_counter = self.sim_manager.register_counter("counter.packets").ok();
+    }
+
+    fn reset(&mut self) {
+        self.sent_count = 0;
+    }
+
+    fn poll_recv(&mut self) {
+        if let Ok(event) = self.clock_receiver.try_recv() {
+            self.on_clock();
+            self.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn save_state(&self) -> Result<Value, SimError> {
+        Ok(serde_json::to_value(self.sent_count)?)
+    }
+
+    fn restore_state(&mut self, state: Value) -> Result<(), SimError> {
+        self.sent_count = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use crossbeam_channel::{unbounded, Sender};
+use rsim_core::component::Component;
+use rsim_core::error::SimError;
+use rsim_core::gdb::GdbTarget;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::types::{ComponentId, EventId, Input, Output};
+use std::sync::{Arc, Mutex};
+
+pub const NUM_REGISTERS: usize = 4;
+pub const PC_REGISTER: usize = NUM_REGISTERS;
+
+/// A toy CPU taking two cycles per instruction,
+/// every instruction adds 1 to `r1`, stores `r1` at `r2` and moves to the next word
+pub struct SimpleCpu {
+    component_id: ComponentId,
+    sim_manager: Arc<SimManager>,
+    num_instructions: u64,
+    registers: [u32; NUM_REGISTERS],
+    pc: u32,
+    memory: Vec<u8>,
+    retired: u64,
+    in_flight: bool,
+    clock_sender: Output,
+    clock_receiver: Input,
+    ack_sender: Sender<EventId>,
+}
+
+impl SimpleCpu {
+    pub fn new(
+        component_id: ComponentId,
+        sim_manager: Arc<SimManager>,
+        num_instructions: u64,
+        ack_sender: Sender<EventId>,
+    ) -> Arc<Mutex<Self>> {
+        let clock_tick_channel = unbounded();
+        Arc::new(Mutex::new(SimpleCpu {
+            component_id,
+            sim_manager,
+            num_instructions,
+            registers: [0; NUM_REGISTERS],
+            pc: 0,
+            memory: vec![0; 64],
+            retired: 0,
+            in_flight: false,
+            clock_sender: clock_tick_channel.0,
+            clock_receiver: clock_tick_channel.1,
+            ack_sender,
+        }))
+    }
+
+    fn on_clock(&mut self) {
+        if self.retired == self.num_instructions {
+            self.sim_manager.register_can_end(self.component_id);
+            return;
+        }
+        if self.in_flight {
+            self.registers[1] = self.registers[1].wrapping_add(1);
+            let address = self.registers[2] as usize % self.memory.len();
+            self.memory[address] = self.registers[1] as u8;
+            self.pc += 4;
+            self.retired += 1;
+        }
+        self.in_flight = !self.in_flight;
+    }
+
+    fn check_range(&self, address: u64, len: usize) -> Result<usize, SimError> {
+        let address = address as usize;
+        if address + len > self.memory.len() {
+            return Err(SimError::GdbError(format!(
+                "address {:#x} out of range",
+                address
+            )));
+        }
+        Ok(address)
+    }
+}
+
+impl Component for SimpleCpu {
+    fn init(&mut self) {
+        self.sim_manager
+            .register_clock_tick(self.clock_sender.clone());
+        self.sim_manager
+            .register_component(self.component_id, "cpu")
+            .unwrap();
+        self.sim_manager.register_do_not_end(self.component_id);
+    }
+
+    fn reset(&mut self) {
+        self.registers = [0; NUM_REGISTERS];
+        self.pc = 0;
+        self.retired = 0;
+        self.in_flight = false;
+    }
+
+    fn poll_recv(&mut self) {
+        if let Ok(event) = self.clock_receiver.try_recv() {
+            self.on_clock();
+            self.ack_sender.send(event.get_event_id()).unwrap();
+        }
+    }
+
+    fn get_component_id(&self) -> ComponentId {
+        self.component_id
+    }
+}
+
+impl GdbTarget for SimpleCpu {
+    fn get_num_registers(&self) -> usize {
+        NUM_REGISTERS + 1
+    }
+
+    fn read_register(&self, register: usize) -> Result<u64, SimError> {
+        match register {
+            PC_REGISTER => Ok(self.pc as u64),
+            _ => self
+                .registers
+                .get(register)
+                .map(|value| *value as u64)
+                .ok_or(SimError::GdbError(format!("no register {}", register))),
+        }
+    }
+
+    fn write_register(&mut self, register: usize, value: u64) -> Result<(), SimError> {
+        match register {
+            PC_REGISTER => self.pc = value as u32,
+            _ => {
+                *self
+                    .registers
+                    .get_mut(register)
+                    .ok_or(SimError::GdbError(format!("no register {}", register)))? = value as u32
+            }
+        }
+        Ok(())
+    }
+
+    fn read_memory(&self, address: u64, len: usize) -> Result<Vec<u8>, SimError> {
+        let address = self.check_range(address, len)?;
+        Ok(self.memory[address..address + len].to_vec())
+    }
+
+    fn write_memory(&mut self, address: u64, data: &[u8]) -> Result<(), SimError> {
+        let address = self.check_range(address, data.len())?;
+        self.memory[address..address + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn get_pc(&self) -> u64 {
+        self.pc as u64
+    }
+
+    fn get_instructions_retired(&self) -> u64 {
+        self.retired
+    }
+}
@@ -1,8 +1,9 @@
 use rsim_core::event::{Event, EventValue};
 use rsim_core::types::{Cycle, EventId};
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct SimpleData {
     pub packet_id: u128,
     pub is_last: bool,
@@ -40,7 +40,17 @@ impl SimpleLink {
 }
 
 impl SimpleLink {
-    fn init_impl(&mut self) {}
+    fn init_impl(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, "link")
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "input", &self.input)
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
+    }
 
     fn reset_impl(&mut self) {}
 
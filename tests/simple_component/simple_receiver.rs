@@ -35,6 +35,12 @@ impl SimpleReceiver {
 
 impl SimpleReceiver {
     fn init_impl(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, "receiver")
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "input", &self.input)
+            .unwrap();
         self.sim_manager.register_do_not_end(self.component_id);
     }
 
@@ -46,6 +46,12 @@ impl SimpleSender {
 
 impl SimpleSender {
     fn init_impl(&mut self) {
+        self.sim_manager
+            .register_component(self.component_id, "sender")
+            .unwrap();
+        self.sim_manager
+            .register_port(self.component_id, "output", &self.output)
+            .unwrap();
         self.sim_manager.register_do_not_end(self.component_id);
     }
 
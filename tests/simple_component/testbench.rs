@@ -0,0 +1,41 @@
+use crossbeam_channel::Sender;
+use rsim_core::component::Component;
+use rsim_core::event::EventValue;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::types::{ComponentId, Cycle, EventId};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Holds the end of the simulation, the components of the library never hold it
+pub const TESTBENCH_ID: ComponentId = 99;
+
+pub fn new_tx<T: Default + Copy + Send + Sync + PartialEq + 'static + EventValue>(
+    sim_manager: &Arc<SimManager>,
+    ack_sender: &Sender<EventId>,
+) -> Tx<T> {
+    Tx::new(sim_manager.clone(), ack_sender.clone())
+}
+
+/// Runs `components` for `num_cycles` cycles, calling `check` at the end of every cycle
+pub fn run_cycles<F: FnMut(Cycle)>(
+    sim_manager: &Arc<SimManager>,
+    components: Vec<Arc<Mutex<dyn Component>>>,
+    num_cycles: Cycle,
+    mut check: F,
+) {
+    let sim_dispatcher = SimDispatcher::new(Arc::downgrade(sim_manager), components);
+    sim_dispatcher.init();
+    sim_manager.register_do_not_end(TESTBENCH_ID);
+    let thread_handler = thread::spawn(move || sim_dispatcher.run());
+
+    for cycle in 1..=num_cycles {
+        sim_manager.run_cycle().unwrap();
+        sim_manager.run_cycle_end().unwrap();
+        check(cycle);
+    }
+
+    sim_manager.register_can_end(TESTBENCH_ID);
+    thread_handler.join().unwrap();
+}
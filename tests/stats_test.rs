@@ -0,0 +1,96 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn stats_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        20,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+    assert!(sim_manager.register_counter("counter.packets").is_err());
+    let ipc = sim_manager.register_counter("ipc").unwrap();
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run().unwrap();
+    ipc.set(0.5);
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+
+    let stats = sim_manager.get_stats().unwrap();
+    assert_eq!(stats.cycles, sim_manager.get_curr_cycle());
+    assert_eq!(stats.events_delivered, stats.events_scheduled);
+    // the clock ticks of the last cycle may not be acknowledged once the simulation can end
+    assert!(stats.events_processed <= stats.events_delivered);
+    assert_eq!(
+        stats.events_processed as u128,
+        sim_manager.get_event_processed().unwrap()
+    );
+    assert_eq!(
+        stats.events_per_cycle.values().sum::<u64>() as u128,
+        stats.cycles
+    );
+    // the histograms only cover finished cycles, the clock ticks of the current one are left out
+    let histogram_events: u64 = stats
+        .events_per_cycle
+        .iter()
+        .map(|(events, cycles)| events * cycles)
+        .sum();
+    assert_eq!(histogram_events + 1, stats.events_delivered);
+    assert_eq!(
+        stats.delta_iterations_per_cycle.values().sum::<u64>() as u128,
+        stats.cycles
+    );
+    // every packet spends 10 cycles in the event queue
+    assert!(stats.peak_event_q_depth >= 10);
+    assert!(stats.peak_rob_size >= 1);
+    assert!(stats.cycles_per_second > 0.0);
+    assert_eq!(stats.counters["counter.packets"], 20.0);
+    assert_eq!(stats.counters["ipc"], 0.5);
+    assert!(stats.to_string().contains("counter.packets: 20"));
+}
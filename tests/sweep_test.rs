@@ -0,0 +1,149 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::error::SimError;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::stats::SimStats;
+use rsim_core::sweep::{ParameterGrid, Sweep, SweepPoint, SweepReport};
+use rsim_core::tx::Tx;
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+
+/// Builds the counter, link and receiver for a point, on one or several dispatchers
+fn simulate(point: &SweepPoint) -> Result<SimStats, SimError> {
+    let num_packets = point.get_u64("num_packets")? as u128;
+    if num_packets == 0 {
+        return Err(SimError::SweepError("no packets to send".to_string()));
+    }
+    let split = point.get_bool("split")?;
+
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        num_packets,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = if split {
+        vec![
+            SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+            SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+        ]
+    } else {
+        vec![SimDispatcher::new(
+            Arc::downgrade(&sim_manager),
+            vec![counter, link, receiver],
+        )]
+    };
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    sim_manager.run()?;
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+    sim_manager.get_stats()
+}
+
+#[test]
+fn sweep_test() {
+    let grid = ParameterGrid::new()
+        .with_parameter("num_packets", vec![0, 5, 20])
+        .with_parameter("split", vec![false, true]);
+    let points = grid.get_points();
+    assert_eq!(points.len(), 6);
+    assert_eq!(points[1].get_u64("num_packets").unwrap(), 0);
+    assert!(points[1].get_bool("split").unwrap());
+    assert!(points[1].get_u64("split").is_err());
+    assert!(points[1].get("depth").is_err());
+
+    let report = Sweep::new(grid).with_threads(4).run(simulate);
+    assert_eq!(report.results.len(), 6);
+    for (result, point) in report.results.iter().zip(points.iter()) {
+        assert_eq!(&result.point, point);
+        let num_packets = point.get_u64("num_packets").unwrap();
+        if num_packets == 0 {
+            assert!(result.stats.is_none());
+            assert_eq!(
+                result.error.as_deref(),
+                Some("SweepError: no packets to send")
+            );
+            continue;
+        }
+        let stats = result.stats.as_ref().unwrap();
+        assert_eq!(stats.counters["counter.packets"], num_packets as f64);
+        // the counter stops sending then lets the simulation end one cycle later
+        assert!(stats.cycles > num_packets as u128);
+    }
+
+    let json_path = std::env::temp_dir().join("rsim_sweep_test.json");
+    report.save_json(&json_path).unwrap();
+    let saved: SweepReport =
+        serde_json::from_reader(std::fs::File::open(&json_path).unwrap()).unwrap();
+    assert_eq!(saved.results.len(), report.results.len());
+
+    let csv_path = std::env::temp_dir().join("rsim_sweep_test.csv");
+    report.save_csv(&csv_path).unwrap();
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert!(lines[0].starts_with("num_packets,split,cycles,"));
+    assert!(lines[0].ends_with(",counter.packets,error"));
+    assert!(lines[1].starts_with("0,false,,"));
+    assert!(lines[1].ends_with(",SweepError: no packets to send"));
+    assert!(lines[6].starts_with("20,true,"));
+    assert!(lines[6].ends_with(",20,"));
+
+    let _ = std::fs::remove_file(json_path);
+    let _ = std::fs::remove_file(csv_path);
+}
+
+#[test]
+fn sweep_panic_test() {
+    let grid = ParameterGrid::new().with_parameter("num_packets", vec![1, 2, 3]);
+    let report = Sweep::new(grid).with_threads(2).run(|point| {
+        if point.get_u64("num_packets")? == 2 {
+            panic!("2 packets");
+        }
+        Ok(SimStats::default())
+    });
+
+    // the other simulations are kept
+    assert_eq!(report.results.len(), 3);
+    assert!(report.results[0].stats.is_some());
+    assert!(report.results[1].stats.is_none());
+    assert_eq!(report.results[1].error.as_deref(), Some("panic: 2 packets"));
+    assert!(report.results[2].stats.is_some());
+}
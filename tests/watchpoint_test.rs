@@ -0,0 +1,110 @@
+mod simple_component;
+
+use crossbeam_channel::unbounded;
+use rsim_core::sim_dispatcher::SimDispatcher;
+use rsim_core::sim_manager::SimManager;
+use rsim_core::tx::Tx;
+use rsim_core::watchpoint::{CompareOp, StopReason, WatchCondition};
+use simple_component::simple_counter::SimpleCounter;
+use simple_component::simple_link::SimpleLink;
+use simple_component::simple_receiver::SimpleReceiver;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn watchpoint_test() {
+    let ack_channel = unbounded();
+
+    let sim_manager = SimManager::new(ack_channel.1);
+
+    let mut counter_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let mut link_output = Tx::new(sim_manager.clone(), ack_channel.0.clone());
+    let link_input = counter_output.add_rx();
+    let receiver_input = link_output.add_rx();
+
+    let counter = SimpleCounter::new(
+        0,
+        sim_manager.clone(),
+        20,
+        counter_output,
+        ack_channel.0.clone(),
+    );
+    let link = SimpleLink::new(
+        1,
+        sim_manager.clone(),
+        link_input,
+        link_output,
+        ack_channel.0.clone(),
+    );
+    let receiver = SimpleReceiver::new(
+        2,
+        sim_manager.clone(),
+        receiver_input,
+        ack_channel.0.clone(),
+    );
+
+    let sim_dispatchers = vec![
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![counter]),
+        SimDispatcher::new(Arc::downgrade(&sim_manager), vec![link, receiver]),
+    ];
+    sim_dispatchers.iter().for_each(|s| s.init());
+
+    let mut thread_handlers = vec![];
+    for sim_dispatcher in sim_dispatchers {
+        thread_handlers.push(thread::spawn(move || sim_dispatcher.run()));
+    }
+
+    assert!(sim_manager
+        .add_watchpoint("receiver.input", WatchCondition::AnyWrite)
+        .is_err());
+    assert!(sim_manager
+        .add_watchpoint("nothing.output", WatchCondition::AnyWrite)
+        .is_err());
+
+    let value_watchpoint = sim_manager
+        .add_watchpoint(
+            "counter.output",
+            WatchCondition::Compare(
+                CompareOp::Eq,
+                "SimpleData { packet_id: 5, is_last: false }".to_string(),
+            ),
+        )
+        .unwrap();
+    let write_watchpoint = sim_manager
+        .add_watchpoint("link.output", WatchCondition::AnyWrite)
+        .unwrap();
+    sim_manager
+        .set_watchpoint_enabled(write_watchpoint, false)
+        .unwrap();
+    assert_eq!(sim_manager.get_watchpoints().unwrap().len(), 2);
+
+    let StopReason::Watchpoint(hits) = sim_manager.run().unwrap() else {
+        panic!("expected the value watchpoint to stop the simulation");
+    };
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].id, value_watchpoint);
+    assert_eq!(hits[0].port, "counter.output");
+    assert_eq!(hits[0].cycle, sim_manager.get_curr_cycle());
+    assert_eq!(hits[0].value, "SimpleData { packet_id: 5, is_last: false }");
+    let stop_cycle = sim_manager.get_curr_cycle();
+
+    sim_manager.remove_watchpoint(value_watchpoint).unwrap();
+    assert!(sim_manager.remove_watchpoint(value_watchpoint).is_err());
+    sim_manager
+        .set_watchpoint_enabled(write_watchpoint, true)
+        .unwrap();
+
+    let StopReason::Watchpoint(hits) = sim_manager.run().unwrap() else {
+        panic!("expected the write watchpoint to stop the simulation");
+    };
+    assert_eq!(hits[0].id, write_watchpoint);
+    assert!(hits[0].cycle > stop_cycle);
+
+    sim_manager.remove_watchpoint(write_watchpoint).unwrap();
+    assert_eq!(sim_manager.run().unwrap(), StopReason::SimEnd);
+    assert!(sim_manager.sim_can_end());
+
+    thread_handlers.into_iter().for_each(|h| {
+        h.join().unwrap();
+    });
+}